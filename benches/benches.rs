@@ -43,6 +43,65 @@ use collatz_dfs::CollatzNode;
 //     }
 // }
 
+#[cfg(feature = "sync")]
+mod heavy {
+    use super::CollatzNode;
+    use par_dfs::sync::{ExtendQueue, FastNode};
+    use std::hash::{Hash, Hasher};
+
+    /// Size of the payload that is copied on every clone.
+    const PAYLOAD_SIZE: usize = 1024;
+
+    /// A [`CollatzNode`] that carries a payload that is costly to clone.
+    #[derive(Clone, Debug)]
+    pub struct HeavyNode {
+        node: CollatzNode,
+        // only ever cloned
+        #[allow(dead_code)]
+        payload: Vec<u8>,
+    }
+
+    impl From<u32> for HeavyNode {
+        fn from(n: u32) -> Self {
+            Self {
+                node: CollatzNode(n),
+                payload: vec![0; PAYLOAD_SIZE],
+            }
+        }
+    }
+
+    impl Hash for HeavyNode {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.node.hash(state);
+        }
+    }
+
+    impl PartialEq for HeavyNode {
+        fn eq(&self, other: &Self) -> bool {
+            self.node == other.node
+        }
+    }
+
+    impl Eq for HeavyNode {}
+
+    impl FastNode for HeavyNode {
+        type Error = std::convert::Infallible;
+
+        #[inline]
+        fn add_children<E>(&self, _depth: usize, queue: &mut E) -> Result<(), Self::Error>
+        where
+            E: ExtendQueue<Self, Self::Error>,
+        {
+            let children = self
+                .node
+                .collatz_children()
+                .map(|child| child.map(|child| Self::from(child.0)));
+            queue.add_all(children);
+            Ok(())
+        }
+    }
+}
+
 #[cfg(feature = "sync")]
 const SYNC_LIMIT: Option<usize> = Some(100);
 #[cfg(feature = "async")]
@@ -158,6 +217,20 @@ bench_collatz_async!(
     par_dfs::r#async::Bfs::<CollatzNode>::new(black_box(START), ASYNC_LIMIT, ALLOW_CIRCLES)
 );
 
+#[cfg(feature = "async")]
+bench_collatz_async!(
+    bench_collatz_async_fast_dfs:
+    "collatz/async/fastdfs",
+    par_dfs::r#async::FastDfs::<CollatzNode>::new(black_box(START), ASYNC_LIMIT, ALLOW_CIRCLES)
+);
+
+#[cfg(feature = "async")]
+bench_collatz_async!(
+    bench_collatz_async_fast_bfs:
+    "collatz/async/fastbfs",
+    par_dfs::r#async::FastBfs::<CollatzNode>::new(black_box(START), ASYNC_LIMIT, ALLOW_CIRCLES)
+);
+
 #[cfg(feature = "sync")]
 bench_collatz_sync!(
     bench_collatz_sync_fast_bfs:
@@ -186,6 +259,24 @@ bench_collatz_sync!(
     par_dfs::sync::Dfs::<CollatzNode>::new(black_box(START), SYNC_LIMIT, ALLOW_CIRCLES)
 );
 
+#[cfg(feature = "sync")]
+bench_collatz_sync!(
+    bench_collatz_sync_fast_dfs_visited:
+    "collatz/sync/fastdfs-visited",
+    par_dfs::sync::FastDfs::<CollatzNode>::new(black_box(START), SYNC_LIMIT, false)
+);
+
+#[cfg(feature = "sync")]
+bench_collatz_sync!(
+    bench_collatz_sync_fast_dfs_visited_fxhash:
+    "collatz/sync/fastdfs-visited-fxhash",
+    par_dfs::sync::FastDfs::<CollatzNode, _>::with_hasher(
+        black_box(START),
+        SYNC_LIMIT,
+        rustc_hash::FxBuildHasher,
+    )
+);
+
 #[cfg(feature = "sync")]
 bench_collatz_sync!(
     bench_collatz_sync_custom_dfs:
@@ -193,11 +284,20 @@ bench_collatz_sync!(
     collatz_dfs::CollatzDfs::new(black_box(START), SYNC_LIMIT, ALLOW_CIRCLES)
 );
 
+#[cfg(feature = "sync")]
+bench_collatz_sync!(
+    bench_heavy_sync_fast_dfs:
+    "heavy/sync/fastdfs",
+    par_dfs::sync::FastDfs::<heavy::HeavyNode>::new(black_box(START), SYNC_LIMIT, false)
+);
+
 #[cfg(feature = "async")]
 criterion_group!(
     collatz_async,
     bench_collatz_async_bfs,
+    bench_collatz_async_fast_bfs,
     bench_collatz_async_dfs,
+    bench_collatz_async_fast_dfs,
 );
 
 #[cfg(feature = "sync")]
@@ -207,7 +307,10 @@ criterion_group!(
     bench_collatz_sync_fast_bfs,
     bench_collatz_sync_dfs,
     bench_collatz_sync_fast_dfs,
-    bench_collatz_sync_custom_dfs
+    bench_collatz_sync_fast_dfs_visited,
+    bench_collatz_sync_fast_dfs_visited_fxhash,
+    bench_collatz_sync_custom_dfs,
+    bench_heavy_sync_fast_dfs
 );
 
 fn main() {
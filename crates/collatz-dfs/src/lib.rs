@@ -1,52 +1,85 @@
+use num_traits::{PrimInt, Unsigned};
 use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
 use std::iter::Iterator;
+use std::sync::{Arc, RwLock};
 
 type Queue = VecDeque<(usize, Result<u32, std::convert::Infallible>)>;
 
+/// The bounds on the integer type of a [`CollatzNode`].
+pub trait CollatzInt: PrimInt + Unsigned + Hash + std::fmt::Debug + Send + Sync + 'static {}
+
+impl<T> CollatzInt for T where T: PrimInt + Unsigned + Hash + std::fmt::Debug + Send + Sync + 'static
+{}
+
+/// Converts the small constant `n` into `T`.
+#[inline]
+fn constant<T: CollatzInt>(n: u8) -> T {
+    T::from(n).expect("every unsigned integer type holds small constants")
+}
+
+/// A number in the graph of [Collatz] orbits, whose children are its predecessors.
+///
+/// Generic over the unsigned integer type `T`, which defaults to [`u32`].
+/// Predecessors that overflow `T` are omitted.
+///
+/// [Collatz]: https://en.wikipedia.org/wiki/Collatz_conjecture
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct CollatzNode(pub u32);
+pub struct CollatzNode<T = u32>(pub T);
 
-impl From<CollatzNode> for u32 {
-    #[inline]
-    fn from(n: CollatzNode) -> Self {
-        n.0
-    }
+macro_rules! impl_into_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<CollatzNode<$int>> for $int {
+                #[inline]
+                fn from(n: CollatzNode<$int>) -> Self {
+                    n.0
+                }
+            }
+        )*
+    };
 }
 
-impl From<u32> for CollatzNode {
+impl_into_int!(u8, u16, u32, u64, u128, usize);
+
+impl<T> From<T> for CollatzNode<T> {
     #[inline]
-    fn from(n: u32) -> Self {
+    fn from(n: T) -> Self {
         Self(n)
     }
 }
 
-impl CollatzNode {
+impl<T> CollatzNode<T>
+where
+    T: CollatzInt,
+{
     #[inline]
-    pub fn collatz_children(
-        &self,
-    ) -> impl Iterator<Item = Result<CollatzNode, std::convert::Infallible>> {
+    pub fn collatz_children(&self) -> impl Iterator<Item = Result<Self, std::convert::Infallible>> {
         let n = self.0;
         let mut children = vec![];
 
         // n can be reached by dividing by two
         // as long as it doesn't overflow
-        if let Some(even) = n.checked_mul(2) {
+        if let Some(even) = n.checked_mul(&constant(2)) {
             children.push(even);
         }
 
         // n can be reached by 3x + 1 iff (n - 1) / 3 is an odd integer
-        if n > 4 && n % 6 == 4 {
-            children.push((n - 1) / 3);
+        if n > constant(4) && n % constant(6) == constant(4) {
+            children.push((n - T::one()) / constant(3));
         }
         children.into_iter().map(Self).map(Result::Ok)
     }
 }
 
 mod sync_collatz {
-    use super::CollatzNode;
+    use super::{constant, CollatzInt, CollatzNode};
     use par_dfs::sync::{ExtendQueue, FastNode, Node, NodeIter};
 
-    impl FastNode for CollatzNode {
+    impl<T> FastNode for CollatzNode<T>
+    where
+        T: CollatzInt,
+    {
         type Error = std::convert::Infallible;
 
         #[inline]
@@ -58,41 +91,215 @@ mod sync_collatz {
 
             // n can be reached by dividing by two
             // as long as it doesn't overflow
-            if let Some(even) = n.checked_mul(2) {
+            if let Some(even) = n.checked_mul(&constant(2)) {
                 queue.add(Ok(Self(even)));
             }
 
             // n can be reached by 3x + 1 iff (n - 1) / 3 is an odd integer
-            if n > 4 && n % 6 == 4 {
-                queue.add(Ok(Self((n - 1) / 3)));
+            if n > constant(4) && n % constant(6) == constant(4) {
+                queue.add(Ok(Self((n - T::one()) / constant(3))));
             }
             Ok(())
         }
     }
 
-    impl Node for CollatzNode {
+    impl<T> Node for CollatzNode<T>
+    where
+        T: CollatzInt,
+    {
         type Error = std::convert::Infallible;
 
         #[inline]
         fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
             Ok(Box::new(self.collatz_children()))
         }
+
+        #[inline]
+        fn children_into<E>(&self, depth: usize, queue: &mut E) -> Result<(), Self::Error>
+        where
+            E: ExtendQueue<Self, Self::Error>,
+        {
+            // push the children directly, without allocating an iterator
+            FastNode::add_children(self, depth, queue)
+        }
     }
 }
 
 mod async_collatz {
-    use super::CollatzNode;
+    use super::{CollatzInt, CollatzNode};
     use futures::StreamExt;
-    use par_dfs::r#async::{Node, NodeStream};
+    use par_dfs::r#async::{ExtendQueue, FastNode, Node, NodeStream};
     use std::sync::Arc;
 
     #[async_trait::async_trait]
-    impl Node for CollatzNode {
+    impl<T> FastNode for CollatzNode<T>
+    where
+        T: CollatzInt,
+    {
+        type Error = std::convert::Infallible;
+
+        #[inline]
+        async fn add_children<Q>(
+            self: Arc<Self>,
+            _depth: usize,
+            queue: &mut Q,
+        ) -> Result<(), Self::Error>
+        where
+            Q: ExtendQueue<Self, Self::Error> + Send,
+        {
+            queue.add_all(self.collatz_children());
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl<T> Node for CollatzNode<T>
+    where
+        T: CollatzInt,
+    {
         type Error = std::convert::Infallible;
 
         #[inline]
         async fn children(
             self: Arc<Self>,
+            depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            (*self).children_owned(depth).await
+        }
+
+        #[inline]
+        async fn children_owned(
+            self,
+            _depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            // the node is copied, so it is never wrapped in an Arc
+            let stream = futures::stream::iter(self.collatz_children()).boxed();
+            Ok(Box::pin(stream))
+        }
+    }
+}
+
+/// A number in its forward [Collatz] orbit, whose only child is the next number.
+///
+/// The next number of `n` is `n / 2` if `n` is even and `3n + 1` otherwise.
+/// The orbit ends at 1, which has no child, so the depth of 1 in a traversal
+/// is the total stopping time of the root.
+/// A number whose successor overflows [`u32`] has no child either.
+///
+/// [Collatz]: https://en.wikipedia.org/wiki/Collatz_conjecture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollatzForwardNode(pub u32);
+
+impl From<CollatzForwardNode> for u32 {
+    #[inline]
+    fn from(n: CollatzForwardNode) -> Self {
+        n.0
+    }
+}
+
+impl From<u32> for CollatzForwardNode {
+    #[inline]
+    fn from(n: u32) -> Self {
+        Self(n)
+    }
+}
+
+impl CollatzForwardNode {
+    /// Returns the next number of the orbit, or [`None`] at 1 or on overflow.
+    #[inline]
+    #[must_use]
+    pub fn successor(&self) -> Option<Self> {
+        let n = self.0;
+        match n {
+            0 | 1 => None,
+            n if n % 2 == 0 => Some(Self(n / 2)),
+            n => n.checked_mul(3).and_then(|n| n.checked_add(1)).map(Self),
+        }
+    }
+
+    #[inline]
+    pub fn collatz_children(&self) -> impl Iterator<Item = Result<Self, std::convert::Infallible>> {
+        self.successor().into_iter().map(Result::Ok)
+    }
+}
+
+mod sync_collatz_forward {
+    use super::CollatzForwardNode;
+    use par_dfs::sync::{ExtendQueue, FastNode, Node, NodeIter};
+
+    impl FastNode for CollatzForwardNode {
+        type Error = std::convert::Infallible;
+
+        #[inline]
+        fn add_children<E>(&self, _depth: usize, queue: &mut E) -> Result<(), Self::Error>
+        where
+            E: ExtendQueue<Self, Self::Error>,
+        {
+            if let Some(next) = self.successor() {
+                queue.add(Ok(next));
+            }
+            Ok(())
+        }
+    }
+
+    impl Node for CollatzForwardNode {
+        type Error = std::convert::Infallible;
+
+        #[inline]
+        fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+            Ok(Box::new(self.collatz_children()))
+        }
+
+        #[inline]
+        fn children_into<E>(&self, depth: usize, queue: &mut E) -> Result<(), Self::Error>
+        where
+            E: ExtendQueue<Self, Self::Error>,
+        {
+            // push the child directly, without allocating an iterator
+            FastNode::add_children(self, depth, queue)
+        }
+    }
+}
+
+mod async_collatz_forward {
+    use super::CollatzForwardNode;
+    use futures::StreamExt;
+    use par_dfs::r#async::{ExtendQueue, FastNode, Node, NodeStream};
+    use std::sync::Arc;
+
+    #[async_trait::async_trait]
+    impl FastNode for CollatzForwardNode {
+        type Error = std::convert::Infallible;
+
+        #[inline]
+        async fn add_children<Q>(
+            self: Arc<Self>,
+            _depth: usize,
+            queue: &mut Q,
+        ) -> Result<(), Self::Error>
+        where
+            Q: ExtendQueue<Self, Self::Error> + Send,
+        {
+            queue.add_all(self.collatz_children());
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Node for CollatzForwardNode {
+        type Error = std::convert::Infallible;
+
+        #[inline]
+        async fn children(
+            self: Arc<Self>,
+            depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            (*self).children_owned(depth).await
+        }
+
+        #[inline]
+        async fn children_owned(
+            self,
             _depth: usize,
         ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
             let stream = futures::stream::iter(self.collatz_children()).boxed();
@@ -106,43 +313,68 @@ mod async_collatz {
 ///
 /// [Collatz]: https://en.wikipedia.org/wiki/Collatz_conjecture
 /// [graph]: https://en.wikipedia.org/wiki/File:Collatz_orbits_of_the_all_integers_up_to_1000.svg
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct CollatzDfs {
     max_depth: Option<usize>,
     queue: Queue,
-    visited: HashSet<u32>,
+    /// Shared by all splits, so that no number is yielded by more than one split.
+    visited: Arc<RwLock<HashSet<u32>>>,
     allow_circles: bool,
 }
 
+impl Clone for CollatzDfs {
+    fn clone(&self) -> Self {
+        // a clone is an independent traversal, unlike a split
+        Self {
+            max_depth: self.max_depth,
+            queue: self.queue.clone(),
+            visited: Arc::new(RwLock::new(self.visited.read().unwrap().clone())),
+            allow_circles: self.allow_circles,
+        }
+    }
+}
+
 impl CollatzDfs {
     pub fn new<D: Into<Option<usize>>>(start: u32, max_depth: D, allow_circles: bool) -> Self {
         Self {
             max_depth: max_depth.into(),
             queue: VecDeque::from_iter([(0, Ok(start))]),
-            visited: HashSet::from_iter([start]),
+            visited: Arc::new(RwLock::new(HashSet::from_iter([start]))),
             allow_circles,
         }
     }
-}
 
-impl Iterator for CollatzDfs {
-    type Item = Result<u32, std::convert::Infallible>;
+    /// Marks `n` as visited, returning whether it was not visited before.
+    #[inline]
+    fn visit(&self, n: u32) -> bool {
+        if self.allow_circles {
+            return true;
+        }
+        // most numbers are only checked, so the exclusive lock is only taken
+        // to insert, which also decides which split gets to yield a number
+        !self.visited.read().unwrap().contains(&n) && self.visited.write().unwrap().insert(n)
+    }
+
+    /// Yields every number together with its depth, i.e. the number of
+    /// Collatz steps from it to the starting point, which is at depth 0.
+    #[must_use]
+    pub fn with_depth(self) -> WithDepth {
+        WithDepth(self)
+    }
 
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
+    fn next_with_depth(&mut self) -> Option<Result<(usize, u32), std::convert::Infallible>> {
         match self.queue.pop_back() {
             Some((depth, Ok(n))) => {
-                self.visited.insert(n);
-
                 if let Some(max_depth) = self.max_depth {
                     if depth >= max_depth {
-                        return Some(Ok(n));
+                        return Some(Ok((depth, n)));
                     }
                 }
                 // n can be reached by dividing by two
                 // as long as it doesn't overflow
                 if let Some(even) = n.checked_mul(2) {
-                    if self.allow_circles || !self.visited.contains(&even) {
+                    if self.visit(even) {
                         self.queue.push_back((depth + 1, Ok(even)));
                     }
                 }
@@ -150,31 +382,37 @@ impl Iterator for CollatzDfs {
                 // n can be reached by 3x + 1 iff (n - 1) / 3 is an odd integer
                 if n > 4 && n % 6 == 4 {
                     let odd = (n - 1) / 3;
-                    if self.allow_circles || !self.visited.contains(&odd) {
+                    if self.visit(odd) {
                         self.queue.push_back((depth + 1, Ok(odd)));
                     }
                 }
-                Some(Ok(n))
+                Some(Ok((depth, n)))
             }
-            Some((_, n)) => Some(n),
+            Some((_, Err(err))) => Some(Err(err)),
             None => None,
         }
     }
 }
 
+impl Iterator for CollatzDfs {
+    type Item = Result<u32, std::convert::Infallible>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_depth().map(|item| item.map(|(_, n)| n))
+    }
+}
+
 impl par_dfs::sync::par::SplittableIterator for CollatzDfs {
     fn split(&mut self) -> Option<Self> {
         let len = self.queue.len();
         if len >= 2 {
             let split = self.queue.split_off(len / 2);
-            // cannot avoid circles when running in parallel
-            self.visited.clear();
-            self.allow_circles = true;
             Some(Self {
                 queue: split,
                 max_depth: self.max_depth,
-                visited: HashSet::new(),
-                allow_circles: true,
+                visited: Arc::clone(&self.visited),
+                allow_circles: self.allow_circles,
             })
         } else {
             None
@@ -182,6 +420,27 @@ impl par_dfs::sync::par::SplittableIterator for CollatzDfs {
     }
 }
 
+/// A [`CollatzDfs`] that yields every number together with its depth.
+///
+/// See [`CollatzDfs::with_depth`].
+#[derive(Clone, Debug)]
+pub struct WithDepth(CollatzDfs);
+
+impl Iterator for WithDepth {
+    type Item = Result<(usize, u32), std::convert::Infallible>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_with_depth()
+    }
+}
+
+impl par_dfs::sync::par::SplittableIterator for WithDepth {
+    fn split(&mut self) -> Option<Self> {
+        self.0.split().map(Self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -190,13 +449,136 @@ mod tests {
         let limit = 10;
         let allow_circles = false;
 
+        // unlike the traversals of par_dfs, the plain iterator also yields the start
+        // and pops the children in reverse order
         let plain: Vec<_> = super::CollatzDfs::new(start, limit, allow_circles)
+            .skip(1)
             .map(Result::ok)
             .collect();
         let sync: Vec<_> =
             par_dfs::sync::FastDfs::<super::CollatzNode>::new(start, limit, allow_circles)
+                .preserve_child_order(false)
                 .map(|n| n.ok().map(Into::into))
                 .collect();
         similar_asserts::assert_eq!(plain, sync);
     }
+
+    #[test]
+    fn test_collatz_with_depth() {
+        let start = 1;
+        let limit = 10;
+
+        let plain: Vec<_> = super::CollatzDfs::new(start, limit, false)
+            .with_depth()
+            .map(Result::unwrap)
+            .collect();
+        similar_asserts::assert_eq!(plain.first(), Some(&(0, start)));
+        let sync: Vec<_> = par_dfs::sync::FastDfs::<super::CollatzNode>::new(start, limit, false)
+            .preserve_child_order(false)
+            .with_depth()
+            .map(|n| n.map(|(depth, n)| (depth, n.into())).unwrap())
+            .collect();
+        similar_asserts::assert_eq!(plain[1..], sync);
+
+        // 3 -> 10 -> 5 -> 16 -> 8 -> 4 -> 2 -> 1
+        let depth = super::CollatzDfs::new(1, None, false)
+            .with_depth()
+            .find_map(|n| n.ok().filter(|(_, n)| *n == 3))
+            .map(|(depth, _)| depth);
+        similar_asserts::assert_eq!(depth, Some(7));
+    }
+
+    #[test]
+    fn test_collatz_parallel_dedup() {
+        use par_dfs::sync::par::ParallelSplittableIterator;
+        use rayon::iter::ParallelIterator;
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let mut sequential: Vec<u32> = super::CollatzDfs::new(1, 30, false)
+            .map(Result::unwrap)
+            .collect();
+        sequential.sort_unstable();
+        for _ in 0..10 {
+            let dfs = super::CollatzDfs::new(1, 30, false);
+            let mut parallel: Vec<u32> = pool.install(|| {
+                ParallelSplittableIterator::new(dfs)
+                    .map(Result::unwrap)
+                    .collect()
+            });
+            parallel.sort_unstable();
+            similar_asserts::assert_eq!(parallel, sequential);
+        }
+
+        // a clone does not share the visited numbers
+        let mut dfs = super::CollatzDfs::new(1, 5, false);
+        let clone = dfs.clone();
+        assert!(dfs.by_ref().count() > 1);
+        similar_asserts::assert_eq!(clone.count(), super::CollatzDfs::new(1, 5, false).count());
+    }
+
+    #[test]
+    fn test_collatz_forward_reaches_one() {
+        use super::CollatzForwardNode;
+        use par_dfs::sync::{Dfs, FastDfs};
+
+        // known total stopping times, i.e. the number of steps to reach 1
+        for (start, stopping_time) in [(1, 0), (2, 1), (3, 7), (7, 16), (27, 111), (97, 118)] {
+            let orbit: Vec<u32> = Dfs::<CollatzForwardNode>::new(start, None, false)
+                .map(|n| n.map(u32::from))
+                .collect::<Result<_, _>>()
+                .unwrap();
+            similar_asserts::assert_eq!(orbit.len(), stopping_time);
+            if stopping_time > 0 {
+                similar_asserts::assert_eq!(orbit.last(), Some(&1));
+            }
+
+            let fast = FastDfs::<CollatzForwardNode>::new(start, None, false)
+                .with_depth()
+                .last()
+                .transpose()
+                .unwrap();
+            similar_asserts::assert_eq!(
+                fast.map(|(depth, n)| (depth, n.0)),
+                (stopping_time > 0).then_some((stopping_time, 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_collatz_wide_integers() {
+        use super::CollatzNode;
+        use par_dfs::sync::FastDfs;
+
+        // the same predecessors, as long as none overflows
+        let narrow: Vec<u64> = FastDfs::<CollatzNode>::new(1, 20, false)
+            .map(|n| n.map(|n| u32::from(n).into()))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let wide: Vec<u64> = FastDfs::<CollatzNode<u64>>::new(1, 20, false)
+            .map(|n| n.map(u64::from))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        similar_asserts::assert_eq!(narrow, wide);
+
+        // doubling 2^31 overflows u32, but not u64
+        let start = 1 << 31;
+        let narrow = FastDfs::<CollatzNode>::new(start, 1, false).count();
+        similar_asserts::assert_eq!(narrow, 0);
+        let wide: Vec<u64> = FastDfs::<CollatzNode<u64>>::new(u64::from(start), 1, false)
+            .map(|n| n.map(u64::from))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        similar_asserts::assert_eq!(wide, [1 << 32]);
+
+        // 2^100 is 4 mod 6, so it also has an odd predecessor
+        let start = 1u128 << 100;
+        let wide: Vec<u128> = FastDfs::<CollatzNode<u128>>::new(start, 1, false)
+            .map(|n| n.map(u128::from))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        similar_asserts::assert_eq!(wide, [start * 2, (start - 1) / 3]);
+    }
 }
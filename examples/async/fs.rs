@@ -13,14 +13,48 @@ mod sealed {
     #[derive(Hash, PartialEq, Eq, Clone, Debug)]
     pub enum FsNode {
         File(PathBuf),
-        Dir(PathBuf),
+        Dir {
+            path: PathBuf,
+            follow_symlinks: bool,
+        },
+        /// A symlink that is not followed.
+        Symlink(PathBuf),
     }
 
     impl FsNode {
-        pub fn from_type<P: Into<PathBuf>>(path: P, file_type: FileType) -> Result<Self> {
+        /// Creates the root node for `path`, following it if it is a symlink.
+        pub async fn root<P: Into<PathBuf>>(path: P, follow_symlinks: bool) -> Result<Self> {
             let path = path.into();
+            let file_type = fs::metadata(&path).await?.file_type();
+            Self::from_type(path, file_type, follow_symlinks).await
+        }
+
+        /// Creates a node for `path` of type `file_type`.
+        ///
+        /// When following symlinks, paths are canonicalized, so that they can
+        /// be used to detect directories that were already visited through
+        /// another link, which would otherwise loop forever.
+        pub async fn from_type<P: Into<PathBuf>>(
+            path: P,
+            file_type: FileType,
+            follow_symlinks: bool,
+        ) -> Result<Self> {
+            let path = path.into();
+            if file_type.is_symlink() && !follow_symlinks {
+                return Ok(Self::Symlink(path));
+            }
+            let (path, file_type) = if follow_symlinks {
+                let path = fs::canonicalize(path).await?;
+                let file_type = fs::metadata(&path).await?.file_type();
+                (path, file_type)
+            } else {
+                (path, file_type)
+            };
             if file_type.is_dir() {
-                Ok(Self::Dir(path))
+                Ok(Self::Dir {
+                    path,
+                    follow_symlinks,
+                })
             } else if file_type.is_file() {
                 Ok(Self::File(path))
             } else {
@@ -33,15 +67,6 @@ mod sealed {
         }
     }
 
-    impl TryFrom<PathBuf> for FsNode {
-        type Error = anyhow::Error;
-
-        fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
-            let file_type = path.metadata()?.file_type();
-            Self::from_type(path, file_type)
-        }
-    }
-
     #[async_trait]
     impl Node for FsNode {
         type Error = anyhow::Error;
@@ -51,12 +76,16 @@ mod sealed {
             _depth: usize,
         ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
             let children = match self.as_ref() {
-                FsNode::File(_) => {
+                FsNode::File(_) | FsNode::Symlink(_) => {
                     // no children
                     futures::stream::empty().boxed()
                 }
-                FsNode::Dir(path) => {
+                FsNode::Dir {
+                    path,
+                    follow_symlinks,
+                } => {
                     let path: PathBuf = path.clone();
+                    let follow_symlinks = *follow_symlinks;
                     // get stream of files
                     let entries = fs::read_dir(&path).await?;
                     let entries_stream = ReadDirStream::new(entries);
@@ -65,7 +94,7 @@ mod sealed {
                         .then(move |entry| async move {
                             let entry = entry?;
                             let file_type = entry.file_type().await?;
-                            Self::from_type(entry.path(), file_type)
+                            Self::from_type(entry.path(), file_type, follow_symlinks).await
                         })
                         .boxed()
                 }
@@ -98,19 +127,28 @@ async fn main() -> anyhow::Result<()> {
         path: PathBuf,
         #[clap(short = 'd', long = "depth", help = "max depth", default_value = "2")]
         max_depth: usize,
+        #[clap(long = "follow-symlinks", help = "follow symbolic links")]
+        follow_symlinks: bool,
     }
 
     #[derive(Debug, Default)]
     struct Stats {
         files: usize,
         dirs: usize,
+        symlinks: usize,
         errs: usize,
     }
 
     let start = Instant::now();
     let options = Options::parse();
-    let root: FsNode = options.path.try_into()?;
-    let bfs: Bfs<FsNode> = Bfs::new(root, options.max_depth, true);
+    let root = FsNode::root(options.path, options.follow_symlinks).await?;
+    // followed symlinks can form cycles, which are detected by tracking the
+    // canonical paths of visited nodes
+    let allow_circles = !options.follow_symlinks;
+    // stop the traversal and cancel pending directory reads on ctrl-c
+    let bfs: Bfs<FsNode> = Bfs::new(root, options.max_depth, allow_circles).cancel_on(async {
+        let _ = tokio::signal::ctrl_c().await;
+    });
 
     let stats = Arc::new(Mutex::new(Stats::default()));
 
@@ -120,8 +158,9 @@ async fn main() -> anyhow::Result<()> {
             println!("{node:?}");
             let mut stats = stats.lock().await;
             match node {
-                Ok(FsNode::Dir(_)) => stats.dirs += 1,
+                Ok(FsNode::Dir { .. }) => stats.dirs += 1,
                 Ok(FsNode::File(_)) => stats.files += 1,
+                Ok(FsNode::Symlink(_)) => stats.symlinks += 1,
                 Err(_) => stats.errs += 1,
             };
         }
@@ -2,20 +2,55 @@
 mod sealed {
     use anyhow::Result;
     use par_dfs::r#sync::{ExtendQueue, FastNode};
+    use std::borrow::Cow;
     use std::fs::FileType;
     use std::path::PathBuf;
 
     #[derive(Hash, PartialEq, Eq, Clone, Debug)]
     pub enum FsNode {
         File(PathBuf),
-        Dir(PathBuf),
+        Dir {
+            path: PathBuf,
+            follow_symlinks: bool,
+        },
+        /// A symlink that is not followed.
+        Symlink(PathBuf),
     }
 
     impl FsNode {
-        pub fn from_type<P: Into<PathBuf>>(path: P, file_type: FileType) -> Result<Self> {
+        /// Creates the root node for `path`, following it if it is a symlink.
+        pub fn root<P: Into<PathBuf>>(path: P, follow_symlinks: bool) -> Result<Self> {
             let path = path.into();
+            let file_type = path.metadata()?.file_type();
+            Self::from_type(path, file_type, follow_symlinks)
+        }
+
+        /// Creates a node for `path` of type `file_type`.
+        ///
+        /// When following symlinks, paths are canonicalized, so that they can
+        /// be used to detect directories that were already visited through
+        /// another link, which would otherwise loop forever.
+        pub fn from_type<P: Into<PathBuf>>(
+            path: P,
+            file_type: FileType,
+            follow_symlinks: bool,
+        ) -> Result<Self> {
+            let path = path.into();
+            if file_type.is_symlink() && !follow_symlinks {
+                return Ok(Self::Symlink(path));
+            }
+            let (path, file_type) = if follow_symlinks {
+                let path = path.canonicalize()?;
+                let file_type = path.metadata()?.file_type();
+                (path, file_type)
+            } else {
+                (path, file_type)
+            };
             if file_type.is_dir() {
-                Ok(Self::Dir(path))
+                Ok(Self::Dir {
+                    path,
+                    follow_symlinks,
+                })
             } else if file_type.is_file() {
                 Ok(Self::File(path))
             } else {
@@ -28,15 +63,6 @@ mod sealed {
         }
     }
 
-    impl TryFrom<PathBuf> for FsNode {
-        type Error = anyhow::Error;
-
-        fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
-            let file_type = path.metadata()?.file_type();
-            Self::from_type(path, file_type)
-        }
-    }
-
     impl FastNode for FsNode {
         type Error = anyhow::Error;
 
@@ -45,17 +71,26 @@ mod sealed {
             E: ExtendQueue<Self, Self::Error>,
         {
             match self {
-                FsNode::Dir(path) => {
-                    let nodes = path.read_dir()?.map(|entry| match entry {
-                        Ok(entry) => entry.path().try_into(),
-                        Err(err) => Err(err.into()),
+                FsNode::Dir {
+                    path,
+                    follow_symlinks,
+                } => {
+                    let nodes = path.read_dir()?.map(|entry| {
+                        let entry = entry?;
+                        Self::from_type(entry.path(), entry.file_type()?, *follow_symlinks)
                     });
                     queue.add_all(nodes);
                 }
-                FsNode::File(_) => {}
+                FsNode::File(_) | FsNode::Symlink(_) => {}
             };
             Ok(())
         }
+
+        fn display_label(&self) -> Cow<'_, str> {
+            let (FsNode::File(path) | FsNode::Dir { path, .. } | FsNode::Symlink(path)) = self;
+            path.file_name()
+                .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy())
+        }
     }
 }
 
@@ -67,7 +102,7 @@ fn main() {
 #[cfg(feature = "sync")]
 fn main() -> anyhow::Result<()> {
     use clap::Parser;
-    use par_dfs::r#sync::FastBfs;
+    use par_dfs::r#sync::{FastBfs, FastNode};
     #[cfg(feature = "rayon")]
     use rayon::iter::{IntoParallelIterator, ParallelIterator};
     use sealed::FsNode;
@@ -81,19 +116,40 @@ fn main() -> anyhow::Result<()> {
         path: PathBuf,
         #[clap(short = 'd', long = "depth", help = "max depth", default_value = "2")]
         max_depth: usize,
+        #[clap(long = "follow-symlinks", help = "follow symbolic links")]
+        follow_symlinks: bool,
+        #[clap(
+            long = "exclude",
+            help = "glob matching the names of directories not to descend into"
+        )]
+        exclude: Vec<glob::Pattern>,
     }
 
     #[derive(Debug, Default)]
     struct Stats {
         files: usize,
         dirs: usize,
+        symlinks: usize,
         errs: usize,
     }
 
     let start = Instant::now();
     let options = Options::parse();
-    let root: FsNode = options.path.try_into()?;
-    let bfs: FastBfs<FsNode> = FastBfs::new(root, options.max_depth, true);
+    let root = FsNode::root(options.path, options.follow_symlinks)?;
+    // followed symlinks can form cycles, which are detected by tracking the
+    // canonical paths of visited nodes
+    let allow_circles = !options.follow_symlinks;
+    // excluded directories are yielded, but their contents are skipped
+    let exclude = options.exclude;
+    let bfs: FastBfs<FsNode> = FastBfs::new(root, options.max_depth, allow_circles).prune(
+        move |node, _depth| match node {
+            FsNode::Dir { path, .. } => path.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                exclude.iter().any(|pattern| pattern.matches(&name))
+            }),
+            FsNode::File(_) | FsNode::Symlink(_) => false,
+        },
+    );
 
     #[cfg(feature = "rayon")]
     let bfs = bfs.into_par_iter();
@@ -101,11 +157,15 @@ fn main() -> anyhow::Result<()> {
     let stats = Mutex::new(Stats::default());
 
     bfs.for_each(|node| {
-        println!("{node:?}");
+        match &node {
+            Ok(node) => println!("{}", node.display_label()),
+            Err(err) => println!("{err:?}"),
+        };
         let mut stats = stats.lock().unwrap();
         match node {
-            Ok(FsNode::Dir(_)) => stats.dirs += 1,
+            Ok(FsNode::Dir { .. }) => stats.dirs += 1,
             Ok(FsNode::File(_)) => stats.files += 1,
+            Ok(FsNode::Symlink(_)) => stats.symlinks += 1,
             Err(_) => stats.errs += 1,
         };
     });
@@ -0,0 +1,80 @@
+#[cfg(feature = "petgraph")]
+mod sealed {
+    use petgraph::graph::{DiGraph, NodeIndex};
+
+    /// Builds a graph where every node `i` has edges to `i * k + 1` for `k` in `1..=degree`.
+    pub fn build_graph(nodes: usize, degree: usize) -> (DiGraph<usize, ()>, NodeIndex) {
+        let mut graph = DiGraph::with_capacity(nodes, nodes * degree);
+        let indices = (0..nodes).map(|i| graph.add_node(i)).collect::<Vec<_>>();
+        for (i, &from) in indices.iter().enumerate() {
+            for k in 1..=degree {
+                graph.add_edge(from, indices[(i * k + 1) % nodes], ());
+            }
+        }
+        (graph, indices[0])
+    }
+}
+
+#[cfg(not(feature = "petgraph"))]
+fn main() {
+    panic!("Feature \"petgraph\" must be enabled for this example");
+}
+
+#[cfg(feature = "petgraph")]
+fn main() -> anyhow::Result<()> {
+    use clap::Parser;
+    use par_dfs::sync::{petgraph::PetNode, FastBfs};
+    #[cfg(feature = "rayon")]
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    #[derive(Parser, Debug)]
+    pub struct Options {
+        #[clap(
+            short = 'n',
+            long = "nodes",
+            help = "number of nodes",
+            default_value = "1000"
+        )]
+        nodes: usize,
+        #[clap(long = "degree", help = "outgoing edges per node", default_value = "3")]
+        degree: usize,
+        #[clap(short = 'd', long = "depth", help = "max depth")]
+        max_depth: Option<usize>,
+    }
+
+    #[derive(Debug, Default)]
+    struct Stats {
+        nodes: usize,
+        max_label: usize,
+    }
+
+    let options = Options::parse();
+    let (graph, root) = sealed::build_graph(options.nodes.max(1), options.degree);
+
+    let start = Instant::now();
+    // the nodes borrow the graph, which outlives the traversal
+    let bfs: FastBfs<PetNode<_>> =
+        FastBfs::new(PetNode::new(&graph, root), options.max_depth, false);
+
+    #[cfg(feature = "rayon")]
+    let bfs = bfs.into_par_iter();
+
+    let stats = Mutex::new(Stats::default());
+
+    bfs.for_each(|node| {
+        let Ok(node) = node;
+        let label = graph[node.id()];
+        println!("{label}");
+        let mut stats = stats.lock().unwrap();
+        stats.nodes += 1;
+        stats.max_label = stats.max_label.max(label);
+    });
+    println!(
+        "found {:?} in {:?}",
+        *stats.lock().unwrap(),
+        start.elapsed()
+    );
+    Ok(())
+}
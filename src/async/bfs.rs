@@ -1,13 +1,18 @@
-use super::{Node, NodeStream, StreamQueue};
+use super::{
+    queue, Budget, CancelFut, ChildrenQueue, FastNode, Node, NodeStream, Request, StreamQueue,
+};
 
 use futures::stream::{FuturesOrdered, Stream, StreamExt};
-use futures::FutureExt;
+use futures::Future;
 use pin_project::pin_project;
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+/// A node or error together with its depth.
+type DepthItem<N, E> = (usize, Result<N, E>);
+
 #[allow(clippy::module_name_repetitions)]
 #[derive(Default)]
 #[pin_project]
@@ -69,10 +74,17 @@ where
 {
     #[pin]
     current_stream: Option<(usize, NodeStream<N, N::Error>)>,
+    root: Option<N>,
     child_streams_futs: StreamQueue<N, N::Error>,
+    pending: VecDeque<(usize, N)>,
+    max_concurrency: Option<usize>,
     max_depth: Option<usize>,
     allow_circles: bool,
     visited: HashSet<N>,
+    min_depth: usize,
+    budget: Option<Budget<N>>,
+    request: Request<N::Error>,
+    cancel: Option<CancelFut>,
 }
 
 impl<N> Bfs<N>
@@ -84,32 +96,226 @@ where
     /// Creates a new [`Bfs`] stream.
     ///
     /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    /// The bound is inclusive, unless a [`DepthBound`] says otherwise.
     ///
     /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
     ///
     /// [`Bfs`]: struct@crate::async::Bfs
+    /// [`DepthBound`]: enum@crate::DepthBound
     pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
     where
         R: Into<N>,
         D: Into<Option<usize>>,
     {
         let root = root.into();
-        let max_depth = max_depth.into();
-        let mut child_streams_futs: StreamQueue<N, N::Error> = FuturesOrdered::new();
-        let depth = 1;
-        let child_stream_fut = Arc::new(root.clone())
-            .children(depth)
-            .map(move |stream| (depth, stream));
-        child_streams_futs.push_back(Box::pin(child_stream_fut));
-
         Self {
             current_stream: None,
-            child_streams_futs,
-            max_depth,
+            root: Some(root.clone()),
+            child_streams_futs: FuturesOrdered::new(),
+            pending: VecDeque::new(),
+            max_concurrency: None,
+            max_depth: max_depth.into(),
             visited: HashSet::from_iter([root]),
             allow_circles,
+            min_depth: 0,
+            budget: None,
+            request: Request::default(),
+            cancel: None,
+        }
+    }
+
+    #[inline]
+    /// Creates a new [`Bfs`] stream that requests children on tasks of `spawner`.
+    ///
+    /// Every call to [`children`] is handed to `spawner` as a separate task,
+    /// e.g. `tokio::spawn` or `async_std::task::spawn`, and the stream awaits
+    /// its result.
+    /// Since the children of all queued nodes are requested concurrently,
+    /// up to the limit of [`max_concurrency`](Self::max_concurrency), a
+    /// multi-threaded executor runs CPU-bound [`children`] in parallel,
+    /// independent of how the traversal is polled.
+    ///
+    /// Only resolving the child stream runs on the task, while its items are
+    /// polled by the task of the traversal, so CPU-bound work should be done
+    /// before returning the stream.
+    /// The handle returned by `spawner` does not need to be awaited, but is
+    /// held until the children are resolved, and dropped with the traversal.
+    /// Whether this cancels the task depends on the executor, e.g. tokio
+    /// detaches it while smol cancels it.
+    /// Retries are requested by the task of the traversal.
+    ///
+    /// See [`new`](Self::new) for the remaining arguments.
+    ///
+    /// [`Bfs`]: struct@crate::async::Bfs
+    /// [`children`]: fn@crate::async::Node::children
+    pub fn with_spawner<R, D, S, H>(root: R, max_depth: D, allow_circles: bool, spawner: S) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+        S: Fn(super::ChildrenTask) -> H + Send + Sync + 'static,
+        H: Send + 'static,
+    {
+        Self {
+            request: Request {
+                spawner: Some(super::spawner::Spawner::new(spawner)),
+                ..Request::default()
+            },
+            ..Self::new(root, max_depth, allow_circles)
         }
     }
+
+    /// Only yields nodes at depth `min_depth` or deeper.
+    ///
+    /// Shallower nodes are still expanded, so deeper nodes remain reachable.
+    /// The children of the root are at depth 1.
+    /// Errors are always yielded.
+    #[must_use]
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    /// Limits the traversal by the accumulated cost of the yielded nodes.
+    ///
+    /// The cost of every yielded node is computed by `cost_fn` and accumulated.
+    /// Once the accumulated cost exceeds `budget`, no more children are
+    /// requested, e.g. to crawl at most a given number of bytes.
+    ///
+    /// Children that were already requested are still yielded, so the
+    /// accumulated cost of all yielded nodes may slightly exceed `budget`.
+    #[must_use]
+    pub fn with_budget<F>(mut self, budget: u64, cost_fn: F) -> Self
+    where
+        F: Fn(&N) -> u64 + Send + Sync + 'static,
+    {
+        self.budget = Some(Budget::new(budget, cost_fn));
+        self
+    }
+
+    /// Limits how many child streams are requested concurrently.
+    ///
+    /// Every yielded node requests its child stream right away, which can
+    /// use a lot of memory when the branching factor is high.
+    /// Once `max_concurrency` child streams are in flight, the expansion of
+    /// further nodes is deferred until a child stream resolves.
+    /// The traversal order is unchanged, and a limit of zero is treated as one.
+    ///
+    /// A [`Dfs`] has no such limit, since it awaits the child stream of a
+    /// node before it continues, so at most one child stream is in flight.
+    ///
+    /// [`Dfs`]: struct@crate::async::Dfs
+    #[must_use]
+    pub fn max_concurrency<M>(mut self, max_concurrency: M) -> Self
+    where
+        M: Into<Option<usize>>,
+    {
+        self.max_concurrency = max_concurrency.into().map(|limit| limit.max(1));
+        self
+    }
+
+    /// Returns the number of child stream requests that are in flight.
+    ///
+    /// This is at most [`max_concurrency`](Self::max_concurrency), if set.
+    #[inline]
+    #[must_use]
+    pub fn pending_streams(&self) -> usize {
+        self.child_streams_futs.len()
+    }
+
+    /// Returns the number of nodes whose child stream is not yet requested
+    /// because of [`max_concurrency`](Self::max_concurrency).
+    #[inline]
+    #[must_use]
+    pub fn deferred_streams(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Stops the traversal once `signal` resolves, e.g. on Ctrl-C.
+    ///
+    /// See [`Dfs::cancel_on`] for details.
+    ///
+    /// [`Dfs::cancel_on`]: fn@crate::async::Dfs::cancel_on
+    #[must_use]
+    pub fn cancel_on<F>(mut self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.cancel = Some(Box::pin(signal));
+        self
+    }
+
+    /// Limits how long the child stream of every node may take to resolve.
+    ///
+    /// See [`Dfs::per_node_timeout`] for details.
+    ///
+    /// [`Dfs::per_node_timeout`]: fn@crate::async::Dfs::per_node_timeout
+    #[cfg(feature = "timeout")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "timeout")))]
+    #[must_use]
+    pub fn per_node_timeout<T>(mut self, per_node_timeout: T) -> Self
+    where
+        T: Into<Option<std::time::Duration>>,
+        N::Error: From<super::TimeoutError>,
+    {
+        self.request.timeout = per_node_timeout.into().map(super::timeout::Timeout::new);
+        self
+    }
+
+    /// Requests the children of nodes on the global rayon thread pool.
+    ///
+    /// See [`children_on_pool`](Self::children_on_pool) for details.
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[must_use]
+    pub fn children_on_global_pool(mut self) -> Self {
+        self.request.pool = Some(super::pool::Pool::Global);
+        self
+    }
+
+    /// Requests the children of nodes on the rayon thread pool `pool`.
+    ///
+    /// Every call to [`children`] is handed to the pool and blocked on by one
+    /// of its threads, while the results are fed back into the traversal.
+    /// Since the children of all queued nodes are requested concurrently,
+    /// up to the limit of [`max_concurrency`](Self::max_concurrency), this
+    /// saturates multiple cores when [`children`] is CPU-bound, while the
+    /// traversal remains a [`Stream`].
+    ///
+    /// Only resolving the child stream runs on the pool, while its items are
+    /// polled by the executor of the traversal, so CPU-bound work should be
+    /// done before returning the stream.
+    /// Since [`children`] is not run by the executor of the traversal,
+    /// it must not depend on its runtime, e.g. on tokio timers or I/O.
+    /// Requests that already started are not cancelled when the traversal
+    /// is dropped.
+    ///
+    /// This should be called on a fresh stream.
+    ///
+    /// [`children`]: fn@crate::async::Node::children
+    /// [`Stream`]: trait@futures::stream::Stream
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[must_use]
+    pub fn children_on_pool(mut self, pool: std::sync::Arc<rayon::ThreadPool>) -> Self {
+        self.request.pool = Some(super::pool::Pool::Custom(pool));
+        self
+    }
+
+    /// Retries requesting the children of a node when it fails.
+    ///
+    /// See [`Dfs::retry`] for details.
+    ///
+    /// [`Dfs::retry`]: fn@crate::async::Dfs::retry
+    #[cfg(feature = "retry")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "retry")))]
+    #[must_use]
+    pub fn retry<R>(mut self, retry: R) -> Self
+    where
+        R: Into<Option<super::RetryPolicy>>,
+    {
+        self.request.retry = retry.into();
+        self
+    }
 }
 
 impl<N> Stream for Bfs<N>
@@ -120,8 +326,43 @@ where
     type Item = Result<N, N::Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_next_node(cx)
+            .map(|item| item.map(|(_, node)| node))
+    }
+}
+
+impl<N> Bfs<N>
+where
+    N: Node + Send + Clone + Unpin + 'static,
+    N::Error: Send + 'static,
+{
+    /// Polls for the next node together with its depth.
+    fn poll_next_node(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<DepthItem<N, N::Error>>> {
         let mut this = self.project();
 
+        if let Some(signal) = this.cancel.as_mut() {
+            if signal.as_mut().poll(cx).is_ready() {
+                // dropping the requests of child streams cancels them
+                *this.cancel = None;
+                *this.root = None;
+                this.current_stream.set(None);
+                this.pending.clear();
+                *this.child_streams_futs = FuturesOrdered::new();
+                return Poll::Ready(None);
+            }
+        }
+
+        // the root is expanded on the first poll, unless it is at the depth limit
+        if let Some(root) = this.root.take() {
+            if *this.max_depth != Some(0) {
+                let fut = this.request.child_stream_fut(root.clone(), 1);
+                this.child_streams_futs.push_back(fut);
+            }
+        }
+
         // println!("------- poll");
         // println!("has current stream: {:?}", this.current_stream.is_some());
 
@@ -138,33 +379,47 @@ where
             // println!("next item: {:?}", next_item);
             match next_item {
                 // stream item is ready but failure success
-                Some(Poll::Ready((_, Some(Err(err))))) => {
-                    return Poll::Ready(Some(Err(err)));
+                Some(Poll::Ready((depth, Some(Err(err))))) => {
+                    return Poll::Ready(Some((*depth, Err(err))));
                 }
                 // stream item is ready and success
                 Some(Poll::Ready((depth, Some(Ok(node))))) => {
-                    if *this.allow_circles || !this.visited.contains(&node) {
-                        if !*this.allow_circles {
-                            this.visited.insert(node.clone());
+                    if !*this.allow_circles {
+                        if this.visited.contains(&node) {
+                            crate::trace::skipped_visited(*depth);
+                            // the node was already visited, so continue with the
+                            // remaining nodes of the current stream
+                            continue;
                         }
+                        this.visited.insert(node.clone());
+                    }
 
-                        if let Some(max_depth) = this.max_depth {
-                            if depth >= max_depth {
-                                return Poll::Ready(Some(Ok(node)));
-                            }
-                        }
+                    let yields = *depth >= *this.min_depth;
+                    let exhausted = yields
+                        && this
+                            .budget
+                            .as_mut()
+                            .is_some_and(|budget| budget.spend(&node));
+                    let at_max_depth = this.max_depth.is_some_and(|max_depth| *depth >= max_depth);
 
-                        // add child stream future to be polled
-                        let arc_node = Arc::new(node.clone());
+                    if !exhausted && !at_max_depth {
                         let next_depth = *depth + 1;
-                        let child_stream_fut = arc_node
-                            .children(next_depth)
-                            .map(move |stream| (next_depth, stream));
-                        this.child_streams_futs
-                            .push_back(Box::pin(child_stream_fut));
-
-                        return Poll::Ready(Some(Ok(node)));
+                        let limited = this
+                            .max_concurrency
+                            .is_some_and(|limit| this.child_streams_futs.len() >= limit);
+                        if limited {
+                            // requested once an earlier child stream resolves
+                            this.pending.push_back((next_depth, node.clone()));
+                        } else {
+                            // add child stream future to be polled
+                            let fut = this.request.child_stream_fut(node.clone(), next_depth);
+                            this.child_streams_futs.push_back(fut);
+                        }
+                    }
+                    if yields {
+                        return Poll::Ready(Some((*depth, Ok(node))));
                     }
+                    continue;
                 }
                 // stream item is pending
                 Some(Poll::Pending) => {
@@ -185,6 +440,11 @@ where
                     //     depth,
                     //     stream.is_ok()
                     // );
+                    // the resolved child stream frees a slot for a deferred one
+                    if let Some((next_depth, node)) = this.pending.pop_front() {
+                        let fut = this.request.child_stream_fut(node.clone(), next_depth);
+                        this.child_streams_futs.push_back(fut);
+                    }
                     let stream = match stream {
                         Ok(stream) => stream.boxed(),
                         Err(err) => futures::stream::iter([Err(err)]).boxed(),
@@ -207,9 +467,328 @@ where
     }
 }
 
+/// Asynchronous breadth-first stream that yields whole levels of a [`Bfs`].
+///
+/// All nodes at the same depth are buffered and yielded together with
+/// their depth, once the first node of the next level or the end of the
+/// traversal is reached.
+/// The levels are yielded in increasing depth, because the child streams
+/// of a [`Bfs`] are consumed in the order they were requested, even when
+/// they resolve out of order.
+/// Like the nodes of a [`Bfs`], the nodes of a level are in traversal order.
+/// Errors are yielded as they are encountered, before the level they occur in.
+///
+/// ### Example
+/// ```
+/// use futures::StreamExt;
+/// use par_dfs::r#async::{LevelBfs, Node, NodeStream};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct WordNode(String);
+///
+/// #[async_trait::async_trait]
+/// impl Node for WordNode {
+///     type Error = std::convert::Infallible;
+///
+///     async fn children(
+///         self: std::sync::Arc<Self>,
+///         _depth: usize
+///     ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+///         let len = self.0.len();
+///         let nodes: Vec<String> = if len > 1 {
+///             let mid = len/2;
+///             vec![self.0[..mid].into(), self.0[mid..].into()]
+///         } else {
+///             vec![]
+///         };
+///         let nodes = nodes.into_iter()
+///             .map(Self)
+///             .map(Result::Ok);
+///         Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+///     }
+/// }
+///
+/// let levels = tokio_test::block_on(async {
+///     let root = WordNode("Hello World".into());
+///     LevelBfs::<WordNode>::new(root, 2, true)
+///         .collect::<Vec<_>>()
+///         .await
+///         .into_iter()
+///         .collect::<Result<Vec<_>, _>>()
+///         .unwrap()
+/// });
+/// let levels = levels
+///     .into_iter()
+///     .map(|(depth, nodes)| (depth, nodes.into_iter().map(|s| s.0).collect::<Vec<_>>()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(levels, [
+///     (1, vec!["Hello".to_string(), " World".to_string()]),
+///     (2, vec!["He".to_string(), "llo".to_string(), " Wo".to_string(), "rld".to_string()]),
+/// ]);
+/// ```
+///
+/// [`Bfs`]: struct@crate::async::Bfs
+#[allow(clippy::module_name_repetitions)]
+#[pin_project]
+pub struct LevelBfs<N>
+where
+    N: Node,
+{
+    #[pin]
+    bfs: Bfs<N>,
+    level: Option<(usize, Vec<N>)>,
+}
+
+impl<N> LevelBfs<N>
+where
+    N: Node + Send + Unpin + Clone + 'static,
+    N::Error: Send + 'static,
+{
+    #[inline]
+    /// Creates a new [`LevelBfs`] stream.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`LevelBfs`]: struct@crate::async::LevelBfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        Self::from(Bfs::new(root, max_depth, allow_circles))
+    }
+}
+
+impl<N> From<Bfs<N>> for LevelBfs<N>
+where
+    N: Node,
+{
+    /// Groups the nodes of `bfs` by level, keeping all of its options.
+    #[inline]
+    fn from(bfs: Bfs<N>) -> Self {
+        Self { bfs, level: None }
+    }
+}
+
+impl<N> Stream for LevelBfs<N>
+where
+    N: Node + Send + Clone + Unpin + 'static,
+    N::Error: Send + 'static,
+{
+    type Item = Result<(usize, Vec<N>), N::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            match this.bfs.as_mut().poll_next_node(cx) {
+                Poll::Ready(Some((_, Err(err)))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(Some((depth, Ok(node)))) => match this.level.as_mut() {
+                    Some((level, nodes)) if *level == depth => nodes.push(node),
+                    // the first node of the next level completes the current level
+                    _ => {
+                        if let Some(level) = this.level.replace((depth, vec![node])) {
+                            return Poll::Ready(Some(Ok(level)));
+                        }
+                    }
+                },
+                Poll::Ready(None) => return Poll::Ready(this.level.take().map(Ok)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Default)]
+#[pin_project]
+/// Asynchronous, fast breadth-first stream for types implementing the [`FastNode`] trait.
+///
+/// ### Example
+/// ```
+/// use futures::StreamExt;
+/// use par_dfs::r#async::{FastNode, FastBfs, ExtendQueue};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct WordNode(String);
+///
+/// #[async_trait::async_trait]
+/// impl FastNode for WordNode {
+///     type Error = std::convert::Infallible;
+///
+///     async fn add_children<Q>(
+///         self: std::sync::Arc<Self>,
+///         _depth: usize,
+///         queue: &mut Q,
+///     ) -> Result<(), Self::Error>
+///     where
+///         Q: ExtendQueue<Self, Self::Error> + Send,
+///     {
+///         let len = self.0.len();
+///         if len > 1 {
+///             let mid = len/2;
+///             queue.add(Ok(Self(self.0[..mid].into())));
+///             queue.add(Ok(Self(self.0[mid..].into())));
+///         } else {
+///             assert!(len == 1);
+///             queue.add(Ok(Self(self.0.clone())));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let result = tokio_test::block_on(async {
+///     let word = "Hello World";
+///     let root = WordNode(word.into());
+///     let limit = (word.len() as f32).log2().ceil() as usize;
+///     let bfs = FastBfs::<WordNode>::new(root, limit, true);
+///     let output = bfs
+///         .collect::<Vec<_>>()
+///         .await
+///         .into_iter()
+///         .collect::<Result<Vec<_>, _>>()
+///         .unwrap();
+///     output[output.len()-word.len()..]
+///         .into_iter().map(|s| s.0.as_str()).collect::<String>()
+/// });
+/// assert_eq!(result, "Hello World");
+/// ```
+///
+/// [`FastNode`]: trait@crate::async::FastNode
+pub struct FastBfs<N>
+where
+    N: FastNode,
+{
+    current_queue: Option<(usize, queue::Queue<N, N::Error>)>,
+    child_queues_futs: ChildrenQueue<N, N::Error>,
+    max_depth: Option<usize>,
+    allow_circles: bool,
+    visited: HashSet<N>,
+    min_depth: usize,
+}
+
+impl<N> FastBfs<N>
+where
+    N: FastNode + Send + Sync + Unpin + Clone + 'static,
+    N::Error: Send + 'static,
+{
+    #[inline]
+    /// Creates a new [`FastBfs`] stream.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`FastBfs`]: struct@crate::async::FastBfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let root = root.into();
+        let max_depth = max_depth.into();
+        let mut child_queues_futs: ChildrenQueue<N, N::Error> = FuturesOrdered::new();
+        // the root is not expanded when it is at the depth limit
+        if max_depth != Some(0) {
+            let depth = 1;
+            child_queues_futs.push_back(queue::add_children(Arc::new(root.clone()), depth));
+        }
+
+        Self {
+            current_queue: None,
+            child_queues_futs,
+            max_depth,
+            visited: HashSet::from_iter([root]),
+            allow_circles,
+            min_depth: 0,
+        }
+    }
+
+    /// Only yields nodes at depth `min_depth` or deeper.
+    ///
+    /// Shallower nodes are still expanded, so deeper nodes remain reachable.
+    /// The children of the root are at depth 1.
+    /// Errors are always yielded.
+    #[must_use]
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+}
+
+impl<N> Stream for FastBfs<N>
+where
+    N: FastNode + Send + Sync + Clone + Unpin + 'static,
+    N::Error: Send + 'static,
+{
+    type Item = Result<N, N::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        loop {
+            let next_item = this
+                .current_queue
+                .as_mut()
+                .map(|(depth, queue)| (*depth, queue.pop_front()));
+
+            match next_item {
+                // item failed
+                Some((_, Some(Err(err)))) => {
+                    return Poll::Ready(Some(Err(err)));
+                }
+                // item succeeded
+                Some((depth, Some(Ok(node)))) => {
+                    if !*this.allow_circles {
+                        if this.visited.contains(&node) {
+                            crate::trace::skipped_visited(depth);
+                            continue;
+                        }
+                        this.visited.insert(node.clone());
+                    }
+
+                    let at_max_depth = this.max_depth.is_some_and(|max_depth| depth >= max_depth);
+                    if !at_max_depth {
+                        // add children future to be polled
+                        let arc_node = Arc::new(node.clone());
+                        this.child_queues_futs
+                            .push_back(queue::add_children(arc_node, depth + 1));
+                    }
+                    if depth >= *this.min_depth {
+                        return Poll::Ready(Some(Ok(node)));
+                    }
+                    continue;
+                }
+                // no current queue or all items consumed
+                Some((_, None)) | None => {
+                    // proceed to poll the next queue
+                }
+            }
+
+            // poll the children of the next node
+            match this.child_queues_futs.poll_next_unpin(cx) {
+                Poll::Ready(Some((depth, queue))) => {
+                    let queue = queue.unwrap_or_else(queue::Queue::from_err);
+                    *this.current_queue = Some((depth, queue));
+                }
+                // when there are no more children futures,
+                // we are done
+                Poll::Ready(None) => {
+                    return Poll::Ready(None);
+                }
+                // still waiting for the next children
+                Poll::Pending => {
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Bfs;
+    use super::{Bfs, FastBfs};
     use anyhow::Result;
 
     macro_rules! depths {
@@ -284,6 +863,66 @@ mod tests {
         }
     }
 
+    test_depths!(
+        bfs_leaves:
+        (
+            Bfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true),
+            [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        bfs_max_depth_zero:
+        (
+            Bfs::<crate::utils::test::LeafNode>::new(0, 0, true),
+            [] as [usize; 0]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        fast_bfs_leaves:
+        (
+            FastBfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true),
+            [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        fast_bfs_max_depth_zero:
+        (
+            FastBfs::<crate::utils::test::LeafNode>::new(0, 0, true),
+            [] as [usize; 0]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        bfs_min_depth:
+        (
+            Bfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2),
+            [2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        fast_bfs_min_depth:
+        (
+            FastBfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2),
+            [2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
     test_depths!(
         bfs:
         (
@@ -294,6 +933,16 @@ mod tests {
         test_depths_unordered,
     );
 
+    test_depths!(
+        bfs_budget:
+        (
+            Bfs::<crate::utils::test::Node>::new(0, 3, true).with_budget(2, |_| 1),
+            [1, 1, 2, 2, 2, 2]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
     test_depths!(
         bfs_no_circles:
         (
@@ -303,4 +952,132 @@ mod tests {
         test_depths_ordered,
         test_depths_unordered,
     );
+
+    test_depths!(
+        fast_bfs:
+        (
+            FastBfs::<crate::utils::test::Node>::new(0, 3, true),
+            [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        fast_bfs_no_circles:
+        (
+            FastBfs::<crate::utils::test::Node>::new(0, 3, false),
+            [1, 2, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_level_bfs() -> Result<()> {
+        use super::LevelBfs;
+        use crate::utils::test::GraphNode;
+        use futures::StreamExt;
+
+        let levels = LevelBfs::<crate::utils::test::Node>::new(0, 3, true)
+            .map(|level| level.map(|(depth, nodes)| (depth, nodes.len())))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(levels, [(1, 2), (2, 4), (3, 8)]);
+
+        // the expansions of the graph nodes complete out of order
+        let edges: &[&[usize]] = &[&[1, 2, 3], &[4, 5], &[5, 6], &[1, 6], &[0], &[], &[2]];
+        let root = GraphNode::new(0, edges);
+        let levels = LevelBfs::<GraphNode>::new(root, None, false)
+            .map(|level| {
+                level.map(|(depth, nodes)| (depth, nodes.iter().map(|n| n.id).collect::<Vec<_>>()))
+            })
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(levels, [(1, vec![1, 2, 3]), (2, vec![4, 5, 6])]);
+
+        let levels = LevelBfs::from(Bfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2))
+            .map(|level| level.map(|(depth, nodes)| (depth, nodes.len())))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(levels, [(2, 4), (3, 8)]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bfs_skips_visited_without_dropping_siblings() -> Result<()> {
+        use crate::utils::test::GraphNode;
+        use futures::StreamExt;
+
+        // the children of 1 start with the visited root
+        let edges: &[&[usize]] = &[&[1, 2], &[0, 3], &[4], &[], &[]];
+        let root = GraphNode::new(0, edges);
+        let ids = Bfs::<GraphNode>::new(root, None, false)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [1, 2, 3, 4]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bfs_max_concurrency() -> Result<()> {
+        use crate::r#async::{Node, NodeStream};
+        use futures::StreamExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::time::{sleep, Duration};
+
+        static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+        static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+        struct WideNode(usize);
+
+        #[async_trait::async_trait]
+        impl Node for WideNode {
+            type Error = std::convert::Infallible;
+
+            async fn children(
+                self: Arc<Self>,
+                _depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+                MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+                // complete expansions out of order
+                sleep(Duration::from_millis(10 * (4 - self.0 % 4) as u64)).await;
+                IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+                let id = self.0;
+                let nodes = (0..4).map(move |i| Ok(Self(id * 4 + i + 1)));
+                Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+            }
+        }
+
+        let ids = Bfs::<WideNode>::new(WideNode(0), 3, false)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        assert!(MAX_IN_FLIGHT.swap(0, Ordering::SeqCst) > 2);
+
+        let limited_ids = Bfs::<WideNode>::new(WideNode(0), 3, false)
+            .max_concurrency(2)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        assert!(MAX_IN_FLIGHT.load(Ordering::SeqCst) <= 2);
+        similar_asserts::assert_eq!(limited_ids, ids);
+        Ok(())
+    }
 }
@@ -1,19 +1,869 @@
-use super::{Node, Stack, StreamQueue};
+use super::{
+    queue, Budget, CancelFut, ChildrenQueue, FastNode, FastStack, Node, Request, Stack, StreamQueue,
+};
 
-use futures::stream::{FuturesOrdered, Stream, StreamExt};
-use futures::FutureExt;
+use super::NodeStream;
+use futures::stream::{FuturesOrdered, FuturesUnordered, Stream, StreamExt};
+use futures::{Future, FutureExt};
 use pin_project::pin_project;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
 /// Asynchronous depth-first stream for types implementing the [`Node`] trait.
 ///
+/// The children of a yielded node are awaited before its siblings are
+/// continued, hence at most one child stream is requested at a time.
+/// Use [`ConcurrentDfs`] to expand nodes concurrently.
+///
+/// ### Example
+/// ```
+/// use futures::StreamExt;
+/// use par_dfs::r#async::{Node, Dfs, NodeStream};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct WordNode(String);
+///
+/// #[async_trait::async_trait]
+/// impl Node for WordNode {
+///     type Error = std::convert::Infallible;
+///
+///     async fn children(
+///         self: std::sync::Arc<Self>,
+///         _depth: usize
+///     ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+///         let len = self.0.len();
+///         let nodes: Vec<String> = if len < 2 {
+///             vec![]
+///         } else {
+///             let mid = len/2;
+///             vec![self.0[..mid].into(), self.0[mid..].into()]
+///         };
+///         let nodes = nodes.into_iter()
+///             .map(Self)
+///             .map(Result::Ok);
+///         let stream = futures::stream::iter(nodes);
+///         Ok(Box::pin(stream.boxed()))
+///     }
+/// }
+///
+/// let result = tokio_test::block_on(async {
+///     let root = WordNode("Hello World".into());
+///     let dfs = Dfs::<WordNode>::new(root, None, true);
+///     let output = dfs
+///         .collect::<Vec<_>>()
+///         .await
+///         .into_iter()
+///         .collect::<Result<Vec<_>, _>>()
+///         .unwrap();
+///     output.into_iter()
+///         .filter_map(|s| if s.0.len() == 1 { Some(s.0) } else { None })
+///         .collect::<String>()
+/// });
+/// assert_eq!(result, "Hello World");
+/// ```
+///
+/// [`Node`]: trait@crate::async::Node
+/// [`ConcurrentDfs`]: struct@crate::async::ConcurrentDfs
+#[derive(Default)]
+#[pin_project]
+pub struct Dfs<N>
+where
+    N: Node,
+{
+    stack: Stack<N, N::Error>,
+    root: Option<N>,
+    child_streams_futs: StreamQueue<N, N::Error>,
+    max_depth: Option<usize>,
+    allow_circles: bool,
+    visited: HashSet<N>,
+    min_depth: usize,
+    budget: Option<Budget<N>>,
+    request: Request<N::Error>,
+    cancel: Option<CancelFut>,
+}
+
+impl<N> Dfs<N>
+where
+    N: Node + Send + Unpin + Clone + 'static,
+    N::Error: Send + 'static,
+{
+    #[inline]
+    /// Creates a new [`Dfs`] stream.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    /// The bound is inclusive, unless a [`DepthBound`] says otherwise.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`Dfs`]: struct@crate::async::Dfs
+    /// [`DepthBound`]: enum@crate::DepthBound
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let root = root.into();
+        Self {
+            stack: vec![],
+            root: Some(root.clone()),
+            child_streams_futs: FuturesOrdered::new(),
+            max_depth: max_depth.into(),
+            visited: HashSet::from_iter([root]),
+            allow_circles,
+            min_depth: 0,
+            budget: None,
+            request: Request::default(),
+            cancel: None,
+        }
+    }
+
+    #[inline]
+    /// Creates a new [`Dfs`] stream that requests children on tasks of `spawner`.
+    ///
+    /// Every call to [`children`] is handed to `spawner` as a separate task,
+    /// e.g. `tokio::spawn` or `async_std::task::spawn`.
+    /// Since the children of a node are awaited before its siblings are
+    /// continued, at most one task runs at a time, so this moves CPU-bound
+    /// [`children`] off the task that polls the traversal, but does not
+    /// request them in parallel.
+    /// Use [`Bfs::with_spawner`] to request the children of many nodes in
+    /// parallel, which also describes how the tasks are handled.
+    ///
+    /// See [`new`](Self::new) for the remaining arguments.
+    ///
+    /// [`Dfs`]: struct@crate::async::Dfs
+    /// [`children`]: fn@crate::async::Node::children
+    /// [`Bfs::with_spawner`]: fn@crate::async::Bfs::with_spawner
+    pub fn with_spawner<R, D, S, H>(root: R, max_depth: D, allow_circles: bool, spawner: S) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+        S: Fn(super::ChildrenTask) -> H + Send + Sync + 'static,
+        H: Send + 'static,
+    {
+        Self {
+            request: Request {
+                spawner: Some(super::spawner::Spawner::new(spawner)),
+                ..Request::default()
+            },
+            ..Self::new(root, max_depth, allow_circles)
+        }
+    }
+
+    /// Only yields nodes at depth `min_depth` or deeper.
+    ///
+    /// Shallower nodes are still expanded, so deeper nodes remain reachable.
+    /// The children of the root are at depth 1.
+    /// Errors are always yielded.
+    #[must_use]
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    /// Limits the traversal by the accumulated cost of the yielded nodes.
+    ///
+    /// The cost of every yielded node is computed by `cost_fn` and accumulated.
+    /// Once the accumulated cost exceeds `budget`, no more children are
+    /// requested, e.g. to crawl at most a given number of bytes.
+    ///
+    /// Children that were already requested are still yielded, so the
+    /// accumulated cost of all yielded nodes may slightly exceed `budget`.
+    #[must_use]
+    pub fn with_budget<F>(mut self, budget: u64, cost_fn: F) -> Self
+    where
+        F: Fn(&N) -> u64 + Send + Sync + 'static,
+    {
+        self.budget = Some(Budget::new(budget, cost_fn));
+        self
+    }
+
+    /// Limits how long the child stream of every node may take to resolve.
+    ///
+    /// When the child stream of a node does not resolve within
+    /// `per_node_timeout`, its request is dropped and a [`TimeoutError`] is
+    /// yielded in place of the children of the node, converted into the
+    /// error of the node.
+    /// The traversal then continues with the remaining nodes.
+    ///
+    /// Only resolving the child stream is limited, while its items are
+    /// awaited without a timeout, so a child stream that already produced
+    /// some of its children is never cut short.
+    ///
+    /// This should be called on a fresh stream.
+    ///
+    /// [`TimeoutError`]: struct@crate::async::TimeoutError
+    #[cfg(feature = "timeout")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "timeout")))]
+    #[must_use]
+    pub fn per_node_timeout<T>(mut self, per_node_timeout: T) -> Self
+    where
+        T: Into<Option<std::time::Duration>>,
+        N::Error: From<super::TimeoutError>,
+    {
+        self.request.timeout = per_node_timeout.into().map(super::timeout::Timeout::new);
+        self
+    }
+
+    /// Requests the children of nodes on the global rayon thread pool.
+    ///
+    /// See [`children_on_pool`](Self::children_on_pool) for details.
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[must_use]
+    pub fn children_on_global_pool(mut self) -> Self {
+        self.request.pool = Some(super::pool::Pool::Global);
+        self
+    }
+
+    /// Requests the children of nodes on the rayon thread pool `pool`.
+    ///
+    /// Since the children of a node are awaited before its siblings are
+    /// continued, at most one request runs on the pool at a time, so this
+    /// moves CPU-bound [`children`] off the executor of the traversal, but
+    /// does not request them in parallel.
+    /// Use a [`Bfs`] to request the children of many nodes in parallel,
+    /// see [`Bfs::children_on_pool`] for details.
+    ///
+    /// This should be called on a fresh stream.
+    ///
+    /// [`children`]: fn@crate::async::Node::children
+    /// [`Bfs`]: struct@crate::async::Bfs
+    /// [`Bfs::children_on_pool`]: fn@crate::async::Bfs::children_on_pool
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[must_use]
+    pub fn children_on_pool(mut self, pool: std::sync::Arc<rayon::ThreadPool>) -> Self {
+        self.request.pool = Some(super::pool::Pool::Custom(pool));
+        self
+    }
+
+    /// Retries requesting the children of a node when it fails.
+    ///
+    /// When [`children`] returns an error, it is invoked again on a fresh
+    /// copy of the node after waiting for the `backoff` of the [`RetryPolicy`],
+    /// up to `max_retries` times.
+    /// Only the error of the last attempt is yielded, in place of the
+    /// children of the node.
+    /// Errors of the items of a child stream are never retried.
+    ///
+    /// With a per-node timeout, every attempt has its own timeout, and an
+    /// attempt that timed out is retried like any other failure.
+    /// Retries are requested on the same thread pool or spawner as the
+    /// first attempt, while their backoff is awaited by the traversal.
+    ///
+    /// This should be called on a fresh stream.
+    ///
+    /// [`children`]: fn@crate::async::Node::children
+    /// [`RetryPolicy`]: struct@crate::async::RetryPolicy
+    #[cfg(feature = "retry")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "retry")))]
+    #[must_use]
+    pub fn retry<R>(mut self, retry: R) -> Self
+    where
+        R: Into<Option<super::RetryPolicy>>,
+    {
+        self.request.retry = retry.into();
+        self
+    }
+
+    /// Stops the traversal once `signal` resolves, e.g. on Ctrl-C.
+    ///
+    /// The signal is polled before every node, and once it resolved, the
+    /// stream ends.
+    /// All pending child stream requests are dropped, which cancels their
+    /// [`children`] futures at their next await point.
+    /// Any future works as a signal, e.g. [`tokio::signal::ctrl_c`] or
+    /// `CancellationToken::cancelled_owned` of `tokio-util`.
+    ///
+    /// [`children`]: fn@crate::async::Node::children
+    /// [`tokio::signal::ctrl_c`]: https://docs.rs/tokio/latest/tokio/signal/fn.ctrl_c.html
+    #[must_use]
+    pub fn cancel_on<F>(mut self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.cancel = Some(Box::pin(signal));
+        self
+    }
+
+    /// Returns the number of child stream requests that are in flight.
+    ///
+    /// Together with [`stack_depth`](Self::stack_depth), this reflects the
+    /// memory used by a running traversal.
+    #[inline]
+    #[must_use]
+    pub fn pending_streams(&self) -> usize {
+        self.child_streams_futs.len()
+    }
+
+    /// Returns the number of partially consumed child streams, i.e. the
+    /// depth of the current node below the root, plus one while its child
+    /// stream is consumed.
+    #[inline]
+    #[must_use]
+    pub fn stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Splits off the older half of the partially consumed child streams
+    /// into a new [`Dfs`], e.g. to consume both halves on separate tasks.
+    ///
+    /// The split off half continues the siblings of the shallowest ancestors
+    /// of the current node, which usually hold most of the remaining work,
+    /// while this stream keeps the deeper child streams and any child stream
+    /// request in flight.
+    /// A child stream is never divided, so nothing is split off while fewer
+    /// than two child streams are partially consumed, see [`stack_depth`].
+    ///
+    /// Both halves yield the nodes in depth-first order, but there is no
+    /// global order across them anymore.
+    /// Every half tracks the nodes it visits on its own, starting from the
+    /// nodes visited before the split, so a node reachable from both halves
+    /// can be yielded twice.
+    /// The remaining budget is divided between both halves, and both are
+    /// cancelled by the same signal.
+    ///
+    /// ### Example
+    /// ```
+    /// use futures::stream::{self, StreamExt};
+    /// use par_dfs::r#async::{Dfs, Node, NodeStream};
+    ///
+    /// #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+    /// struct TreeNode(usize);
+    ///
+    /// #[async_trait::async_trait]
+    /// impl Node for TreeNode {
+    ///     type Error = std::convert::Infallible;
+    ///
+    ///     async fn children(
+    ///         self: std::sync::Arc<Self>,
+    ///         _depth: usize,
+    ///     ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+    ///         let nodes = [2 * self.0 + 1, 2 * self.0 + 2].map(Self).map(Ok);
+    ///         Ok(Box::pin(stream::iter(nodes).boxed()))
+    ///     }
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 4, false);
+    /// // yield a node and one of its children to enter a second child stream
+    /// for _ in 0..2 {
+    ///     dfs.next().await;
+    /// }
+    /// let split = dfs.split_off().unwrap();
+    /// let nodes = stream::select_all([dfs, split])
+    ///     .collect::<Vec<_>>()
+    ///     .await;
+    /// assert_eq!(nodes.len() + 2, 30);
+    /// # });
+    /// ```
+    ///
+    /// [`Dfs`]: struct@crate::async::Dfs
+    /// [`stack_depth`]: Self::stack_depth
+    pub fn split_off(&mut self) -> Option<Self> {
+        let len = self.stack.len();
+        if len < 2 {
+            return None;
+        }
+        // the bottom of the stack is consumed last,
+        // so it is the largest share of the work that has not started
+        let stack = self.stack.drain(..len / 2).collect();
+        let cancel = self.cancel.take().map(|signal| {
+            let signal = signal.shared();
+            self.cancel = Some(Box::pin(signal.clone()));
+            Box::pin(signal) as CancelFut
+        });
+        Some(Self {
+            stack,
+            root: None,
+            child_streams_futs: FuturesOrdered::new(),
+            max_depth: self.max_depth,
+            allow_circles: self.allow_circles,
+            visited: self.visited.clone(),
+            min_depth: self.min_depth,
+            budget: self.budget.as_mut().map(Budget::split),
+            request: self.request.clone(),
+            cancel,
+        })
+    }
+
+    /// Returns a blocking [`Iterator`] over the nodes of this stream.
+    ///
+    /// Every call to `next` blocks on `handle` until the next node is ready,
+    /// so nodes are only requested as they are consumed and the stream is
+    /// never buffered.
+    /// This allows consuming the traversal from synchronous code.
+    ///
+    /// # Panics
+    ///
+    /// Advancing the iterator panics when called from within an asynchronous
+    /// execution context, see [`Handle::block_on`].
+    ///
+    /// [`Iterator`]: trait@std::iter::Iterator
+    /// [`Handle::block_on`]: fn@tokio::runtime::Handle::block_on
+    #[cfg(feature = "bridge")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
+    pub fn blocking_iter(
+        mut self,
+        handle: tokio::runtime::Handle,
+    ) -> impl Iterator<Item = Result<N, N::Error>> {
+        std::iter::from_fn(move || handle.block_on(self.next()))
+    }
+
+    /// Traverses the stream and runs `f` for every item, with at most
+    /// `limit` futures of `f` and child stream requests in flight.
+    ///
+    /// In contrast to [`StreamExt::for_each_concurrent`], the traversal
+    /// counts towards `limit`.
+    /// The children of a node are only requested while the stream is polled,
+    /// which happens only while fewer than `limit` futures of `f` are in flight.
+    /// Since a [`Dfs`] awaits the children of a node before yielding the
+    /// next one, no request is in flight once a node was yielded, so the
+    /// combined work stays bounded by `limit`.
+    /// A limit of zero is treated as one, which runs `f` sequentially.
+    ///
+    /// [`StreamExt::for_each_concurrent`]: fn@futures::stream::StreamExt::for_each_concurrent
+    /// [`Dfs`]: struct@crate::async::Dfs
+    pub async fn for_each_limited<F, Fut>(self, limit: usize, mut f: F)
+    where
+        F: FnMut(Result<N, N::Error>) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let limit = limit.max(1);
+        let mut stream = self;
+        let mut running = FuturesUnordered::new();
+        let mut done = false;
+        futures::future::poll_fn(|cx| loop {
+            while !done && running.len() < limit {
+                match stream.poll_next_unpin(cx) {
+                    Poll::Ready(Some(item)) => running.push(f(item)),
+                    Poll::Ready(None) => done = true,
+                    Poll::Pending => break,
+                }
+            }
+            match running.poll_next_unpin(cx) {
+                // a finished future frees a slot to advance the traversal
+                Poll::Ready(Some(())) => {}
+                Poll::Ready(None) if done => return Poll::Ready(()),
+                Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+            }
+        })
+        .await;
+    }
+}
+
+impl<N> Stream for Dfs<N>
+where
+    N: Node + Send + Clone + Unpin + 'static,
+    N::Error: Send + 'static,
+{
+    type Item = Result<N, N::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_next_until(cx, |_| false)
+    }
+}
+
+impl<N> Dfs<N>
+where
+    N: Node + Send + Clone + Unpin + 'static,
+    N::Error: Send + 'static,
+{
+    /// Returns the first node for which `f` returns `true`.
+    ///
+    /// The children of the matching node are not requested, and all pending
+    /// child streams are dropped once a match is found.
+    /// Returns the first error encountered before a match, or [`None`] when
+    /// the stream completes without a match.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    pub async fn find_map_node<F>(mut self, mut f: F) -> Option<Result<N, N::Error>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        let mut found = false;
+        while let Some(node) = futures::future::poll_fn(|cx| {
+            Pin::new(&mut self).poll_next_until(cx, |node| {
+                found = f(node);
+                found
+            })
+        })
+        .await
+        {
+            match node {
+                Ok(node) if found => return Some(Ok(node)),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+
+    /// Polls for the next node, but does not expand it when `found` returns
+    /// `true` for it.
+    fn poll_next_until<F>(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        mut found: F,
+    ) -> Poll<Option<Result<N, N::Error>>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        let this = self.project();
+
+        if let Some(signal) = this.cancel.as_mut() {
+            if signal.as_mut().poll(cx).is_ready() {
+                // dropping the requests of child streams cancels them
+                *this.cancel = None;
+                *this.root = None;
+                this.stack.clear();
+                *this.child_streams_futs = FuturesOrdered::new();
+                return Poll::Ready(None);
+            }
+        }
+
+        // the root is expanded on the first poll, unless it is at the depth limit
+        if let Some(root) = this.root.take() {
+            if *this.max_depth != Some(0) {
+                let fut = this.request.child_stream_fut(root.clone(), 1);
+                this.child_streams_futs.push_front(fut);
+            }
+        }
+
+        // a node that is not yielded continues with its children first
+        loop {
+            // println!("------- poll");
+            // println!("stack size: {:?}", this.stack.len());
+
+            // we first poll for the newest child stream in dfs
+            // println!("child stream futs: {:?}", this.child_streams_futs.len());
+            match this.child_streams_futs.poll_next_unpin(cx) {
+                Poll::Ready(Some((depth, stream))) => {
+                    // println!(
+                    //     "child stream fut depth {} completed: {:?}",
+                    //     depth,
+                    //     stream.is_ok()
+                    // );
+                    let stream = match stream {
+                        Ok(stream) => stream.boxed(),
+                        Err(err) => futures::stream::iter([Err(err)]).boxed(),
+                    };
+                    this.stack.push((depth, Box::pin(stream)));
+                    // println!("stack size: {}", this.stack.len());
+                }
+                // when there is no child stream future,
+                // continue to poll the current stream
+                Poll::Ready(None) => {
+                    // println!("no child stream to wait for");
+                }
+                // still waiting for the new child stream
+                Poll::Pending => {
+                    // println!("child stream is still pending");
+                    return Poll::Pending;
+                }
+            }
+
+            // at this point, the last element in the stack is the current level
+            loop {
+                let next_item = match this.stack.last_mut() {
+                    Some((depth, current_stream)) => {
+                        let next_item = current_stream.as_mut().poll_next(cx);
+                        Some(next_item.map(|node| (depth, node)))
+                    }
+                    None => None,
+                };
+
+                // println!("next item: {:?}", next_item);
+                match next_item {
+                    // stream item is ready but failure success
+                    Some(Poll::Ready((_, Some(Err(err))))) => {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    // stream item is ready and success
+                    Some(Poll::Ready((depth, Some(Ok(node))))) => {
+                        if !*this.allow_circles {
+                            if this.visited.contains(&node) {
+                                crate::trace::skipped_visited(*depth);
+                                continue;
+                            }
+                            this.visited.insert(node.clone());
+                        }
+
+                        let yields = *depth >= *this.min_depth;
+                        if yields && found(&node) {
+                            return Poll::Ready(Some(Ok(node)));
+                        }
+                        let exhausted = yields
+                            && this
+                                .budget
+                                .as_mut()
+                                .is_some_and(|budget| budget.spend(&node));
+                        let at_max_depth =
+                            this.max_depth.is_some_and(|max_depth| *depth >= max_depth);
+
+                        if !exhausted && !at_max_depth {
+                            // add child stream future to be polled
+                            let next_depth = *depth + 1;
+                            let fut = this.request.child_stream_fut(node.clone(), next_depth);
+                            this.child_streams_futs.push_front(fut);
+                        }
+                        if yields {
+                            return Poll::Ready(Some(Ok(node)));
+                        }
+                        // poll the children of the node first
+                        break;
+                    }
+                    // stream completed for this level completed
+                    Some(Poll::Ready((_, None))) => {
+                        this.stack.pop();
+                        // println!("pop stack to size: {}", this.stack.len());
+                        // try again in the next round
+                        // returning Poll::Pending here is bad because the runtime can not know when to poll
+                        // us again to make progress since we never passed the cx to poll of the next
+                        // level stream
+                    }
+                    // stream item is pending
+                    Some(Poll::Pending) => {
+                        return Poll::Pending;
+                    }
+                    // stack is empty and we are done
+                    None => {
+                        return Poll::Ready(None);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Asynchronous, fast depth-first stream for types implementing the [`FastNode`] trait.
+///
+/// ### Example
+/// ```
+/// use futures::StreamExt;
+/// use par_dfs::r#async::{FastNode, FastDfs, ExtendQueue};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct WordNode(String);
+///
+/// #[async_trait::async_trait]
+/// impl FastNode for WordNode {
+///     type Error = std::convert::Infallible;
+///
+///     async fn add_children<Q>(
+///         self: std::sync::Arc<Self>,
+///         _depth: usize,
+///         queue: &mut Q,
+///     ) -> Result<(), Self::Error>
+///     where
+///         Q: ExtendQueue<Self, Self::Error> + Send,
+///     {
+///         let len = self.0.len();
+///         if len > 1 {
+///             let mid = len/2;
+///             queue.add(Ok(Self(self.0[..mid].into())));
+///             queue.add(Ok(Self(self.0[mid..].into())));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let result = tokio_test::block_on(async {
+///     let root = WordNode("Hello World".into());
+///     let dfs = FastDfs::<WordNode>::new(root, None, true);
+///     let output = dfs
+///         .collect::<Vec<_>>()
+///         .await
+///         .into_iter()
+///         .collect::<Result<Vec<_>, _>>()
+///         .unwrap();
+///     output.into_iter()
+///         .filter_map(|s| if s.0.len() == 1 { Some(s.0) } else { None })
+///         .collect::<String>()
+/// });
+/// assert_eq!(result, "Hello World");
+/// ```
+///
+/// [`FastNode`]: trait@crate::async::FastNode
+#[allow(clippy::module_name_repetitions)]
+#[derive(Default)]
+#[pin_project]
+pub struct FastDfs<N>
+where
+    N: FastNode,
+{
+    stack: FastStack<N, N::Error>,
+    child_queues_futs: ChildrenQueue<N, N::Error>,
+    max_depth: Option<usize>,
+    allow_circles: bool,
+    visited: HashSet<N>,
+    min_depth: usize,
+}
+
+impl<N> FastDfs<N>
+where
+    N: FastNode + Send + Sync + Unpin + Clone + 'static,
+    N::Error: Send + 'static,
+{
+    #[inline]
+    /// Creates a new [`FastDfs`] stream.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`FastDfs`]: struct@crate::async::FastDfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let root = root.into();
+        let max_depth = max_depth.into();
+        let mut child_queues_futs: ChildrenQueue<N, N::Error> = FuturesOrdered::new();
+        // the root is not expanded when it is at the depth limit
+        if max_depth != Some(0) {
+            let depth = 1;
+            child_queues_futs.push_front(queue::add_children(Arc::new(root.clone()), depth));
+        }
+
+        Self {
+            stack: vec![],
+            child_queues_futs,
+            max_depth,
+            visited: HashSet::from_iter([root]),
+            allow_circles,
+            min_depth: 0,
+        }
+    }
+
+    /// Only yields nodes at depth `min_depth` or deeper.
+    ///
+    /// Shallower nodes are still expanded, so deeper nodes remain reachable.
+    /// The children of the root are at depth 1.
+    /// Errors are always yielded.
+    #[must_use]
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+}
+
+impl<N> Stream for FastDfs<N>
+where
+    N: FastNode + Send + Sync + Clone + Unpin + 'static,
+    N::Error: Send + 'static,
+{
+    type Item = Result<N, N::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        // a node that is not yielded continues with its children first
+        loop {
+            // we first poll for the children of the newest node in dfs
+            match this.child_queues_futs.poll_next_unpin(cx) {
+                Poll::Ready(Some((depth, queue))) => {
+                    let queue = queue.unwrap_or_else(queue::Queue::from_err);
+                    this.stack.push((depth, queue));
+                }
+                // when there are no children to wait for,
+                // continue with the current level
+                Poll::Ready(None) => {}
+                // still waiting for the children
+                Poll::Pending => {
+                    return Poll::Pending;
+                }
+            }
+
+            // at this point, the last element in the stack is the current level
+            loop {
+                let next_item = this
+                    .stack
+                    .last_mut()
+                    .map(|(depth, queue)| (*depth, queue.pop_front()));
+
+                match next_item {
+                    // item failed
+                    Some((_, Some(Err(err)))) => {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    // item succeeded
+                    Some((depth, Some(Ok(node)))) => {
+                        if !*this.allow_circles {
+                            if this.visited.contains(&node) {
+                                crate::trace::skipped_visited(depth);
+                                continue;
+                            }
+                            this.visited.insert(node.clone());
+                        }
+
+                        let at_max_depth =
+                            this.max_depth.is_some_and(|max_depth| depth >= max_depth);
+                        if !at_max_depth {
+                            // add children future to be polled
+                            let arc_node = Arc::new(node.clone());
+                            this.child_queues_futs
+                                .push_front(queue::add_children(arc_node, depth + 1));
+                        }
+                        if depth >= *this.min_depth {
+                            return Poll::Ready(Some(Ok(node)));
+                        }
+                        // poll the children of the node first
+                        break;
+                    }
+                    // all items of this level have been consumed
+                    Some((_, None)) => {
+                        this.stack.pop();
+                    }
+                    // stack is empty and we are done
+                    None => {
+                        return Poll::Ready(None);
+                    }
+                }
+            }
+        }
+    }
+}
+
+type Expansion<N, E> = Result<NodeStream<N, E>, E>;
+
+type ExpansionFut<N, E> = Pin<Box<dyn Future<Output = (u64, Expansion<N, E>)> + Send + 'static>>;
+
+/// A node that was read ahead from the children of its parent,
+/// together with the id of its pending expansion, if any.
+type Slot<N, E> = Result<(N, Option<u64>), E>;
+
+/// The children of a node on the current path of a [`ConcurrentDfs`].
+struct Level<N, E> {
+    depth: usize,
+    stream: NodeStream<N, E>,
+    lookahead: VecDeque<Slot<N, E>>,
+    done: bool,
+}
+
+/// Asynchronous depth-first stream that expands nodes concurrently
+/// but yields them in the same order as [`Dfs`].
+///
+/// While the node at the front of the current level is yielded and its
+/// children are awaited, up to `limit` of the following nodes are read ahead
+/// and their [`Node::children`] futures are started concurrently.
+/// Their results are buffered until the traversal reaches them,
+/// such that the output order is deterministic and does not depend on
+/// which futures complete first.
+///
+/// At most `limit` nodes are read ahead in total, in addition to the node
+/// at the front of each level of the current path.
+/// When nodes are not allowed to be visited twice, a node that turns out to be
+/// visited once it is reached is dropped along with its expansion.
+///
 /// ### Example
 /// ```
 /// use futures::StreamExt;
-/// use par_dfs::r#async::{Node, Dfs, NodeStream};
+/// use par_dfs::r#async::{Node, ConcurrentDfs, NodeStream};
 ///
 /// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 /// struct WordNode(String);
@@ -43,7 +893,7 @@ use std::task::{Context, Poll};
 ///
 /// let result = tokio_test::block_on(async {
 ///     let root = WordNode("Hello World".into());
-///     let dfs = Dfs::<WordNode>::new(root, None, true);
+///     let dfs = ConcurrentDfs::<WordNode>::new(root, None, true, 8);
 ///     let output = dfs
 ///         .collect::<Vec<_>>()
 ///         .await
@@ -57,58 +907,103 @@ use std::task::{Context, Poll};
 /// assert_eq!(result, "Hello World");
 /// ```
 ///
-/// [`Node`]: trait@crate::async::Node
-#[derive(Default)]
+/// [`Dfs`]: struct@crate::async::Dfs
+/// [`Node::children`]: fn@crate::async::Node::children
+#[allow(clippy::module_name_repetitions)]
 #[pin_project]
-pub struct Dfs<N>
+pub struct ConcurrentDfs<N>
 where
     N: Node,
 {
-    stack: Stack<N, N::Error>,
-    child_streams_futs: StreamQueue<N, N::Error>,
+    stack: Vec<Level<N, N::Error>>,
+    expansions: FuturesUnordered<ExpansionFut<N, N::Error>>,
+    expanded: HashMap<u64, Expansion<N, N::Error>>,
+    discarded: HashSet<u64>,
+    awaiting: Option<(u64, usize)>,
+    next_id: u64,
+    buffered: usize,
+    limit: usize,
     max_depth: Option<usize>,
     allow_circles: bool,
     visited: HashSet<N>,
+    min_depth: usize,
 }
 
-impl<N> Dfs<N>
+impl<N> ConcurrentDfs<N>
 where
     N: Node + Send + Unpin + Clone + 'static,
     N::Error: Send + 'static,
 {
     #[inline]
-    /// Creates a new [`Dfs`] stream.
+    /// Creates a new [`ConcurrentDfs`] stream.
     ///
-    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    /// The DFS will be performed from the `root` node up to depth `max_depth`,
+    /// reading ahead and expanding at most `limit` nodes concurrently.
     ///
     /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
     ///
-    /// [`Dfs`]: struct@crate::async::Dfs
-    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    /// [`ConcurrentDfs`]: struct@crate::async::ConcurrentDfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool, limit: usize) -> Self
     where
         R: Into<N>,
         D: Into<Option<usize>>,
     {
         let root = root.into();
-        let max_depth = max_depth.into();
-        let mut child_streams_futs: StreamQueue<N, N::Error> = FuturesOrdered::new();
-        let depth = 1;
-        let child_stream_fut = Arc::new(root.clone())
-            .children(depth)
-            .map(move |stream| (depth, stream));
-        child_streams_futs.push_front(Box::pin(child_stream_fut));
-
-        Self {
+        let mut dfs = Self {
             stack: vec![],
-            child_streams_futs,
-            max_depth,
-            visited: HashSet::from_iter([root]),
+            expansions: FuturesUnordered::new(),
+            expanded: HashMap::new(),
+            discarded: HashSet::new(),
+            awaiting: None,
+            next_id: 0,
+            buffered: 0,
+            limit,
+            max_depth: max_depth.into(),
+            visited: HashSet::from_iter([root.clone()]),
             allow_circles,
+            min_depth: 0,
+        };
+        // the root is not expanded when it is at the depth limit
+        if dfs.max_depth != Some(0) {
+            let depth = 1;
+            let id = expand(&mut dfs.expansions, &mut dfs.next_id, root, depth);
+            dfs.awaiting = Some((id, depth));
         }
+        dfs
+    }
+
+    /// Only yields nodes at depth `min_depth` or deeper.
+    ///
+    /// Shallower nodes are still expanded, so deeper nodes remain reachable.
+    /// The children of the root are at depth 1.
+    /// Errors are always yielded.
+    #[must_use]
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
     }
 }
 
-impl<N> Stream for Dfs<N>
+/// Starts the expansion of `node` and returns its id.
+fn expand<N>(
+    expansions: &mut FuturesUnordered<ExpansionFut<N, N::Error>>,
+    next_id: &mut u64,
+    node: N,
+    depth: usize,
+) -> u64
+where
+    N: Node + Send + 'static,
+    N::Error: Send + 'static,
+{
+    let id = *next_id;
+    *next_id += 1;
+    let expansion = crate::trace::instrument_children(node.children_owned(depth), depth)
+        .map(move |stream| (id, stream));
+    expansions.push(Box::pin(expansion));
+    id
+}
+
+impl<N> Stream for ConcurrentDfs<N>
 where
     N: Node + Send + Clone + Unpin + 'static,
     N::Error: Send + 'static,
@@ -118,95 +1013,95 @@ where
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
 
-        // println!("------- poll");
-        // println!("stack size: {:?}", this.stack.len());
-
-        // we first poll for the newest child stream in dfs
-        // println!("child stream futs: {:?}", this.child_streams_futs.len());
-        match this.child_streams_futs.poll_next_unpin(cx) {
-            Poll::Ready(Some((depth, stream))) => {
-                // println!(
-                //     "child stream fut depth {} completed: {:?}",
-                //     depth,
-                //     stream.is_ok()
-                // );
+        loop {
+            // collect all completed expansions
+            while let Poll::Ready(Some((id, stream))) = this.expansions.poll_next_unpin(cx) {
+                if !this.discarded.remove(&id) {
+                    this.expanded.insert(id, stream);
+                }
+            }
+
+            // the children of the last yielded node come next
+            if let Some((id, depth)) = *this.awaiting {
+                let Some(stream) = this.expanded.remove(&id) else {
+                    return Poll::Pending;
+                };
                 let stream = match stream {
-                    Ok(stream) => stream.boxed(),
-                    Err(err) => futures::stream::iter([Err(err)]).boxed(),
+                    Ok(stream) => stream,
+                    Err(err) => Box::pin(futures::stream::iter([Err(err)]).boxed()),
                 };
-                this.stack.push((depth, Box::pin(stream)));
-                // println!("stack size: {}", this.stack.len());
-            }
-            // when there is no child stream future,
-            // continue to poll the current stream
-            Poll::Ready(None) => {
-                // println!("no child stream to wait for");
+                this.stack.push(Level {
+                    depth,
+                    stream,
+                    lookahead: VecDeque::new(),
+                    done: false,
+                });
+                *this.awaiting = None;
             }
-            // still waiting for the new child stream
-            Poll::Pending => {
-                // println!("child stream is still pending");
-                return Poll::Pending;
-            }
-        }
 
-        // at this point, the last element in the stack is the current level
-        loop {
-            let next_item = match this.stack.last_mut() {
-                Some((depth, current_stream)) => {
-                    let next_item = current_stream.as_mut().poll_next(cx);
-                    Some(next_item.map(|node| (depth, node)))
-                }
-                None => None,
+            let Some(level) = this.stack.last_mut() else {
+                // stack is empty and we are done
+                return Poll::Ready(None);
             };
 
-            // println!("next item: {:?}", next_item);
-            match next_item {
-                // stream item is ready but failure success
-                Some(Poll::Ready((_, Some(Err(err))))) => {
-                    return Poll::Ready(Some(Err(err)));
+            // read ahead and start expanding while within the limit
+            while !level.done && (level.lookahead.is_empty() || *this.buffered < *this.limit) {
+                match level.stream.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(Ok(node))) => {
+                        let at_max_depth = this
+                            .max_depth
+                            .is_some_and(|max_depth| level.depth >= max_depth);
+                        let id = if at_max_depth {
+                            None
+                        } else {
+                            let depth = level.depth + 1;
+                            let node = node.clone();
+                            Some(expand(this.expansions, this.next_id, node, depth))
+                        };
+                        level.lookahead.push_back(Ok((node, id)));
+                        *this.buffered += 1;
+                    }
+                    Poll::Ready(Some(Err(err))) => {
+                        level.lookahead.push_back(Err(err));
+                        *this.buffered += 1;
+                    }
+                    Poll::Ready(None) => level.done = true,
+                    Poll::Pending => break,
                 }
-                // stream item is ready and success
-                Some(Poll::Ready((depth, Some(Ok(node))))) => {
-                    if *this.allow_circles || !this.visited.contains(&node) {
-                        if !*this.allow_circles {
-                            this.visited.insert(node.clone());
-                        }
+            }
 
-                        if let Some(max_depth) = this.max_depth {
-                            if depth >= max_depth {
+            match level.lookahead.pop_front() {
+                Some(slot) => {
+                    *this.buffered -= 1;
+                    match slot {
+                        Err(err) => return Poll::Ready(Some(Err(err))),
+                        Ok((node, id)) => {
+                            if !*this.allow_circles {
+                                if this.visited.contains(&node) {
+                                    crate::trace::skipped_visited(level.depth);
+                                    // visited by now, drop the node and its expansion
+                                    if let Some(id) = id {
+                                        if this.expanded.remove(&id).is_none() {
+                                            this.discarded.insert(id);
+                                        }
+                                    }
+                                    continue;
+                                }
+                                this.visited.insert(node.clone());
+                            }
+                            *this.awaiting = id.map(|id| (id, level.depth + 1));
+                            if level.depth >= *this.min_depth {
                                 return Poll::Ready(Some(Ok(node)));
                             }
                         }
-
-                        // add child stream future to be polled
-                        let arc_node = Arc::new(node.clone());
-                        let next_depth = *depth + 1;
-                        let child_stream_fut = arc_node
-                            .children(next_depth)
-                            .map(move |stream| (next_depth, stream));
-                        this.child_streams_futs
-                            .push_front(Box::pin(child_stream_fut));
-
-                        return Poll::Ready(Some(Ok(node)));
                     }
                 }
-                // stream completed for this level completed
-                Some(Poll::Ready((_, None))) => {
+                // the children of this level are exhausted
+                None if level.done => {
                     this.stack.pop();
-                    // println!("pop stack to size: {}", this.stack.len());
-                    // try again in the next round
-                    // returning Poll::Pending here is bad because the runtime can not know when to poll
-                    // us again to make progress since we never passed the cx to poll of the next
-                    // level stream
-                }
-                // stream item is pending
-                Some(Poll::Pending) => {
-                    return Poll::Pending;
-                }
-                // stack is empty and we are done
-                None => {
-                    return Poll::Ready(None);
                 }
+                // still waiting for the next child
+                None => return Poll::Pending,
             }
         }
     }
@@ -214,7 +1109,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::Dfs;
+    use super::{ConcurrentDfs, Dfs, FastDfs};
     use anyhow::Result;
 
     macro_rules! depths {
@@ -291,6 +1186,96 @@ mod tests {
         }
     }
 
+    test_depths!(
+        dfs_leaves:
+        (
+            Dfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true),
+            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        dfs_max_depth_zero:
+        (
+            Dfs::<crate::utils::test::LeafNode>::new(0, 0, true),
+            [] as [usize; 0]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        fast_dfs_leaves:
+        (
+            FastDfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true),
+            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        fast_dfs_max_depth_zero:
+        (
+            FastDfs::<crate::utils::test::LeafNode>::new(0, 0, true),
+            [] as [usize; 0]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        concurrent_dfs_leaves:
+        (
+            ConcurrentDfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true, 4),
+            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        concurrent_dfs_max_depth_zero:
+        (
+            ConcurrentDfs::<crate::utils::test::LeafNode>::new(0, 0, true, 4),
+            [] as [usize; 0]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        dfs_min_depth:
+        (
+            Dfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2),
+            [2, 3, 3, 2, 3, 3, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        fast_dfs_min_depth:
+        (
+            FastDfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2),
+            [2, 3, 3, 2, 3, 3, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        concurrent_dfs_min_depth:
+        (
+            ConcurrentDfs::<crate::utils::test::Node>::new(0, 3, true, 4).min_depth(2),
+            [2, 3, 3, 2, 3, 3, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
     test_depths!(
         dfs:
         (
@@ -301,6 +1286,16 @@ mod tests {
         test_depths_unordered,
     );
 
+    test_depths!(
+        dfs_budget:
+        (
+            Dfs::<crate::utils::test::Node>::new(0, 3, true).with_budget(2, |_| 1),
+            [1, 2, 3, 3, 2, 1]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
     test_depths!(
         dfs_no_circles:
         (
@@ -310,4 +1305,419 @@ mod tests {
         test_depths_ordered,
         test_depths_unordered,
     );
+
+    test_depths!(
+        fast_dfs:
+        (
+            FastDfs::<crate::utils::test::Node>::new(0, 3, true),
+            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        fast_dfs_no_circles:
+        (
+            FastDfs::<crate::utils::test::Node>::new(0, 3, false),
+            [1, 2, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        concurrent_dfs:
+        (
+            ConcurrentDfs::<crate::utils::test::Node>::new(0, 3, true, 4),
+            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    test_depths!(
+        concurrent_dfs_no_circles:
+        (
+            ConcurrentDfs::<crate::utils::test::Node>::new(0, 3, false, 4),
+            [1, 2, 3]
+        ),
+        test_depths_ordered,
+        test_depths_unordered,
+    );
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_children_owned() -> Result<()> {
+        use crate::r#async::{Bfs, Node, NodeStream};
+        use futures::StreamExt;
+        use std::sync::Arc;
+
+        /// A binary tree node that is only ever expanded by value.
+        #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+        struct CopyNode(usize);
+
+        #[async_trait::async_trait]
+        impl Node for CopyNode {
+            type Error = std::convert::Infallible;
+
+            async fn children(
+                self: Arc<Self>,
+                _depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                unreachable!("children requested from an Arc");
+            }
+
+            async fn children_owned(
+                self,
+                _depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                let nodes = [Ok(Self(self.0 * 2 + 1)), Ok(Self(self.0 * 2 + 2))];
+                Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+            }
+        }
+
+        let ids = |nodes: Vec<Result<CopyNode, _>>| {
+            nodes
+                .into_iter()
+                .map(|node| node.map(|node| node.0))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let dfs = Dfs::<CopyNode>::new(CopyNode(0), 2, false)
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(ids(dfs)?, [1, 3, 4, 2, 5, 6]);
+        let bfs = Bfs::<CopyNode>::new(CopyNode(0), 2, false)
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(ids(bfs)?, [1, 2, 3, 4, 5, 6]);
+        let concurrent = ConcurrentDfs::<CopyNode>::new(CopyNode(0), 2, false, 4)
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(ids(concurrent)?.len(), 6);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancel_on_drops_pending_children() -> Result<()> {
+        use crate::r#async::{Bfs, Node, NodeStream};
+        use futures::{Stream, StreamExt};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::sync::oneshot;
+        use tokio::time::{timeout, Duration};
+
+        static CANCELLED: AtomicUsize = AtomicUsize::new(0);
+
+        /// Counts the requests of child streams that are dropped before they resolve.
+        struct Guard;
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                CANCELLED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        /// A node whose children below the root never resolve.
+        #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+        struct HangingNode(usize);
+
+        #[async_trait::async_trait]
+        impl Node for HangingNode {
+            type Error = std::convert::Infallible;
+
+            async fn children(
+                self: Arc<Self>,
+                depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                if depth > 1 {
+                    let _guard = Guard;
+                    std::future::pending::<()>().await;
+                }
+                let nodes = [Ok(Self(1)), Ok(Self(2))];
+                Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+            }
+        }
+
+        async fn cancel<S>(
+            stream: impl FnOnce(oneshot::Receiver<()>) -> S,
+            yielded: usize,
+        ) -> Result<usize>
+        where
+            S: Stream + Unpin,
+        {
+            let (sender, receiver) = oneshot::channel();
+            let mut stream = stream(receiver);
+            for _ in 0..yielded {
+                assert!(stream.next().await.is_some());
+            }
+            // the children of the yielded nodes never resolve
+            assert!(timeout(Duration::from_millis(50), stream.next())
+                .await
+                .is_err());
+            let cancelled = CANCELLED.load(Ordering::SeqCst);
+            let _ = sender.send(());
+            assert!(stream.next().await.is_none());
+            assert!(stream.next().await.is_none());
+            Ok(CANCELLED.load(Ordering::SeqCst) - cancelled)
+        }
+
+        let signal = |receiver: oneshot::Receiver<()>| async {
+            let _ = receiver.await;
+        };
+        let dfs = cancel(
+            |receiver| {
+                Dfs::<HangingNode>::new(HangingNode(0), None, true).cancel_on(signal(receiver))
+            },
+            1,
+        );
+        similar_asserts::assert_eq!(dfs.await?, 1);
+        let bfs = cancel(
+            |receiver| {
+                Bfs::<HangingNode>::new(HangingNode(0), None, true).cancel_on(signal(receiver))
+            },
+            2,
+        );
+        similar_asserts::assert_eq!(bfs.await?, 2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dfs_find_map_node_does_not_expand_match() -> Result<()> {
+        use crate::utils::test::{LeafNode, LEAF_DEPTH};
+
+        // expanding a leaf panics, so the match must not be expanded
+        let found = Dfs::<LeafNode>::new(0, None, true)
+            .find_map_node(|node| node.0 == LEAF_DEPTH)
+            .await;
+        similar_asserts::assert_eq!(found.transpose()?, Some(LeafNode(LEAF_DEPTH)));
+
+        let found = Dfs::<LeafNode>::new(0, LEAF_DEPTH, true)
+            .find_map_node(|node| node.0 > LEAF_DEPTH)
+            .await;
+        assert!(found.is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "bridge")]
+    #[test]
+    fn test_dfs_blocking_iter() -> Result<()> {
+        use crate::utils::test::Node;
+        use futures::StreamExt;
+
+        let runtime = tokio::runtime::Runtime::new()?;
+        let expected = runtime.block_on(async {
+            Dfs::<Node>::new(0, 3, true)
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        let nodes = Dfs::<Node>::new(0, 3, true)
+            .blocking_iter(runtime.handle().clone())
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(nodes, expected);
+
+        // the unbounded traversal never completes, so it must not be buffered
+        let depths = Dfs::<Node>::new(0, None, true)
+            .blocking_iter(runtime.handle().clone())
+            .take(5)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(depths, [1, 2, 3, 4, 5]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dfs_requests_one_child_stream_at_a_time() -> Result<()> {
+        use crate::r#async::{Node, NodeStream};
+        use futures::StreamExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::time::{sleep, Duration};
+
+        static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+        static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+        struct WideNode(usize);
+
+        #[async_trait::async_trait]
+        impl Node for WideNode {
+            type Error = std::convert::Infallible;
+
+            async fn children(
+                self: Arc<Self>,
+                _depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+                MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+                sleep(Duration::from_millis(1)).await;
+                IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+                let id = self.0;
+                let nodes = (0..4).map(move |i| Ok(Self(id * 4 + i + 1)));
+                Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+            }
+        }
+
+        // even spawned requests never overlap, which for_each_limited relies on
+        let ids = Dfs::<WideNode>::with_spawner(WideNode(0), 3, false, tokio::spawn)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids.len(), 4 + 16 + 64);
+        similar_asserts::assert_eq!(MAX_IN_FLIGHT.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dfs_for_each_limited() -> Result<()> {
+        use crate::r#async::{Node, NodeStream};
+        use futures::StreamExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+        use tokio::time::{sleep, Duration};
+
+        /// The number of callbacks and child stream requests in flight, and its maximum.
+        static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+        static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+        async fn track() {
+            let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+            MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+            sleep(Duration::from_millis(5)).await;
+            IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+        struct TrackedNode(usize);
+
+        #[async_trait::async_trait]
+        impl Node for TrackedNode {
+            type Error = std::convert::Infallible;
+
+            async fn children(
+                self: Arc<Self>,
+                depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                track().await;
+                let nodes = [Ok(Self(depth)), Ok(Self(depth))];
+                Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+            }
+        }
+
+        for limit in [1, 3] {
+            MAX_IN_FLIGHT.store(0, Ordering::SeqCst);
+            let depths = Arc::new(Mutex::new(Vec::new()));
+            Dfs::<TrackedNode>::new(TrackedNode(0), 3, true)
+                .for_each_limited(limit, |node| {
+                    let depths = Arc::clone(&depths);
+                    async move {
+                        track().await;
+                        depths.lock().unwrap().push(node.unwrap().0);
+                    }
+                })
+                .await;
+            let mut depths = depths.lock().unwrap().clone();
+            depths.sort_unstable();
+            similar_asserts::assert_eq!(depths, [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]);
+            similar_asserts::assert_eq!(MAX_IN_FLIGHT.load(Ordering::SeqCst), limit);
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dfs_gauges() -> Result<()> {
+        use crate::r#async::Bfs;
+        use futures::StreamExt;
+
+        let mut dfs = Dfs::<crate::utils::test::Node>::new(0, 3, true);
+        similar_asserts::assert_eq!((dfs.stack_depth(), dfs.pending_streams()), (0, 0));
+        // every yielded node requests its children, except at the depth limit
+        for (stack_depth, pending_streams) in [(1, 1), (2, 1), (3, 0)] {
+            assert!(dfs.next().await.transpose()?.is_some());
+            similar_asserts::assert_eq!(
+                (dfs.stack_depth(), dfs.pending_streams()),
+                (stack_depth, pending_streams)
+            );
+        }
+        similar_asserts::assert_eq!(dfs.by_ref().count().await, 11);
+        similar_asserts::assert_eq!((dfs.stack_depth(), dfs.pending_streams()), (0, 0));
+
+        let mut bfs = Bfs::<crate::utils::test::Node>::new(0, 3, true).max_concurrency(1);
+        similar_asserts::assert_eq!((bfs.pending_streams(), bfs.deferred_streams()), (0, 0));
+        for deferred_streams in [0, 1] {
+            assert!(bfs.next().await.transpose()?.is_some());
+            similar_asserts::assert_eq!(
+                (bfs.pending_streams(), bfs.deferred_streams()),
+                (1, deferred_streams)
+            );
+        }
+        similar_asserts::assert_eq!(bfs.by_ref().count().await, 12);
+        similar_asserts::assert_eq!((bfs.pending_streams(), bfs.deferred_streams()), (0, 0));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dfs_split_off() -> Result<()> {
+        use crate::utils::test::TreeNode;
+        use futures::StreamExt;
+
+        let ids = |nodes: Vec<Result<TreeNode, _>>| {
+            nodes
+                .into_iter()
+                .map(|node| node.map(|node| node.0))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 2, false);
+        assert!(dfs.split_off().is_none());
+        let yielded = ids(dfs.by_ref().take(2).collect().await)?;
+        similar_asserts::assert_eq!(yielded, [1, 3]);
+        // the remaining sibling of the first node is split off
+        let split = dfs.split_off().unwrap();
+        similar_asserts::assert_eq!((dfs.stack_depth(), split.stack_depth()), (1, 1));
+        similar_asserts::assert_eq!(ids(split.collect().await)?, [2, 5, 6]);
+        similar_asserts::assert_eq!(ids(dfs.collect().await)?, [4]);
+
+        // both halves can be consumed on separate tasks
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 10, false);
+        let mut yielded = ids(dfs.by_ref().take(4).collect().await)?;
+        let split = dfs.split_off().unwrap();
+        let handles = [dfs, split].map(|half| tokio::spawn(half.collect::<Vec<_>>()));
+        for handle in handles {
+            yielded.extend(ids(handle.await?)?);
+        }
+        yielded.sort_unstable();
+        similar_asserts::assert_eq!(yielded, (1..2047).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_dfs_matches_dfs() -> Result<()> {
+        use crate::utils::test::GraphNode;
+        use futures::StreamExt;
+
+        let edges: &[&[usize]] = &[&[1, 2, 3], &[4, 5], &[5, 6], &[1, 6], &[0], &[], &[2]];
+        for allow_circles in [false, true] {
+            for limit in [1, 2, 8] {
+                let root = GraphNode::new(0, edges);
+                let expected = Dfs::<GraphNode>::new(root, 4, allow_circles)
+                    .map(|node| node.map(|node| node.id))
+                    .collect::<Vec<_>>()
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?;
+                let ids = ConcurrentDfs::<GraphNode>::new(root, 4, allow_circles, limit)
+                    .map(|node| node.map(|node| node.id))
+                    .collect::<Vec<_>>()
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?;
+                similar_asserts::assert_eq!(ids, expected);
+            }
+        }
+        Ok(())
+    }
 }
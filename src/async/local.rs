@@ -0,0 +1,484 @@
+//! Asynchronous traversals for nodes that are not [`Send`].
+//!
+//! In contrast to [`Node`], [`LocalNode::children`] takes `self: Rc<Self>`
+//! and returns a stream that is not [`Send`], which avoids atomic reference
+//! counting and allows `!Send` payloads.
+//! The traversals must be polled on the thread they were created on,
+//! e.g. within a [`LocalSet`] or using [`block_on`].
+//!
+//! [`Send`]: trait@std::marker::Send
+//! [`Node`]: trait@crate::async::Node
+//! [`LocalNode::children`]: fn@crate::async::local::LocalNode::children
+//! [`LocalSet`]: https://docs.rs/tokio/latest/tokio/task/struct.LocalSet.html
+//! [`block_on`]: fn@futures::executor::block_on
+
+use async_trait::async_trait;
+use futures::stream::{FuturesOrdered, Stream, StreamExt};
+use futures::{Future, FutureExt};
+use pin_project::pin_project;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// A pinned [`Stream`] of [`LocalNode`]s, which is not [`Send`].
+///
+/// [`Stream`]: trait@futures::stream::Stream
+/// [`LocalNode`]: trait@crate::async::local::LocalNode
+/// [`Send`]: trait@std::marker::Send
+pub type LocalNodeStream<N, E> = Pin<Box<dyn Stream<Item = Result<N, E>>>>;
+
+type LocalStack<N, E> = Vec<(usize, LocalNodeStream<N, E>)>;
+
+type LocalNewNodesFut<N, E> =
+    Pin<Box<dyn Future<Output = (usize, Result<LocalNodeStream<N, E>, E>)>>>;
+
+type LocalStreamQueue<N, E> = FuturesOrdered<LocalNewNodesFut<N, E>>;
+
+#[async_trait(?Send)]
+/// A node which produces a [`Stream`] of children [`LocalNode`]s for a
+/// given depth, without requiring [`Send`].
+///
+/// [`Stream`]: trait@futures::stream::Stream
+/// [`LocalNode`]: trait@crate::async::local::LocalNode
+/// [`Send`]: trait@std::marker::Send
+pub trait LocalNode
+where
+    Self: Sized + Hash + Eq + std::fmt::Debug,
+{
+    /// The type of the error when creating the stream fails.
+    type Error: std::fmt::Debug;
+
+    /// Returns a [`LocalNodeStream`] of its children.
+    ///
+    /// # Errors
+    ///
+    /// Should return [`Self::Error`] if the stream can not be created.
+    ///
+    /// [`LocalNodeStream`]: type@crate::async::local::LocalNodeStream
+    /// [`Self::Error`]: type@crate::async::local::LocalNode::Error
+    async fn children(
+        self: Rc<Self>,
+        depth: usize,
+    ) -> Result<LocalNodeStream<Self, Self::Error>, Self::Error>;
+}
+
+/// Returns the future of the child stream of `node` at `depth`.
+fn child_stream_fut<N>(node: N, depth: usize) -> LocalNewNodesFut<N, N::Error>
+where
+    N: LocalNode + 'static,
+{
+    let child_stream_fut = Rc::new(node)
+        .children(depth)
+        .map(move |stream| (depth, stream));
+    Box::pin(child_stream_fut)
+}
+
+/// Asynchronous depth-first stream for types implementing the [`LocalNode`] trait.
+///
+/// Like a [`Dfs`], but neither the nodes nor the stream are [`Send`].
+///
+/// ### Example
+/// ```
+/// use futures::StreamExt;
+/// use par_dfs::r#async::local::{LocalDfs, LocalNode, LocalNodeStream};
+/// use std::rc::Rc;
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct WordNode(Rc<str>);
+///
+/// #[async_trait::async_trait(?Send)]
+/// impl LocalNode for WordNode {
+///     type Error = std::convert::Infallible;
+///
+///     async fn children(
+///         self: Rc<Self>,
+///         _depth: usize
+///     ) -> Result<LocalNodeStream<Self, Self::Error>, Self::Error> {
+///         let len = self.0.len();
+///         let nodes: Vec<Rc<str>> = if len > 1 {
+///             let mid = len/2;
+///             vec![self.0[..mid].into(), self.0[mid..].into()]
+///         } else {
+///             vec![]
+///         };
+///         let nodes = nodes.into_iter()
+///             .map(Self)
+///             .map(Result::Ok);
+///         Ok(Box::pin(futures::stream::iter(nodes)))
+///     }
+/// }
+///
+/// let result = futures::executor::block_on(async {
+///     let root = WordNode("Hello World".into());
+///     let output = LocalDfs::<WordNode>::new(root, None, true)
+///         .collect::<Vec<_>>()
+///         .await
+///         .into_iter()
+///         .collect::<Result<Vec<_>, _>>()
+///         .unwrap();
+///     output.into_iter()
+///         .filter(|s| s.0.len() == 1)
+///         .map(|s| s.0.to_string())
+///         .collect::<String>()
+/// });
+/// assert_eq!(result, "Hello World");
+/// ```
+///
+/// [`LocalNode`]: trait@crate::async::local::LocalNode
+/// [`Dfs`]: struct@crate::async::Dfs
+/// [`Send`]: trait@std::marker::Send
+#[allow(clippy::module_name_repetitions)]
+#[pin_project]
+pub struct LocalDfs<N>
+where
+    N: LocalNode,
+{
+    stack: LocalStack<N, N::Error>,
+    root: Option<N>,
+    child_streams_futs: LocalStreamQueue<N, N::Error>,
+    max_depth: Option<usize>,
+    allow_circles: bool,
+    visited: HashSet<N>,
+    min_depth: usize,
+}
+
+impl<N> LocalDfs<N>
+where
+    N: LocalNode + Clone + 'static,
+{
+    #[inline]
+    /// Creates a new [`LocalDfs`] stream.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`LocalDfs`]: struct@crate::async::local::LocalDfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let root = root.into();
+        Self {
+            stack: vec![],
+            root: Some(root.clone()),
+            child_streams_futs: FuturesOrdered::new(),
+            max_depth: max_depth.into(),
+            visited: HashSet::from_iter([root]),
+            allow_circles,
+            min_depth: 0,
+        }
+    }
+
+    /// Only yields nodes at depth `min_depth` or deeper.
+    ///
+    /// See [`Dfs::min_depth`] for details.
+    ///
+    /// [`Dfs::min_depth`]: fn@crate::async::Dfs::min_depth
+    #[must_use]
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+}
+
+impl<N> Stream for LocalDfs<N>
+where
+    N: LocalNode + Clone + 'static,
+{
+    type Item = Result<N, N::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        // the root is expanded on the first poll, unless it is at the depth limit
+        if let Some(root) = this.root.take() {
+            if *this.max_depth != Some(0) {
+                this.child_streams_futs
+                    .push_front(child_stream_fut(root, 1));
+            }
+        }
+
+        // a node that is not yielded continues with its children first
+        loop {
+            // the newest child stream is the next level
+            match this.child_streams_futs.poll_next_unpin(cx) {
+                Poll::Ready(Some((depth, stream))) => {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(err) => Box::pin(futures::stream::iter([Err(err)])),
+                    };
+                    this.stack.push((depth, stream));
+                }
+                // when there is no child stream future,
+                // continue to poll the current stream
+                Poll::Ready(None) => {}
+                // still waiting for the new child stream
+                Poll::Pending => return Poll::Pending,
+            }
+
+            // at this point, the last element in the stack is the current level
+            loop {
+                let Some((depth, current_stream)) = this.stack.last_mut() else {
+                    // stack is empty and we are done
+                    return Poll::Ready(None);
+                };
+                let depth = *depth;
+                match current_stream.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                    Poll::Ready(Some(Ok(node))) => {
+                        if !*this.allow_circles {
+                            if this.visited.contains(&node) {
+                                continue;
+                            }
+                            this.visited.insert(node.clone());
+                        }
+                        if !this.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                            this.child_streams_futs
+                                .push_front(child_stream_fut(node.clone(), depth + 1));
+                        }
+                        if depth >= *this.min_depth {
+                            return Poll::Ready(Some(Ok(node)));
+                        }
+                        // poll the children of the node first
+                        break;
+                    }
+                    // the stream of this level completed
+                    Poll::Ready(None) => {
+                        this.stack.pop();
+                    }
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+        }
+    }
+}
+
+/// Asynchronous breadth-first stream for types implementing the [`LocalNode`] trait.
+///
+/// Like a [`Bfs`], but neither the nodes nor the stream are [`Send`].
+/// See [`LocalDfs`] for an example.
+///
+/// [`LocalNode`]: trait@crate::async::local::LocalNode
+/// [`LocalDfs`]: struct@crate::async::local::LocalDfs
+/// [`Bfs`]: struct@crate::async::Bfs
+/// [`Send`]: trait@std::marker::Send
+#[allow(clippy::module_name_repetitions)]
+#[pin_project]
+pub struct LocalBfs<N>
+where
+    N: LocalNode,
+{
+    current_stream: Option<(usize, LocalNodeStream<N, N::Error>)>,
+    root: Option<N>,
+    child_streams_futs: LocalStreamQueue<N, N::Error>,
+    max_depth: Option<usize>,
+    allow_circles: bool,
+    visited: HashSet<N>,
+    min_depth: usize,
+}
+
+impl<N> LocalBfs<N>
+where
+    N: LocalNode + Clone + 'static,
+{
+    #[inline]
+    /// Creates a new [`LocalBfs`] stream.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`LocalBfs`]: struct@crate::async::local::LocalBfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let root = root.into();
+        Self {
+            current_stream: None,
+            root: Some(root.clone()),
+            child_streams_futs: FuturesOrdered::new(),
+            max_depth: max_depth.into(),
+            visited: HashSet::from_iter([root]),
+            allow_circles,
+            min_depth: 0,
+        }
+    }
+
+    /// Only yields nodes at depth `min_depth` or deeper.
+    ///
+    /// See [`Bfs::min_depth`] for details.
+    ///
+    /// [`Bfs::min_depth`]: fn@crate::async::Bfs::min_depth
+    #[must_use]
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+}
+
+impl<N> Stream for LocalBfs<N>
+where
+    N: LocalNode + Clone + 'static,
+{
+    type Item = Result<N, N::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        // the root is expanded on the first poll, unless it is at the depth limit
+        if let Some(root) = this.root.take() {
+            if *this.max_depth != Some(0) {
+                this.child_streams_futs.push_back(child_stream_fut(root, 1));
+            }
+        }
+
+        loop {
+            if let Some((depth, stream)) = this.current_stream.as_mut() {
+                let depth = *depth;
+                match stream.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                    Poll::Ready(Some(Ok(node))) => {
+                        if !*this.allow_circles {
+                            if this.visited.contains(&node) {
+                                continue;
+                            }
+                            this.visited.insert(node.clone());
+                        }
+                        if !this.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                            this.child_streams_futs
+                                .push_back(child_stream_fut(node.clone(), depth + 1));
+                        }
+                        if depth >= *this.min_depth {
+                            return Poll::Ready(Some(Ok(node)));
+                        }
+                        continue;
+                    }
+                    Poll::Pending => return Poll::Pending,
+                    // proceed to poll the next stream
+                    Poll::Ready(None) => {}
+                }
+            }
+
+            match this.child_streams_futs.poll_next_unpin(cx) {
+                Poll::Ready(Some((depth, stream))) => {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(err) => Box::pin(futures::stream::iter([Err(err)])),
+                    };
+                    *this.current_stream = Some((depth, stream));
+                }
+                // when there are no more child stream futures, we are done
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalBfs, LocalDfs, LocalNode, LocalNodeStream};
+    use crate::r#async::{Bfs, Dfs};
+    use crate::utils::test::GraphNode;
+    use anyhow::Result;
+    use futures::StreamExt;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A [`GraphNode`] that counts its expansions in a `!Send` counter.
+    #[derive(Clone, Debug)]
+    struct RcGraphNode {
+        node: GraphNode,
+        expanded: Rc<Cell<usize>>,
+    }
+
+    impl PartialEq for RcGraphNode {
+        fn eq(&self, other: &Self) -> bool {
+            self.node == other.node
+        }
+    }
+
+    impl Eq for RcGraphNode {}
+
+    impl std::hash::Hash for RcGraphNode {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.node.hash(state);
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl LocalNode for RcGraphNode {
+        type Error = crate::utils::test::Error;
+
+        async fn children(
+            self: Rc<Self>,
+            _depth: usize,
+        ) -> Result<LocalNodeStream<Self, Self::Error>, Self::Error> {
+            tokio::task::yield_now().await;
+            self.expanded.set(self.expanded.get() + 1);
+            let expanded = Rc::clone(&self.expanded);
+            let nodes = self.node.neighbors().map(move |node| {
+                Ok(Self {
+                    node,
+                    expanded: Rc::clone(&expanded),
+                })
+            });
+            Ok(Box::pin(futures::stream::iter(nodes.collect::<Vec<_>>())))
+        }
+    }
+
+    #[test]
+    fn test_local_matches_async() -> Result<()> {
+        let edges: &[&[usize]] = &[&[1, 2, 3], &[4, 5], &[5, 6], &[1, 6], &[0], &[], &[2]];
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()?;
+        let local = tokio::task::LocalSet::new();
+        local.block_on(&runtime, async {
+            for allow_circles in [false, true] {
+                let expanded = Rc::new(Cell::new(0));
+                let root = RcGraphNode {
+                    node: GraphNode::new(0, edges),
+                    expanded: Rc::clone(&expanded),
+                };
+                let ids = LocalDfs::<RcGraphNode>::new(root.clone(), 4, allow_circles)
+                    .map(|node| node.map(|node| node.node.id))
+                    .collect::<Vec<_>>()
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?;
+                let expected = Dfs::<GraphNode>::new(root.node, 4, allow_circles)
+                    .map(|node| node.map(|node| node.id))
+                    .collect::<Vec<_>>()
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?;
+                similar_asserts::assert_eq!(ids, expected);
+                assert!(expanded.get() > 0);
+
+                let ids = LocalBfs::<RcGraphNode>::new(root.clone(), 4, allow_circles)
+                    .min_depth(2)
+                    .map(|node| node.map(|node| node.node.id))
+                    .collect::<Vec<_>>()
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?;
+                let expected = Bfs::<GraphNode>::new(root.node, 4, allow_circles)
+                    .min_depth(2)
+                    .map(|node| node.map(|node| node.id))
+                    .collect::<Vec<_>>()
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?;
+                similar_asserts::assert_eq!(ids, expected);
+            }
+            Ok(())
+        })
+    }
+}
@@ -1,13 +1,31 @@
 pub mod bfs;
 pub mod dfs;
+pub mod local;
+#[cfg(feature = "rayon")]
+mod pool;
+mod queue;
+#[cfg(feature = "retry")]
+mod retry;
+mod spawner;
+#[cfg(feature = "timeout")]
+mod timeout;
 
-pub use bfs::Bfs;
-pub use dfs::Dfs;
+pub use bfs::{Bfs, FastBfs, LevelBfs};
+pub use dfs::{ConcurrentDfs, Dfs, FastDfs};
+pub use local::{LocalBfs, LocalDfs, LocalNode, LocalNodeStream};
+#[cfg(feature = "retry")]
+#[cfg_attr(docsrs, doc(cfg(feature = "retry")))]
+pub use retry::RetryPolicy;
+pub use spawner::ChildrenTask;
+#[cfg(feature = "timeout")]
+#[cfg_attr(docsrs, doc(cfg(feature = "timeout")))]
+pub use timeout::TimeoutError;
 
 use async_trait::async_trait;
 use futures::stream::{FuturesOrdered, Stream};
-use futures::Future;
+use futures::{Future, FutureExt};
 use std::hash::Hash;
+use std::marker::PhantomData;
 use std::pin::Pin;
 use std::sync::Arc;
 
@@ -18,6 +36,150 @@ type NewNodesFut<N, E> =
 
 type StreamQueue<N, E> = FuturesOrdered<NewNodesFut<N, E>>;
 
+/// A signal that cancels a traversal once it resolves.
+type CancelFut = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Returns the future of the child stream of `node` at `depth`.
+fn child_stream_fut<N>(node: N, depth: usize) -> NewNodesFut<N, N::Error>
+where
+    N: Node + Send + 'static,
+    N::Error: Send + 'static,
+{
+    let child_stream_fut = crate::trace::instrument_children(node.children_owned(depth), depth)
+        .map(move |stream| (depth, stream));
+    Box::pin(child_stream_fut)
+}
+
+type FastStack<N, E> = Vec<(usize, queue::Queue<N, E>)>;
+
+type NewChildrenFut<N, E> =
+    Pin<Box<dyn Future<Output = (usize, Result<queue::Queue<N, E>, E>)> + Send + 'static>>;
+
+type ChildrenQueue<N, E> = FuturesOrdered<NewChildrenFut<N, E>>;
+
+type CostFn<N> = dyn Fn(&N) -> u64 + Send + Sync;
+
+/// Accumulates the cost of yielded nodes up to a budget.
+struct Budget<N> {
+    cost_fn: Arc<CostFn<N>>,
+    budget: u64,
+    spent: u64,
+}
+
+impl<N> Budget<N> {
+    #[inline]
+    fn new<F>(budget: u64, cost_fn: F) -> Self
+    where
+        F: Fn(&N) -> u64 + Send + Sync + 'static,
+    {
+        Self {
+            cost_fn: Arc::new(cost_fn),
+            budget,
+            spent: 0,
+        }
+    }
+
+    /// Adds the cost of `node` and returns whether the budget is exceeded.
+    #[inline]
+    fn spend(&mut self, node: &N) -> bool {
+        self.spent = self.spent.saturating_add((self.cost_fn)(node));
+        self.spent > self.budget
+    }
+
+    /// Moves half of the remaining budget into a new budget.
+    #[inline]
+    fn split(&mut self) -> Self {
+        let remaining = self.budget.saturating_sub(self.spent);
+        self.budget -= remaining / 2;
+        Self {
+            cost_fn: Arc::clone(&self.cost_fn),
+            budget: remaining / 2,
+            spent: 0,
+        }
+    }
+}
+
+/// The options that wrap every request of the children of a node.
+struct Request<E> {
+    #[cfg(feature = "timeout")]
+    timeout: Option<timeout::Timeout<E>>,
+    #[cfg(feature = "retry")]
+    retry: Option<RetryPolicy>,
+    #[cfg(feature = "rayon")]
+    pool: Option<pool::Pool>,
+    spawner: Option<spawner::Spawner>,
+    error: PhantomData<fn() -> E>,
+}
+
+impl<E> Default for Request<E> {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "timeout")]
+            timeout: None,
+            #[cfg(feature = "retry")]
+            retry: None,
+            #[cfg(feature = "rayon")]
+            pool: None,
+            spawner: None,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<E> Clone for Request<E> {
+    fn clone(&self) -> Self {
+        Self {
+            #[cfg(feature = "timeout")]
+            timeout: self.timeout.clone(),
+            #[cfg(feature = "retry")]
+            retry: self.retry,
+            #[cfg(feature = "rayon")]
+            pool: self.pool.clone(),
+            spawner: self.spawner.clone(),
+            error: PhantomData,
+        }
+    }
+}
+
+impl<E> Request<E>
+where
+    E: Send + 'static,
+{
+    /// Returns the future of the child stream of `node` at `depth`.
+    ///
+    /// Retries wait for their backoff on the executor of the traversal,
+    /// and are requested like the first attempt.
+    fn child_stream_fut<N>(&self, node: N, depth: usize) -> NewNodesFut<N, E>
+    where
+        N: Node<Error = E> + Send + Clone + 'static,
+    {
+        let fut = self.attempt(node.clone(), depth);
+        #[cfg(feature = "retry")]
+        let fut = {
+            let request = self.clone();
+            retry::wrap(self.retry.as_ref(), fut, move || {
+                request.attempt(node.clone(), depth)
+            })
+        };
+        fut
+    }
+
+    /// Returns the future of a single attempt to request the child stream
+    /// of `node` at `depth`, which is limited by the timeout.
+    fn attempt<N>(&self, node: N, depth: usize) -> NewNodesFut<N, E>
+    where
+        N: Node<Error = E> + Send + 'static,
+    {
+        let fut = child_stream_fut(node, depth);
+        #[cfg(feature = "rayon")]
+        let fut = pool::wrap(self.pool.as_ref(), fut);
+        let fut = spawner::wrap(self.spawner.as_ref(), fut);
+        #[cfg(feature = "timeout")]
+        let fut = timeout::wrap(self.timeout.as_ref(), fut, depth);
+        fut
+    }
+}
+
 /// A pinned [`Stream`] of [`Node`]s
 ///
 /// [`Stream`]: trait@futures::stream::Stream
@@ -48,6 +210,106 @@ where
         self: Arc<Self>,
         depth: usize,
     ) -> Result<NodeStream<Self, Self::Error>, Self::Error>;
+
+    /// Returns a [`NodeStream`] of its children, taking the node by value.
+    ///
+    /// Used by the traversals to expand a node.
+    /// Defaults to moving the node into an [`Arc`] for [`children`], which
+    /// allocates for every expanded node.
+    /// Override this for [`Copy`] nodes, or nodes that are otherwise cheap to
+    /// move, to avoid the allocation.
+    ///
+    /// # Errors
+    ///
+    /// Should return [`Self::Error`] if the stream can not be created.
+    ///
+    /// [`NodeStream`]: type@crate::async::NodeStream
+    /// [`Arc`]: struct@std::sync::Arc
+    /// [`children`]: Self::children
+    /// [`Copy`]: trait@std::marker::Copy
+    /// [`Self::Error`]: type@crate::async::Node::Error
+    async fn children_owned(
+        self,
+        depth: usize,
+    ) -> Result<NodeStream<Self, Self::Error>, Self::Error>
+    where
+        Self: Send + 'static,
+    {
+        Arc::new(self).children(depth).await
+    }
+
+    /// Returns a short, human readable label of this node.
+    ///
+    /// Used as the label of this node when printing or exporting a traversal.
+    /// Defaults to the [`Debug`] representation.
+    ///
+    /// [`Debug`]: trait@std::fmt::Debug
+    fn display_label(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Owned(format!("{self:?}"))
+    }
+}
+
+/// Extend a queue with the contents of an [`Iterator`].
+///
+/// Queues to be used by [`FastNode`] must implement this trait.
+///
+/// [`FastNode`]: trait@crate::async::FastNode
+/// [`Iterator`]: trait@std::iter::Iterator
+pub trait ExtendQueue<I, E> {
+    /// Add single item to the queue.
+    fn add(&mut self, item: Result<I, E>);
+
+    /// Extend the queue with the contents of an [`Iterator`].
+    ///
+    /// [`Iterator`]: trait@std::iter::Iterator
+    fn add_all<Iter>(&mut self, iter: Iter)
+    where
+        Iter: IntoIterator<Item = Result<I, E>>;
+}
+
+#[async_trait]
+/// A node which asynchronously adds children [`Node`]s to a queue in place.
+///
+/// In contrast to [`Node`], no boxed [`Stream`] has to be allocated
+/// for the children of each node, which is cheaper when nodes only have
+/// a handful of children.
+///
+/// [`Stream`]: trait@futures::stream::Stream
+/// [`Node`]: trait@crate::async::Node
+pub trait FastNode
+where
+    Self: Sized + Hash + Eq + std::fmt::Debug,
+{
+    /// The type of the error when adding children fails.
+    type Error: std::fmt::Debug;
+
+    /// Callback for adding children [`Node`]s to a queue
+    /// implementing [`ExtendQueue`].
+    ///
+    /// # Errors
+    ///
+    /// Should return [`Self::Error`] if the children could not be added.
+    ///
+    /// [`ExtendQueue`]: trait@crate::async::ExtendQueue
+    /// [`Node`]: trait@crate::async::Node
+    /// [`Self::Error`]: type@crate::async::FastNode::Error
+    async fn add_children<Q>(
+        self: Arc<Self>,
+        depth: usize,
+        queue: &mut Q,
+    ) -> Result<(), Self::Error>
+    where
+        Q: ExtendQueue<Self, Self::Error> + Send;
+
+    /// Returns a short, human readable label of this node.
+    ///
+    /// Used as the label of this node when printing or exporting a traversal.
+    /// Defaults to the [`Debug`] representation.
+    ///
+    /// [`Debug`]: trait@std::fmt::Debug
+    fn display_label(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Owned(format!("{self:?}"))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,170 @@
+use super::NewNodesFut;
+use std::sync::Arc;
+
+/// The rayon thread pool that requests the children of nodes.
+#[derive(Debug, Clone)]
+pub(super) enum Pool {
+    /// The global rayon thread pool.
+    Global,
+    /// A custom rayon thread pool.
+    Custom(Arc<rayon::ThreadPool>),
+}
+
+impl Pool {
+    #[inline]
+    fn spawn<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        match self {
+            Self::Global => rayon::spawn(f),
+            Self::Custom(pool) => pool.spawn(f),
+        }
+    }
+}
+
+/// Runs the child stream `fut` on `pool`, if any.
+///
+/// The wrapped future hands `fut` to the pool once it is first polled and
+/// resolves when the pool completed it, see [`Bfs::children_on_pool`].
+///
+/// [`Bfs::children_on_pool`]: fn@crate::async::Bfs::children_on_pool
+#[inline]
+pub(super) fn wrap<N, E>(pool: Option<&Pool>, fut: NewNodesFut<N, E>) -> NewNodesFut<N, E>
+where
+    N: 'static,
+    E: Send + 'static,
+{
+    let Some(pool) = pool.cloned() else {
+        return fut;
+    };
+    let fut = Box::pin(async move {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        pool.spawn(move || {
+            // a panic is resumed by the traversal instead of aborting the pool
+            let output = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                futures::executor::block_on(fut)
+            }));
+            // the receiver is gone when the traversal was dropped
+            let _ = tx.send(output);
+        });
+        match rx
+            .await
+            .expect("thread pool dropped the child stream request")
+        {
+            Ok(output) => output,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    });
+    Box::pin(fut)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::r#async::{Bfs, Dfs, Node, NodeStream};
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    static OFF_POOL: AtomicBool = AtomicBool::new(false);
+
+    /// A binary tree node that records whether its children were requested
+    /// outside of a rayon thread pool.
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct PoolNode(usize);
+
+    #[async_trait::async_trait]
+    impl Node for PoolNode {
+        type Error = std::convert::Infallible;
+
+        async fn children(
+            self: Arc<Self>,
+            depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            if rayon::current_thread_index().is_none() {
+                OFF_POOL.store(true, Ordering::SeqCst);
+            }
+            let id = self.0;
+            let nodes = (1..=2).map(move |i| Ok(Self(id * 2 + i)));
+            let nodes = nodes.take(if depth <= 3 { 2 } else { 0 });
+            Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_children_on_rayon_pool() {
+        let pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(2)
+                .build()
+                .unwrap(),
+        );
+
+        let dfs = Dfs::<PoolNode>::new(PoolNode(0), None, false)
+            .collect::<Vec<_>>()
+            .await;
+        assert!(OFF_POOL.swap(false, Ordering::SeqCst));
+        let dfs_parallel = Dfs::<PoolNode>::new(PoolNode(0), None, false)
+            .children_on_pool(Arc::clone(&pool))
+            .collect::<Vec<_>>()
+            .await;
+        assert!(!OFF_POOL.load(Ordering::SeqCst));
+        similar_asserts::assert_eq!(dfs_parallel, dfs);
+
+        let bfs = Bfs::<PoolNode>::new(PoolNode(0), None, false)
+            .collect::<Vec<_>>()
+            .await;
+        assert!(OFF_POOL.swap(false, Ordering::SeqCst));
+        let bfs_parallel = Bfs::<PoolNode>::new(PoolNode(0), None, false)
+            .children_on_global_pool()
+            .collect::<Vec<_>>()
+            .await;
+        assert!(!OFF_POOL.load(Ordering::SeqCst));
+        similar_asserts::assert_eq!(bfs_parallel, bfs);
+        similar_asserts::assert_eq!(bfs.len(), 14);
+    }
+
+    static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+    static MAX_ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+    /// A binary tree node whose children block the thread while they are
+    /// requested, recording how many requests overlap.
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct BusyNode(usize);
+
+    #[async_trait::async_trait]
+    impl Node for BusyNode {
+        type Error = std::convert::Infallible;
+
+        async fn children(
+            self: Arc<Self>,
+            _depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            let active = ACTIVE.fetch_add(1, Ordering::SeqCst) + 1;
+            MAX_ACTIVE.fetch_max(active, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            ACTIVE.fetch_sub(1, Ordering::SeqCst);
+            let id = self.0;
+            let nodes = (1..=2).map(move |i| Ok(Self(id * 2 + i)));
+            Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bfs_children_on_rayon_pool_in_parallel() {
+        let pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(4)
+                .build()
+                .unwrap(),
+        );
+        let bfs = Bfs::<BusyNode>::new(BusyNode(0), 4, false)
+            .children_on_pool(pool)
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(bfs.len(), 30);
+        // the single-threaded runtime alone never overlaps blocking requests
+        assert!(MAX_ACTIVE.load(Ordering::SeqCst) > 1);
+    }
+}
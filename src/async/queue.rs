@@ -0,0 +1,73 @@
+use super::{ExtendQueue, FastNode, NewChildrenFut};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Children of a single node, as added by a [`FastNode`].
+///
+/// [`FastNode`]: trait@crate::async::FastNode
+#[derive(Debug)]
+pub(super) struct Queue<I, E> {
+    inner: VecDeque<Result<I, E>>,
+}
+
+impl<I, E> Queue<I, E> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            inner: VecDeque::new(),
+        }
+    }
+
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<Result<I, E>> {
+        self.inner.pop_front()
+    }
+}
+
+impl<I, E> Default for Queue<I, E> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, E> Queue<I, E> {
+    #[inline]
+    #[must_use]
+    pub fn from_err(err: E) -> Self {
+        Self {
+            inner: VecDeque::from_iter([Err(err)]),
+        }
+    }
+}
+
+impl<I, E> ExtendQueue<I, E> for Queue<I, E> {
+    #[inline]
+    fn add(&mut self, item: Result<I, E>) {
+        self.inner.push_back(item);
+    }
+
+    #[inline]
+    fn add_all<Iter>(&mut self, iter: Iter)
+    where
+        Iter: IntoIterator<Item = Result<I, E>>,
+    {
+        self.inner.extend(iter);
+    }
+}
+
+/// Creates a future that resolves to the children of `node` at `depth`.
+#[inline]
+pub(super) fn add_children<N>(node: Arc<N>, depth: usize) -> NewChildrenFut<N, N::Error>
+where
+    N: FastNode + Send + Sync + 'static,
+    N::Error: Send + 'static,
+{
+    let add_children = async move {
+        let mut queue = Queue::new();
+        let result = node.add_children(depth, &mut queue).await;
+        (depth, result.map(|()| queue))
+    };
+    Box::pin(crate::trace::instrument_children(add_children, depth))
+}
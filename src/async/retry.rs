@@ -0,0 +1,257 @@
+use super::NewNodesFut;
+use std::time::Duration;
+
+/// How often and how fast to retry requesting the children of a node.
+///
+/// See [`Dfs::retry`] for details.
+///
+/// [`Dfs::retry`]: fn@crate::async::Dfs::retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    /// The number of retries after the first failed request.
+    pub max_retries: usize,
+    /// The delay before every retry.
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates a new [`RetryPolicy`].
+    ///
+    /// [`RetryPolicy`]: struct@crate::async::RetryPolicy
+    #[inline]
+    #[must_use]
+    pub fn new(max_retries: usize, backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            backoff,
+        }
+    }
+}
+
+/// Retries the child stream `fut` according to `retry`, if any.
+///
+/// The wrapped future requests the children again with `attempt` while
+/// they fail, and resolves to the last error once all retries failed.
+#[inline]
+pub(super) fn wrap<N, E, A>(
+    retry: Option<&RetryPolicy>,
+    fut: NewNodesFut<N, E>,
+    attempt: A,
+) -> NewNodesFut<N, E>
+where
+    N: 'static,
+    E: Send + 'static,
+    A: Fn() -> NewNodesFut<N, E> + Send + 'static,
+{
+    let Some(&RetryPolicy {
+        max_retries,
+        backoff,
+    }) = retry
+    else {
+        return fut;
+    };
+    let fut = Box::pin(async move {
+        let mut output = fut.await;
+        for _ in 0..max_retries {
+            if output.1.is_ok() {
+                break;
+            }
+            tokio::time::sleep(backoff).await;
+            output = attempt().await;
+        }
+        output
+    });
+    Box::pin(fut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use crate::r#async::{Bfs, Dfs, Node, NodeStream};
+    use futures::StreamExt;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// The number of failed requests per node.
+    static FAILURES: Mutex<Option<HashMap<(&'static str, usize), usize>>> = Mutex::new(None);
+
+    /// The tests whose children were requested outside of a rayon thread pool.
+    #[cfg(feature = "rayon")]
+    static OFF_POOL: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    /// A binary tree node whose children fail to resolve the first
+    /// `self.1` times they are requested.
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct FlakyNode(&'static str, usize, usize);
+
+    #[async_trait::async_trait]
+    impl Node for FlakyNode {
+        type Error = usize;
+
+        async fn children(
+            self: Arc<Self>,
+            depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            let Self(test, failures, id) = *self;
+            #[cfg(feature = "rayon")]
+            if rayon::current_thread_index().is_none() {
+                OFF_POOL.lock().unwrap().push(test);
+            }
+            {
+                let mut counts = FAILURES.lock().unwrap();
+                let failed = counts
+                    .get_or_insert_with(HashMap::new)
+                    .entry((test, id))
+                    .or_default();
+                if *failed < failures {
+                    *failed += 1;
+                    return Err(id);
+                }
+            }
+            let nodes = (1..=2).map(move |i| Ok(Self(test, failures, id * 2 + i)));
+            let nodes = nodes.take(if depth <= 2 { 2 } else { 0 });
+            Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_retry() {
+        let retry = RetryPolicy::new(2, Duration::from_millis(1));
+
+        // every request fails twice, and succeeds on the last retry
+        let dfs = Dfs::<FlakyNode>::new(FlakyNode("dfs", 2, 0), None, false)
+            .retry(retry)
+            .map(|node| node.map(|node| node.2))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(dfs, [Ok(1), Ok(3), Ok(4), Ok(2), Ok(5), Ok(6)]);
+
+        let bfs = Bfs::<FlakyNode>::new(FlakyNode("bfs", 2, 0), None, false)
+            .retry(retry)
+            .map(|node| node.map(|node| node.2))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(bfs, [Ok(1), Ok(2), Ok(3), Ok(4), Ok(5), Ok(6)]);
+
+        // errors are surfaced once all retries failed
+        let dfs = Dfs::<FlakyNode>::new(FlakyNode("exhausted", 3, 0), None, false)
+            .retry(retry)
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(dfs, [Err(0)]);
+
+        // without a retry policy, the first error is surfaced
+        let dfs = Dfs::<FlakyNode>::new(FlakyNode("none", 1, 0), None, false)
+            .map(|node| node.map(|node| node.2))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(dfs, [Err(0)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_retry_with_spawner() {
+        let retry = RetryPolicy::new(2, Duration::from_millis(1));
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let spawner = {
+            let spawned = Arc::clone(&spawned);
+            move |task| {
+                spawned.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(task)
+            }
+        };
+        let dfs = Dfs::<FlakyNode>::with_spawner(FlakyNode("spawner", 2, 0), None, false, spawner)
+            .retry(retry)
+            .map(|node| node.map(|node| node.2))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(dfs, [Ok(1), Ok(3), Ok(4), Ok(2), Ok(5), Ok(6)]);
+        // all three attempts of the 7 expanded nodes are spawned
+        similar_asserts::assert_eq!(spawned.load(Ordering::SeqCst), 21);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_retry_on_rayon_pool() {
+        let retry = RetryPolicy::new(2, Duration::from_millis(1));
+        let bfs = Bfs::<FlakyNode>::new(FlakyNode("pool", 2, 0), None, false)
+            .retry(retry)
+            .children_on_global_pool()
+            .map(|node| node.map(|node| node.2))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(bfs, [Ok(1), Ok(2), Ok(3), Ok(4), Ok(5), Ok(6)]);
+        assert!(!OFF_POOL.lock().unwrap().contains(&"pool"));
+    }
+
+    /// A binary tree node whose children never resolve the first `self.1`
+    /// times they are requested.
+    #[cfg(feature = "timeout")]
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct HangingNode(&'static str, usize, usize);
+
+    #[cfg(feature = "timeout")]
+    #[async_trait::async_trait]
+    impl Node for HangingNode {
+        type Error = crate::r#async::TimeoutError;
+
+        async fn children(
+            self: Arc<Self>,
+            depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            let Self(test, hangs, id) = *self;
+            let hang = {
+                let mut counts = FAILURES.lock().unwrap();
+                let hung = counts
+                    .get_or_insert_with(HashMap::new)
+                    .entry((test, id))
+                    .or_default();
+                *hung += 1;
+                *hung <= hangs
+            };
+            if hang {
+                std::future::pending::<()>().await;
+            }
+            let nodes = (1..=2).map(move |i| Ok(Self(test, hangs, id * 2 + i)));
+            let nodes = nodes.take(if depth <= 2 { 2 } else { 0 });
+            Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+        }
+    }
+
+    #[cfg(feature = "timeout")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_retry_with_per_node_timeout() {
+        let timeout = Duration::from_millis(20);
+        // the backoff exceeds the timeout, which only limits single attempts
+        let retry = RetryPolicy::new(2, 2 * timeout);
+
+        // every request times out twice, and succeeds on the last retry
+        let dfs = Dfs::<HangingNode>::new(HangingNode("dfs timeout", 2, 0), None, false)
+            .retry(retry)
+            .per_node_timeout(timeout)
+            .map(|node| node.map(|node| node.2))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(dfs, [Ok(1), Ok(3), Ok(4), Ok(2), Ok(5), Ok(6)]);
+
+        let bfs = Bfs::<HangingNode>::new(HangingNode("bfs timeout", 2, 0), None, false)
+            .retry(retry)
+            .per_node_timeout(timeout)
+            .map(|node| node.map(|node| node.2))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(bfs, [Ok(1), Ok(2), Ok(3), Ok(4), Ok(5), Ok(6)]);
+
+        // the timeout is surfaced once all retries timed out
+        let dfs = Dfs::<HangingNode>::new(HangingNode("exhausted timeout", 3, 0), None, false)
+            .retry(retry)
+            .per_node_timeout(timeout)
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(
+            dfs,
+            [Err(crate::r#async::TimeoutError { depth: 1, timeout })]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use super::NewNodesFut;
+use futures::{Future, FutureExt};
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// The request of the children of a node, which is handed to a spawner.
+///
+/// See [`Bfs::with_spawner`] for details.
+///
+/// [`Bfs::with_spawner`]: fn@crate::async::Bfs::with_spawner
+pub type ChildrenTask = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+type SpawnFn = dyn Fn(ChildrenTask) -> Box<dyn Send> + Send + Sync;
+
+/// Spawns the child stream requests of a traversal as separate tasks.
+#[derive(Clone)]
+pub(super) struct Spawner(Arc<SpawnFn>);
+
+impl std::fmt::Debug for Spawner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Spawner").finish_non_exhaustive()
+    }
+}
+
+impl Spawner {
+    #[inline]
+    pub(super) fn new<S, H>(spawner: S) -> Self
+    where
+        S: Fn(ChildrenTask) -> H + Send + Sync + 'static,
+        H: Send + 'static,
+    {
+        Self(Arc::new(move |task| Box::new(spawner(task))))
+    }
+}
+
+/// Runs the child stream `fut` as a task of `spawner`, if any.
+///
+/// The wrapped future spawns `fut` once it is first polled and resolves
+/// when the task completed, while holding on to the handle of the task,
+/// see [`Bfs::with_spawner`].
+///
+/// [`Bfs::with_spawner`]: fn@crate::async::Bfs::with_spawner
+#[inline]
+pub(super) fn wrap<N, E>(spawner: Option<&Spawner>, fut: NewNodesFut<N, E>) -> NewNodesFut<N, E>
+where
+    N: 'static,
+    E: Send + 'static,
+{
+    let Some(spawner) = spawner.cloned() else {
+        return fut;
+    };
+    let fut = Box::pin(async move {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        let _handle = (spawner.0)(Box::pin(async move {
+            // a panic is resumed by the traversal instead of tearing down the task
+            let output = std::panic::AssertUnwindSafe(fut).catch_unwind().await;
+            // the receiver is gone when the traversal was dropped
+            let _ = tx.send(output);
+        }));
+        match rx.await.expect("spawner dropped the child stream request") {
+            Ok(output) => output,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    });
+    Box::pin(fut)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::r#async::{Bfs, Dfs, Node, NodeStream};
+    use crate::utils::test::TreeNode;
+    use anyhow::Result;
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+    static MAX_ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+    /// A binary tree node whose children block the thread while they are
+    /// requested, recording how many requests overlap.
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct BusyNode(usize);
+
+    #[async_trait::async_trait]
+    impl Node for BusyNode {
+        type Error = std::convert::Infallible;
+
+        async fn children(
+            self: Arc<Self>,
+            _depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            let active = ACTIVE.fetch_add(1, Ordering::SeqCst) + 1;
+            MAX_ACTIVE.fetch_max(active, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            ACTIVE.fetch_sub(1, Ordering::SeqCst);
+            let id = self.0;
+            let nodes = (1..=2).map(move |i| Ok(Self(id * 2 + i)));
+            Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_bfs_with_spawner_in_parallel() {
+        let bfs = Bfs::<BusyNode>::with_spawner(BusyNode(0), 4, false, tokio::spawn)
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(bfs.len(), 30);
+        // blocking requests only overlap when they run on separate tasks
+        assert!(MAX_ACTIVE.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dfs_with_spawner() -> Result<()> {
+        let ids = |nodes: Vec<Result<TreeNode, _>>| {
+            nodes
+                .into_iter()
+                .map(|node| node.map(|node| node.0))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let expected = ids(Dfs::<TreeNode>::new(TreeNode(0), 3, false).collect().await)?;
+
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let spawner = {
+            let spawned = Arc::clone(&spawned);
+            move |task| {
+                spawned.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(task)
+            }
+        };
+        let dfs = Dfs::<TreeNode>::with_spawner(TreeNode(0), 3, false, spawner);
+        similar_asserts::assert_eq!(ids(dfs.collect().await)?, expected);
+        // every node above the depth limit, including the root, is expanded
+        similar_asserts::assert_eq!(spawned.load(Ordering::SeqCst), 7);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[should_panic(expected = "children panicked")]
+    async fn test_dfs_with_spawner_resumes_panics() {
+        #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+        struct PanicNode;
+
+        #[async_trait::async_trait]
+        impl Node for PanicNode {
+            type Error = std::convert::Infallible;
+
+            async fn children(
+                self: Arc<Self>,
+                _depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                panic!("children panicked");
+            }
+        }
+
+        let _ = Dfs::<PanicNode>::with_spawner(PanicNode, None, false, tokio::spawn)
+            .next()
+            .await;
+    }
+}
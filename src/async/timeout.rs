@@ -0,0 +1,130 @@
+use super::NewNodesFut;
+use std::time::Duration;
+
+/// The error of a child stream that did not resolve within the per-node timeout.
+///
+/// Convert it into the error of the node by implementing [`From<TimeoutError>`].
+///
+/// [`From<TimeoutError>`]: trait@std::convert::From
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[error("children at depth {depth} did not resolve within {timeout:?}")]
+pub struct TimeoutError {
+    /// The depth of the children that timed out.
+    pub depth: usize,
+    /// The per-node timeout that elapsed.
+    pub timeout: Duration,
+}
+
+/// Limits how long the child stream of a node may take to resolve.
+pub(super) struct Timeout<E> {
+    duration: Duration,
+    error: fn(TimeoutError) -> E,
+}
+
+impl<E> Clone for Timeout<E> {
+    fn clone(&self) -> Self {
+        Self {
+            duration: self.duration,
+            error: self.error,
+        }
+    }
+}
+
+impl<E> Timeout<E> {
+    #[inline]
+    pub(super) fn new(duration: Duration) -> Self
+    where
+        E: From<TimeoutError>,
+    {
+        Self {
+            duration,
+            error: E::from,
+        }
+    }
+}
+
+/// Wraps the future of the child stream at `depth` in `timeout`, if any.
+///
+/// The wrapped future resolves to an error once the timeout elapsed.
+#[inline]
+pub(super) fn wrap<N, E>(
+    timeout: Option<&Timeout<E>>,
+    fut: NewNodesFut<N, E>,
+    depth: usize,
+) -> NewNodesFut<N, E>
+where
+    N: 'static,
+    E: Send + 'static,
+{
+    let Some(&Timeout { duration, error }) = timeout else {
+        return fut;
+    };
+    let fut = Box::pin(async move {
+        match tokio::time::timeout(duration, fut).await {
+            Ok(output) => output,
+            Err(_) => (
+                depth,
+                Err(error(TimeoutError {
+                    depth,
+                    timeout: duration,
+                })),
+            ),
+        }
+    });
+    Box::pin(fut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeoutError;
+    use crate::r#async::{Bfs, Dfs, Node, NodeStream};
+    use futures::StreamExt;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[derive(thiserror::Error, PartialEq, Eq, Clone, Debug)]
+    #[error(transparent)]
+    struct Error(#[from] TimeoutError);
+
+    /// A binary tree node whose children never resolve for node 2.
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct HangingNode(usize);
+
+    #[async_trait::async_trait]
+    impl Node for HangingNode {
+        type Error = Error;
+
+        async fn children(
+            self: Arc<Self>,
+            depth: usize,
+        ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+            if self.0 == 2 {
+                std::future::pending::<()>().await;
+            }
+            let id = self.0;
+            let nodes = (0..2).map(move |i| Ok(Self(id * 2 + i)));
+            let nodes = nodes.take(if depth <= 2 { 2 } else { 0 });
+            Ok(Box::pin(futures::stream::iter(nodes).boxed()))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_per_node_timeout() {
+        let timeout = Duration::from_millis(50);
+        let err = Err(Error(TimeoutError { depth: 2, timeout }));
+
+        let dfs = Dfs::<HangingNode>::new(HangingNode(1), None, false)
+            .per_node_timeout(timeout)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(dfs, [Ok(2), err.clone(), Ok(3), Ok(6), Ok(7)]);
+
+        let bfs = Bfs::<HangingNode>::new(HangingNode(1), None, false)
+            .per_node_timeout(timeout)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Vec<_>>()
+            .await;
+        similar_asserts::assert_eq!(bfs, [Ok(2), Ok(3), err, Ok(6), Ok(7)]);
+    }
+}
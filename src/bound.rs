@@ -0,0 +1,56 @@
+/// How a `max_depth` limits the depth of the yielded nodes.
+///
+/// The children of the root are at depth 1.
+/// A plain number, or [`Some`] number, passed as `max_depth` to the
+/// constructors of the traversals is [`DepthBound::Inclusive`],
+/// and [`None`] does not limit the depth.
+///
+/// For the tree `r → a → c → d` and `r → b`, the traversals yield:
+///
+/// | `max_depth`                                | yielded depths | yielded nodes  |
+/// |--------------------------------------------|----------------|----------------|
+/// | `None`                                     | 1, 2, 3        | `a, b, c, d`   |
+/// | `2`, `Some(2)`, `DepthBound::Inclusive(2)` | 1, 2           | `a, b, c`      |
+/// | `DepthBound::Exclusive(2)`                 | 1              | `a, b`         |
+/// | `0`, `DepthBound::Inclusive(0)`            | none           | none           |
+///
+/// The root is never yielded, unless it is included explicitly.
+/// Then, it is yielded at depth 0 for any bound, including
+/// `DepthBound::Exclusive(0)`.
+///
+/// ### Example
+/// ```
+/// use par_dfs::DepthBound;
+///
+/// assert_eq!(Option::<usize>::from(DepthBound::Inclusive(3)), Some(3));
+/// assert_eq!(Option::<usize>::from(DepthBound::Exclusive(3)), Some(2));
+/// ```
+///
+/// [`Some`]: variant@std::option::Option::Some
+/// [`None`]: variant@std::option::Option::None
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DepthBound {
+    /// Yields nodes up to and including the given depth.
+    Inclusive(usize),
+    /// Yields nodes up to but excluding the given depth.
+    Exclusive(usize),
+}
+
+impl DepthBound {
+    /// Returns the deepest depth at which nodes are yielded.
+    #[inline]
+    #[must_use]
+    pub fn max_depth(self) -> usize {
+        match self {
+            Self::Inclusive(depth) => depth,
+            Self::Exclusive(depth) => depth.saturating_sub(1),
+        }
+    }
+}
+
+impl From<DepthBound> for Option<usize> {
+    #[inline]
+    fn from(bound: DepthBound) -> Self {
+        Some(bound.max_depth())
+    }
+}
@@ -0,0 +1,283 @@
+//! Bridges between the synchronous and asynchronous traversals.
+//!
+//! Any synchronous [`Iterator`], such as [`Dfs`] over a synchronous [`Node`],
+//! can be turned into an asynchronous [`Stream`] using [`IntoStream`].
+//! The iterator is driven on a blocking task of the [`tokio`] runtime and its
+//! items are forwarded over a bounded channel.
+//! When the channel is full, the blocking task waits until items are consumed,
+//! so the iterator only runs ahead of the stream by the capacity of the channel.
+//!
+//! Iterators whose items are cheap to produce, such as [`FastDfs`] over a
+//! [`FastNode`], can also be polled inline by the executor instead, using
+//! [`IntoInlineStream`], which works with any executor and does not require
+//! the iterator to be [`Send`].
+//!
+//! [`Iterator`]: trait@std::iter::Iterator
+//! [`Dfs`]: struct@crate::sync::Dfs
+//! [`FastDfs`]: struct@crate::sync::FastDfs
+//! [`FastNode`]: trait@crate::sync::FastNode
+//! [`IntoInlineStream`]: trait@self::IntoInlineStream
+//! [`Send`]: trait@std::marker::Send
+//! [`Node`]: trait@crate::sync::Node
+//! [`Stream`]: trait@futures::stream::Stream
+//! [`IntoStream`]: trait@self::IntoStream
+//! [`tokio`]: mod@tokio
+
+use futures::stream::Stream;
+use futures::{ready, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// The default capacity of the channel between the iterator and the stream.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// Converts an [`Iterator`] into a [`Stream`] that is driven on a blocking task.
+///
+/// [`Iterator`]: trait@std::iter::Iterator
+/// [`Stream`]: trait@futures::stream::Stream
+pub trait IntoStream: Iterator + Sized {
+    /// Drives this iterator on a blocking task and returns a [`Stream`] of its items.
+    ///
+    /// Equivalent to [`into_stream_with_capacity`] with [`DEFAULT_CAPACITY`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a [`tokio`] runtime.
+    ///
+    /// [`Stream`]: trait@futures::stream::Stream
+    /// [`into_stream_with_capacity`]: fn@self::IntoStream::into_stream_with_capacity
+    /// [`DEFAULT_CAPACITY`]: constant@self::DEFAULT_CAPACITY
+    /// [`tokio`]: mod@tokio
+    fn into_stream(self) -> BlockingStream<Self::Item> {
+        self.into_stream_with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Drives this iterator on a blocking task and returns a [`Stream`] of its items.
+    ///
+    /// At most `capacity` items are buffered before the iterator is paused.
+    /// Dropping the stream stops the iterator after its current item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or if called outside of a [`tokio`] runtime.
+    ///
+    /// [`Stream`]: trait@futures::stream::Stream
+    /// [`tokio`]: mod@tokio
+    fn into_stream_with_capacity(self, capacity: usize) -> BlockingStream<Self::Item>;
+}
+
+impl<I> IntoStream for I
+where
+    I: Iterator + Send + 'static,
+    I::Item: Send + 'static,
+{
+    fn into_stream_with_capacity(self, capacity: usize) -> BlockingStream<Self::Item> {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let task = tokio::task::spawn_blocking(move || {
+            for item in self {
+                // the stream was dropped
+                if sender.blocking_send(item).is_err() {
+                    break;
+                }
+            }
+        });
+        BlockingStream {
+            receiver,
+            task: Some(task),
+        }
+    }
+}
+
+/// Converts an [`Iterator`] into a [`Stream`] that is advanced inline.
+///
+/// In contrast to [`IntoStream`], the iterator never leaves the task that
+/// polls the stream, so it does not need to be [`Send`].
+///
+/// [`Iterator`]: trait@std::iter::Iterator
+/// [`Stream`]: trait@futures::stream::Stream
+/// [`IntoStream`]: trait@self::IntoStream
+/// [`Send`]: trait@std::marker::Send
+pub trait IntoInlineStream: Iterator + Sized {
+    /// Returns a [`Stream`] that advances this iterator whenever it is polled.
+    ///
+    /// In contrast to [`IntoStream::into_stream`], the iterator runs on the
+    /// executor, so every item should be cheap to produce.
+    /// After every item, control is handed back to the executor before the
+    /// next item is produced, so a long traversal does not starve other tasks.
+    ///
+    /// [`Stream`]: trait@futures::stream::Stream
+    /// [`IntoStream::into_stream`]: fn@self::IntoStream::into_stream
+    fn into_inline_stream(self) -> InlineStream<Self> {
+        InlineStream {
+            iter: Some(self),
+            yielded: false,
+        }
+    }
+}
+
+impl<I> IntoInlineStream for I where I: Iterator {}
+
+/// A [`Stream`] of the items of an [`Iterator`] that is driven on a blocking task.
+///
+/// Created by [`IntoStream::into_stream`].
+/// When the iterator panics, the panic is resumed when polling the stream.
+///
+/// [`Stream`]: trait@futures::stream::Stream
+/// [`Iterator`]: trait@std::iter::Iterator
+/// [`IntoStream::into_stream`]: fn@self::IntoStream::into_stream
+#[derive(Debug)]
+pub struct BlockingStream<T> {
+    receiver: mpsc::Receiver<T>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl<T> Stream for BlockingStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(item) = ready!(self.receiver.poll_recv(cx)) {
+            return Poll::Ready(Some(item));
+        }
+        // all items were received, wait for the task to surface a panic
+        if let Some(task) = self.task.as_mut() {
+            let result = ready!(Pin::new(task).poll(cx));
+            self.task = None;
+            if let Err(err) = result {
+                if err.is_panic() {
+                    std::panic::resume_unwind(err.into_panic());
+                }
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+/// A [`Stream`] of the items of an [`Iterator`] that is advanced inline.
+///
+/// Created by [`IntoInlineStream::into_inline_stream`].
+///
+/// [`Stream`]: trait@futures::stream::Stream
+/// [`Iterator`]: trait@std::iter::Iterator
+/// [`IntoInlineStream::into_inline_stream`]: fn@self::IntoInlineStream::into_inline_stream
+#[derive(Debug)]
+pub struct InlineStream<I> {
+    iter: Option<I>,
+    yielded: bool,
+}
+
+// the iterator is never pinned
+impl<I> Unpin for InlineStream<I> {}
+
+impl<I> Stream for InlineStream<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // yield to the executor between items
+        if std::mem::take(&mut self.yielded) {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let item = self.iter.as_mut().and_then(Iterator::next);
+        if item.is_some() {
+            self.yielded = true;
+        } else {
+            // the iterator is not advanced past its end
+            self.iter = None;
+        }
+        Poll::Ready(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.as_ref().map_or((0, Some(0)), Iterator::size_hint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IntoInlineStream, IntoStream};
+    use crate::sync::Dfs;
+    use crate::utils::test::Node;
+    use anyhow::Result;
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dfs_into_stream() -> Result<()> {
+        let expected = Dfs::<Node>::new(0, 3, true).collect::<Result<Vec<_>, _>>()?;
+        let nodes = Dfs::<Node>::new(0, 3, true)
+            .into_stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(nodes, expected);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_into_stream_backpressure() {
+        use tokio::time::{sleep, Duration};
+
+        let produced = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&produced);
+        let mut stream = Dfs::<Node>::new(0, None, true)
+            .inspect(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .into_stream_with_capacity(1);
+        assert!(stream.next().await.is_some());
+        sleep(Duration::from_millis(50)).await;
+        // one received, one buffered and one waiting to be sent
+        assert!(produced.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[tokio::test]
+    async fn test_fast_dfs_into_inline_stream() -> Result<()> {
+        use crate::sync::{FastBfs, FastDfs};
+        use crate::utils::test::TreeNode;
+
+        let expected =
+            FastDfs::<TreeNode>::new(TreeNode(0), 3, false).collect::<Result<Vec<_>, _>>()?;
+        let nodes = FastDfs::<TreeNode>::new(TreeNode(0), 3, false)
+            .into_inline_stream()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(nodes, expected);
+
+        // other tasks run between items, even on a single thread
+        let ran = Arc::new(AtomicUsize::new(0));
+        let task = tokio::spawn({
+            let ran = Arc::clone(&ran);
+            async move {
+                ran.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let nodes = FastBfs::<TreeNode>::new(TreeNode(0), None, false)
+            .into_inline_stream()
+            .take(3)
+            .count()
+            .await;
+        similar_asserts::assert_eq!((nodes, ran.load(Ordering::SeqCst)), (3, 1));
+        task.await?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_send_into_inline_stream() {
+        use std::rc::Rc;
+
+        // the iterator is not `Send`, since it is never moved to another thread
+        let offset = Rc::new(1);
+        let items = (0..3).map(move |i| i + *offset).into_inline_stream();
+        let items = futures::executor::block_on(items.collect::<Vec<_>>());
+        similar_asserts::assert_eq!(items, [1, 2, 3]);
+    }
+}
@@ -2,6 +2,12 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod bound;
+mod macros;
+mod trace;
+
+pub use bound::DepthBound;
+
 #[cfg(feature = "sync")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
 pub mod sync;
@@ -10,4 +16,8 @@ pub mod sync;
 #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
 pub mod r#async;
 
+#[cfg(feature = "bridge")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
+pub mod bridge;
+
 mod utils;
@@ -0,0 +1,48 @@
+/// Implements [`Hash`], [`PartialEq`], and [`Eq`] for a node using only the given key fields.
+///
+/// Traversals require nodes to implement [`Hash`] and [`Eq`] to track visited nodes.
+/// For nodes that carry both an identity and a payload, deriving these traits
+/// would compare the payload too, so nodes with the same identity but
+/// a different payload would be visited more than once.
+/// This macro keys the visited set on the identity alone, without writing the
+/// implementations by hand.
+///
+/// Since the generated [`Eq`] is also used everywhere else, two nodes are
+/// equal if and only if their key fields are equal.
+///
+/// ### Example
+/// ```
+/// #[derive(Clone, Debug)]
+/// struct Page {
+///     url: String,
+///     body: Vec<u8>,
+/// }
+///
+/// par_dfs::dedup_key!(Page { url });
+///
+/// let a = Page { url: "/".into(), body: vec![1] };
+/// let b = Page { url: "/".into(), body: vec![2] };
+/// assert_eq!(a, b);
+/// ```
+///
+/// [`Hash`]: trait@std::hash::Hash
+/// [`PartialEq`]: trait@std::cmp::PartialEq
+/// [`Eq`]: trait@std::cmp::Eq
+#[macro_export]
+macro_rules! dedup_key {
+    ($node:ty { $($key:ident),+ $(,)? }) => {
+        impl ::std::hash::Hash for $node {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                $(::std::hash::Hash::hash(&self.$key, state);)+
+            }
+        }
+
+        impl ::std::cmp::PartialEq for $node {
+            fn eq(&self, other: &Self) -> bool {
+                true $(&& self.$key == other.$key)+
+            }
+        }
+
+        impl ::std::cmp::Eq for $node {}
+    };
+}
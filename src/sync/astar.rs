@@ -0,0 +1,297 @@
+use super::dijkstra::{Entry, WeightedNode};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::iter::Iterator;
+
+type HeuristicFn<N, C> = dyn Fn(&N) -> C + Send + Sync;
+type GoalFn<N> = dyn Fn(&N) -> bool + Send + Sync;
+
+/// Synchronous A* search for types implementing the [`WeightedNode`] trait.
+///
+/// Nodes are expanded in order of their cost from the root plus the
+/// `heuristic` estimate of their remaining cost to a goal.
+/// Once a node matching the `goal` is reached, the path from the root to it
+/// is yielded and the search ends.
+/// When no goal is reachable, the search ends without yielding a path.
+/// Errors are yielded as soon as they occur.
+///
+/// The yielded path is the cheapest one when the heuristic never overestimates
+/// the remaining cost and is consistent, i.e. the estimate of a node is at most
+/// the cost of the edge to a child plus the estimate of that child.
+/// A heuristic that always estimates [`Default::default`] degrades to [`Dijkstra`].
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::astar::AStar;
+/// use par_dfs::sync::dijkstra::{WeightedNode, WeightedNodeIter};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+/// struct Pos(i32);
+///
+/// impl WeightedNode for Pos {
+///     type Cost = u32;
+///     type Error = std::convert::Infallible;
+///
+///     fn children_weighted(&self, _depth: usize) -> WeightedNodeIter<Self, u32, Self::Error> {
+///         // walking costs 2, jumping two steps costs 3
+///         let moves = [(1, 2), (-1, 2), (2, 3), (-2, 3)];
+///         let pos = self.0;
+///         Ok(Box::new(moves.into_iter().map(move |(step, cost)| Ok((Pos(pos + step), cost)))))
+///     }
+/// }
+///
+/// // every step costs at least 1.5 per unit of distance
+/// let heuristic = |pos: &Pos| pos.0.abs_diff(5) * 3 / 2;
+/// let mut astar = AStar::<Pos>::new(Pos(0), heuristic, |pos| pos.0 == 5);
+/// let path = astar.next().unwrap().unwrap();
+/// // two jumps and a single step
+/// assert_eq!(path.len(), 4);
+/// assert_eq!(path.last(), Some(&Pos(5)));
+/// ```
+///
+/// [`WeightedNode`]: trait@crate::sync::dijkstra::WeightedNode
+/// [`Dijkstra`]: struct@crate::sync::dijkstra::Dijkstra
+/// [`Default::default`]: fn@std::default::Default::default
+pub struct AStar<N>
+where
+    N: WeightedNode,
+{
+    heap: BinaryHeap<Entry<N, N::Cost>>,
+    errors: VecDeque<N::Error>,
+    heuristic: Box<HeuristicFn<N, N::Cost>>,
+    goal: Box<GoalFn<N>>,
+    seq: u64,
+    costs: HashMap<N, N::Cost>,
+    parents: HashMap<N, N>,
+    settled: HashSet<N>,
+}
+
+impl<N> std::fmt::Debug for AStar<N>
+where
+    N: WeightedNode,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AStar")
+            .field("queued", &self.heap.len())
+            .field("settled", &self.settled.len())
+            .field("errors", &self.errors)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<N> AStar<N>
+where
+    N: WeightedNode,
+{
+    #[inline]
+    /// Creates a new [`AStar`] search.
+    ///
+    /// The search will be performed from the `root` node for the cheapest path
+    /// to a node matching the `goal`, guided by the `heuristic` estimate of
+    /// the remaining cost of a node.
+    ///
+    /// [`AStar`]: struct@crate::sync::astar::AStar
+    pub fn new<R, H, G>(root: R, heuristic: H, goal: G) -> Self
+    where
+        R: Into<N>,
+        H: Fn(&N) -> N::Cost + Send + Sync + 'static,
+        G: Fn(&N) -> bool + Send + Sync + 'static,
+    {
+        let mut astar = Self {
+            heap: BinaryHeap::new(),
+            errors: VecDeque::new(),
+            heuristic: Box::new(heuristic),
+            goal: Box::new(goal),
+            seq: 0,
+            costs: HashMap::new(),
+            parents: HashMap::new(),
+            settled: HashSet::new(),
+        };
+        astar.relax(0, root.into(), N::Cost::default(), None);
+        astar
+    }
+
+    /// Queues `node` at `depth` with `cost` from the root via `parent`, unless
+    /// a path that is at most as expensive was already found.
+    #[inline]
+    fn relax(&mut self, depth: usize, node: N, cost: N::Cost, parent: Option<&N>) {
+        if self.settled.contains(&node) {
+            return;
+        }
+        if let Some(known) = self.costs.get(&node) {
+            if *known <= cost {
+                return;
+            }
+        }
+        if let Some(parent) = parent {
+            self.parents.insert(node.clone(), parent.clone());
+        }
+        let estimate = cost.clone() + (self.heuristic)(&node);
+        self.costs.insert(node.clone(), cost);
+        self.heap.push(Entry {
+            cost: estimate,
+            seq: self.seq,
+            depth,
+            node,
+        });
+        self.seq += 1;
+    }
+
+    /// Adds the children of `node` at `depth`, which is reached with `cost`.
+    #[inline]
+    fn expand(&mut self, node: &N, depth: usize, cost: &N::Cost) {
+        match node.children_weighted(depth) {
+            Ok(children) => {
+                for child in children {
+                    match child {
+                        Ok((child, edge)) => {
+                            self.relax(depth, child, cost.clone() + edge, Some(node));
+                        }
+                        Err(err) => self.errors.push_back(err),
+                    }
+                }
+            }
+            Err(err) => self.errors.push_back(err),
+        }
+    }
+
+    /// Reconstructs the path from the root to `node` by following the parents.
+    #[inline]
+    fn path(&self, node: N) -> Vec<N> {
+        let mut path = vec![node];
+        while let Some(parent) = path.last().and_then(|node| self.parents.get(node)) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl<N> Iterator for AStar<N>
+where
+    N: WeightedNode,
+{
+    type Item = Result<Vec<N>, N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(err) = self.errors.pop_front() {
+                return Some(Err(err));
+            }
+            let Entry { depth, node, .. } = self.heap.pop()?;
+            // a node is queued again for every cheaper path,
+            // so only the first pop is final
+            if !self.settled.insert(node.clone()) {
+                continue;
+            }
+            if (self.goal)(&node) {
+                // the search ends with the first path found
+                self.heap.clear();
+                return Some(Ok(self.path(node)));
+            }
+            let cost = self.costs[&node].clone();
+            self.expand(&node, depth + 1, &cost);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AStar;
+    use crate::sync::dijkstra::{Dijkstra, WeightedNode, WeightedNodeIter};
+    use anyhow::Result;
+    use std::cell::Cell;
+
+    thread_local! {
+        /// The number of expanded nodes on the current test thread.
+        static EXPANDED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    const GRID: &[&str] = &[
+        "..........",
+        "..........",
+        "....#.....",
+        "....#.....",
+        "....#.....",
+        "....#.....",
+        "..........",
+        "..........",
+    ];
+
+    /// A free cell of [`GRID`] with edges to its free neighbors.
+    #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+    struct GridCell(usize, usize);
+
+    impl GridCell {
+        fn distance(self, other: Self) -> u32 {
+            (self.0.abs_diff(other.0) + self.1.abs_diff(other.1)) as u32
+        }
+    }
+
+    impl WeightedNode for GridCell {
+        type Cost = u32;
+        type Error = std::convert::Infallible;
+
+        fn children_weighted(&self, _depth: usize) -> WeightedNodeIter<Self, u32, Self::Error> {
+            EXPANDED.with(|expanded| expanded.set(expanded.get() + 1));
+            let Self(x, y) = *self;
+            let neighbors = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            let free = neighbors.into_iter().filter(|&(x, y)| {
+                GRID.get(y)
+                    .and_then(|row| row.as_bytes().get(x))
+                    .is_some_and(|cell| *cell == b'.')
+            });
+            Ok(Box::new(free.map(|(x, y)| Ok((Self(x, y), 1)))))
+        }
+    }
+
+    #[test]
+    fn test_astar_grid() -> Result<()> {
+        let start = GridCell(1, 4);
+        let goal = GridCell(7, 4);
+
+        EXPANDED.with(|expanded| expanded.set(0));
+        let (_, cost) = Dijkstra::<GridCell>::new(start, None)
+            .find(|node| node.as_ref().is_ok_and(|(node, _)| *node == goal))
+            .unwrap()?;
+        let dijkstra_expanded = EXPANDED.with(Cell::get);
+
+        EXPANDED.with(|expanded| expanded.set(0));
+        let mut astar = AStar::<GridCell>::new(
+            start,
+            move |cell| cell.distance(goal),
+            move |cell| *cell == goal,
+        );
+        let path = astar.next().unwrap()?;
+        let astar_expanded = EXPANDED.with(Cell::get);
+        assert!(astar.next().is_none());
+
+        // the path has to go around the wall
+        similar_asserts::assert_eq!(cost, 10);
+        similar_asserts::assert_eq!(path.len() as u32, cost + 1);
+        similar_asserts::assert_eq!(path.first(), Some(&start));
+        similar_asserts::assert_eq!(path.last(), Some(&goal));
+        assert!(path.windows(2).all(|step| step[0].distance(step[1]) == 1));
+        assert!(
+            astar_expanded < dijkstra_expanded,
+            "expanded {astar_expanded} nodes, dijkstra expanded {dijkstra_expanded}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_unreachable() {
+        let start = GridCell(0, 0);
+        let mut astar = AStar::<GridCell>::new(start, |_| 0, |cell| cell.0 >= 10);
+        assert!(astar.next().is_none());
+
+        // the root can be the goal
+        let mut astar = AStar::<GridCell>::new(start, |_| 0, move |cell| *cell == start);
+        similar_asserts::assert_eq!(astar.next().transpose().unwrap(), Some(vec![start]));
+    }
+}
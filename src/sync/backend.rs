@@ -0,0 +1,267 @@
+//! Storage for the queued nodes of a [`Bfs`] that do not fit into memory.
+//!
+//! A [`Bfs`] created with [`Bfs::with_backend`] keeps up to
+//! [`QueueBackend::memory_limit`] queued nodes in memory.
+//! Further nodes are appended to the backend and read back in the same order
+//! once the in-memory part of the queue drains, so the traversal yields the
+//! nodes in the same order as without a backend.
+//!
+//! With the `spill` feature, [`SpillFile`] stores the nodes in temporary files.
+//!
+//! [`Bfs`]: struct@crate::sync::Bfs
+//! [`Bfs::with_backend`]: fn@crate::sync::Bfs::with_backend
+//! [`QueueBackend::memory_limit`]: fn@self::QueueBackend::memory_limit
+#![cfg_attr(feature = "spill", doc = "[`SpillFile`]: struct@self::SpillFile")]
+
+use std::collections::VecDeque;
+use std::io;
+
+/// A first-in, first-out store for queued nodes and their depths.
+///
+/// Failures of the backend are yielded by the traversal as errors,
+/// in place of the nodes that were lost.
+pub trait QueueBackend<I>: Send {
+    /// The number of queued nodes to keep in memory before further nodes
+    /// are appended to this backend.
+    fn memory_limit(&self) -> usize;
+
+    /// Appends `item` at `depth` to the back.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `item` could not be stored, in which case it is dropped.
+    fn push_back(&mut self, depth: usize, item: I) -> io::Result<()>;
+
+    /// Removes the first item and its depth, or returns [`None`] if the
+    /// backend is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the first items could not be read back, in which
+    /// case they are dropped, and [`len`](Self::len) accounts for them.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    fn pop_front(&mut self) -> io::Result<Option<(usize, I)>>;
+
+    /// Returns the number of stored items.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if no items are stored.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an independent copy of this backend, including its items.
+    ///
+    /// Used when the traversal is cloned.
+    fn boxed_clone(&self) -> Box<dyn QueueBackend<I>>;
+}
+
+/// An in-memory backend, which never spills.
+///
+/// Mostly useful for testing, since it keeps all nodes in memory.
+#[derive(Debug, Clone)]
+pub struct InMemory<I> {
+    memory_limit: usize,
+    items: VecDeque<(usize, I)>,
+}
+
+impl<I> InMemory<I> {
+    /// Creates a new in-memory backend that receives all queued
+    /// nodes beyond the first `memory_limit`.
+    #[inline]
+    #[must_use]
+    pub fn new(memory_limit: usize) -> Self {
+        Self {
+            memory_limit,
+            items: VecDeque::new(),
+        }
+    }
+}
+
+impl<I> QueueBackend<I> for InMemory<I>
+where
+    I: Clone + Send + 'static,
+{
+    #[inline]
+    fn memory_limit(&self) -> usize {
+        self.memory_limit
+    }
+
+    #[inline]
+    fn push_back(&mut self, depth: usize, item: I) -> io::Result<()> {
+        self.items.push_back((depth, item));
+        Ok(())
+    }
+
+    #[inline]
+    fn pop_front(&mut self) -> io::Result<Option<(usize, I)>> {
+        Ok(self.items.pop_front())
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    fn boxed_clone(&self) -> Box<dyn QueueBackend<I>> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(feature = "spill")]
+#[cfg_attr(docsrs, doc(cfg(feature = "spill")))]
+pub use spill::SpillFile;
+
+#[cfg(feature = "spill")]
+mod spill {
+    use super::QueueBackend;
+    use serde::{de::DeserializeOwned, Serialize};
+    use std::collections::VecDeque;
+    use std::fs::File;
+    use std::io::{self, BufReader, BufWriter, Write};
+    use std::sync::Arc;
+    use tempfile::TempPath;
+
+    /// A chunk of spilled nodes, which is shared by clones of the backend.
+    #[derive(Debug, Clone)]
+    struct Chunk {
+        path: Arc<TempPath>,
+        len: usize,
+    }
+
+    /// A backend that spills nodes to temporary files.
+    ///
+    /// Appended nodes are buffered and written in chunks of `memory_limit`
+    /// nodes, each serialized to its own temporary file.
+    /// Chunks are read back whole, once all earlier nodes were removed.
+    /// Hence, in addition to the in-memory part of the queue, at most two
+    /// chunks are held in memory.
+    /// The files are never modified once written, so clones of the backend
+    /// share them, and they are deleted once no clone reads them anymore.
+    ///
+    /// When a chunk cannot be written, its nodes are kept buffered and
+    /// written with the next chunk, while the node that was appended last
+    /// is dropped.
+    /// When a chunk cannot be read back, all of its nodes are dropped.
+    #[derive(Debug)]
+    pub struct SpillFile<I> {
+        memory_limit: usize,
+        head: VecDeque<(usize, I)>,
+        chunks: VecDeque<Chunk>,
+        tail: Vec<(usize, I)>,
+        len: usize,
+    }
+
+    impl<I> SpillFile<I> {
+        /// Creates a new backend that spills all queued nodes beyond the
+        /// first `memory_limit` to temporary files.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `memory_limit` is zero.
+        #[inline]
+        #[must_use]
+        pub fn new(memory_limit: usize) -> Self {
+            assert!(memory_limit > 0, "memory limit must be positive");
+            Self {
+                memory_limit,
+                head: VecDeque::new(),
+                chunks: VecDeque::new(),
+                tail: Vec::new(),
+                len: 0,
+            }
+        }
+    }
+
+    impl<I> SpillFile<I>
+    where
+        I: Serialize + DeserializeOwned,
+    {
+        /// Writes the buffered nodes to a new temporary file.
+        fn spill(&mut self) -> io::Result<()> {
+            let mut file = tempfile::NamedTempFile::new()?;
+            let mut writer = BufWriter::new(file.as_file_mut());
+            serde_json::to_writer(&mut writer, &self.tail)?;
+            writer.flush()?;
+            drop(writer);
+            self.chunks.push_back(Chunk {
+                path: Arc::new(file.into_temp_path()),
+                len: self.tail.len(),
+            });
+            self.tail.clear();
+            Ok(())
+        }
+
+        /// Reads the oldest chunk, or takes the buffered nodes if there is none.
+        fn unspill(&mut self) -> io::Result<()> {
+            let Some(chunk) = self.chunks.pop_front() else {
+                self.head = std::mem::take(&mut self.tail).into();
+                return Ok(());
+            };
+            let read = File::open(&*chunk.path)
+                .and_then(|file| Ok(serde_json::from_reader(BufReader::new(file))?));
+            match read {
+                Ok(head) => {
+                    self.head = head;
+                    Ok(())
+                }
+                Err(err) => {
+                    self.len -= chunk.len;
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    impl<I> QueueBackend<I> for SpillFile<I>
+    where
+        I: Serialize + DeserializeOwned + Clone + Send + 'static,
+    {
+        #[inline]
+        fn memory_limit(&self) -> usize {
+            self.memory_limit
+        }
+
+        #[inline]
+        fn push_back(&mut self, depth: usize, item: I) -> io::Result<()> {
+            self.tail.push((depth, item));
+            if self.tail.len() >= self.memory_limit {
+                if let Err(err) = self.spill() {
+                    self.tail.pop();
+                    return Err(err);
+                }
+            }
+            self.len += 1;
+            Ok(())
+        }
+
+        #[inline]
+        fn pop_front(&mut self) -> io::Result<Option<(usize, I)>> {
+            if self.head.is_empty() {
+                self.unspill()?;
+            }
+            let Some(item) = self.head.pop_front() else {
+                return Ok(None);
+            };
+            self.len -= 1;
+            Ok(Some(item))
+        }
+
+        #[inline]
+        fn len(&self) -> usize {
+            self.len
+        }
+
+        fn boxed_clone(&self) -> Box<dyn QueueBackend<I>> {
+            Box::new(Self {
+                memory_limit: self.memory_limit,
+                head: self.head.clone(),
+                chunks: self.chunks.clone(),
+                tail: self.tail.clone(),
+                len: self.len,
+            })
+        }
+    }
+}
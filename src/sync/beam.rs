@@ -0,0 +1,219 @@
+use super::{queue, Node, Queue};
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::iter::Iterator;
+
+type ScoreFn<N, S> = dyn Fn(&N) -> S + Send + Sync;
+
+/// Synchronous beam search for types implementing the [`Node`] trait.
+///
+/// Like [`LevelBfs`], the nodes are discovered level by level, but only the
+/// `beam_width` nodes with the highest score of every level are yielded and
+/// expanded, while the rest of the level is discarded.
+/// Wrap the score in [`Reverse`] to keep the nodes with the lowest score.
+/// Nodes with equal scores are kept in the order [`Bfs`] yields them.
+/// Every node is yielded together with its depth, up to depth `max_depth`.
+///
+/// This is an approximate search: the subtrees of discarded nodes are never
+/// visited, so the best nodes of deeper levels are missed when their
+/// ancestors do not score high enough.
+/// Discarded nodes stay visited, so they are not discovered again along
+/// another path either.
+/// Errors are yielded after the level they occurred in, and do not count
+/// towards the beam width.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::{BeamSearch, Node, NodeIter};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct NumNode(u32);
+///
+/// impl Node for NumNode {
+///     type Error = std::convert::Infallible;
+///
+///     fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+///         let nodes = [self.0 * 3, self.0 + 1].map(Self).map(Ok);
+///         Ok(Box::new(nodes.into_iter()))
+///     }
+/// }
+///
+/// let beam = BeamSearch::<NumNode, _>::new(NumNode(1), 3, 1, false, |node| node.0);
+/// let output = beam
+///     .map(|node| node.map(|(depth, node)| (depth, node.0)))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(output, [(1, 3), (2, 9), (3, 27)]);
+/// ```
+///
+/// [`Node`]: trait@crate::sync::Node
+/// [`LevelBfs`]: struct@crate::sync::LevelBfs
+/// [`Bfs`]: struct@crate::sync::Bfs
+/// [`Reverse`]: struct@std::cmp::Reverse
+pub struct BeamSearch<N, S>
+where
+    N: Node,
+{
+    queue: queue::Queue<N, N::Error>,
+    errors: VecDeque<N::Error>,
+    /// The kept nodes of the current level, which remain to be yielded.
+    level: VecDeque<N>,
+    depth: usize,
+    score: Box<ScoreFn<N, S>>,
+    beam_width: usize,
+    max_depth: Option<usize>,
+}
+
+impl<N, S> std::fmt::Debug for BeamSearch<N, S>
+where
+    N: Node,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BeamSearch")
+            .field("queue", &self.queue)
+            .field("errors", &self.errors)
+            .field("level", &self.level)
+            .field("depth", &self.depth)
+            .field("beam_width", &self.beam_width)
+            .field("max_depth", &self.max_depth)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<N, S> BeamSearch<N, S>
+where
+    N: Node,
+    S: Ord,
+{
+    #[inline]
+    /// Creates a new [`BeamSearch`] iterator.
+    ///
+    /// The search will be performed from the `root` node up to depth `max_depth`,
+    /// keeping the `beam_width` nodes with the highest `score` of every level.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`BeamSearch`]: struct@crate::sync::BeamSearch
+    pub fn new<R, D, F>(
+        root: R,
+        max_depth: D,
+        beam_width: usize,
+        allow_circles: bool,
+        score: F,
+    ) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+        F: Fn(&N) -> S + Send + Sync + 'static,
+    {
+        let mut queue = queue::Queue::new(allow_circles);
+        // the root is expanded lazily but never yielded
+        queue.add(0, Ok(root.into()));
+        Self {
+            queue,
+            errors: VecDeque::new(),
+            level: VecDeque::new(),
+            depth: 0,
+            score: Box::new(score),
+            beam_width,
+            max_depth: max_depth.into(),
+        }
+    }
+}
+
+impl<N, S> Iterator for BeamSearch<N, S>
+where
+    N: Node,
+    S: Ord,
+{
+    type Item = Result<(usize, N), N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(node) = self.level.pop_front() {
+                return Some(Ok((self.depth, node)));
+            }
+            if let Some(err) = self.errors.pop_front() {
+                return Some(Err(err));
+            }
+            let depth = self.queue.front_depth()?;
+            let mut level = Vec::new();
+            while self.queue.front_depth() == Some(depth) {
+                match self.queue.pop_front()? {
+                    (_, Ok(node)) => level.push(node),
+                    (_, Err(err)) => self.errors.push_back(err),
+                }
+            }
+            // the root level is only expanded
+            if depth > 0 {
+                // the sort is stable, so ties keep their order
+                level.sort_by_cached_key(|node| Reverse((self.score)(node)));
+                level.truncate(self.beam_width);
+            }
+            // the next level is queued behind the current level
+            if self.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                for node in &level {
+                    match node.children(depth + 1) {
+                        Ok(children) => self.queue.add_all(depth + 1, children),
+                        Err(err) => self.queue.add(depth + 1, Err(err)),
+                    }
+                }
+            }
+            if depth > 0 {
+                self.depth = depth;
+                self.level = level.into();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BeamSearch;
+    use crate::utils::test::GraphNode;
+    use anyhow::Result;
+    use std::cmp::Reverse;
+
+    fn beam<S>(
+        edges: &'static [&'static [usize]],
+        max_depth: Option<usize>,
+        beam_width: usize,
+        score: impl Fn(&GraphNode) -> S + Send + Sync + 'static,
+    ) -> Result<Vec<(usize, usize)>>
+    where
+        S: Ord,
+    {
+        Ok(BeamSearch::new(
+            GraphNode::new(0, edges),
+            max_depth,
+            beam_width,
+            false,
+            score,
+        )
+        .map(|node| node.map(|(depth, node)| (depth, node.id)))
+        .collect::<Result<_, _>>()?)
+    }
+
+    #[test]
+    fn test_beam_search() -> Result<()> {
+        // the best node at depth 2 is 6, but its parent 3 scores lowest
+        let edges: &[&[usize]] = &[&[1, 2, 3], &[4], &[5], &[6], &[], &[], &[]];
+        similar_asserts::assert_eq!(
+            beam(edges, None, 2, |node| node.id % 3)?,
+            [(1, 2), (1, 1), (2, 5), (2, 4)]
+        );
+        similar_asserts::assert_eq!(
+            beam(edges, None, 1, |node| Reverse(node.id))?,
+            [(1, 1), (2, 4)]
+        );
+        // a wide enough beam is a complete breadth-first traversal
+        similar_asserts::assert_eq!(
+            beam(edges, None, 3, |_| 0)?,
+            [(1, 1), (1, 2), (1, 3), (2, 4), (2, 5), (2, 6)]
+        );
+        similar_asserts::assert_eq!(beam(edges, Some(1), 2, |node| node.id)?, [(1, 3), (1, 2)]);
+        similar_asserts::assert_eq!(beam(edges, None, 0, |node| node.id)?, []);
+        Ok(())
+    }
+}
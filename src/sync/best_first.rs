@@ -0,0 +1,246 @@
+use super::Node;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::iter::Iterator;
+
+type PriorityFn<N, P> = dyn Fn(&N) -> P + Send + Sync;
+
+/// A queued node ordered by its priority.
+///
+/// Nodes with equal priority are ordered by insertion, so that
+/// earlier nodes come first.
+struct Entry<N, P> {
+    priority: P,
+    seq: u64,
+    depth: usize,
+    node: N,
+}
+
+impl<N, P> PartialEq for Entry<N, P>
+where
+    P: Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N, P> Eq for Entry<N, P> where P: Ord {}
+
+impl<N, P> PartialOrd for Entry<N, P>
+where
+    P: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, P> Ord for Entry<N, P>
+where
+    P: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Synchronous best-first iterator for types implementing the [`Node`] trait.
+///
+/// Instead of a fixed order, the node with the highest priority among all
+/// discovered nodes is yielded next, e.g. for greedy search over weighted graphs.
+/// Wrap the priority in [`Reverse`] to yield the node with the lowest priority next.
+/// Nodes with equal priority are yielded in the order they were discovered.
+/// Errors are yielded as soon as they occur.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::{BestFirst, Node, NodeIter};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct NumNode(u32);
+///
+/// impl Node for NumNode {
+///     type Error = std::convert::Infallible;
+///
+///     fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+///         let nodes = [self.0 * 3, self.0 + 1].map(Self).map(Ok);
+///         Ok(Box::new(nodes.into_iter()))
+///     }
+/// }
+///
+/// let best = BestFirst::<NumNode, _>::new(NumNode(1), 2, false, |node| node.0);
+/// let output = best.map(|node| node.unwrap().0).collect::<Vec<_>>();
+/// assert_eq!(output, [3, 9, 4, 2, 6]);
+/// ```
+///
+/// [`Node`]: trait@crate::sync::Node
+/// [`Reverse`]: struct@std::cmp::Reverse
+pub struct BestFirst<N, P>
+where
+    N: Node,
+{
+    heap: BinaryHeap<Entry<N, P>>,
+    errors: VecDeque<N::Error>,
+    priority: Box<PriorityFn<N, P>>,
+    seq: u64,
+    visited: HashSet<N>,
+    max_depth: Option<usize>,
+    allow_circles: bool,
+}
+
+impl<N, P> std::fmt::Debug for BestFirst<N, P>
+where
+    N: Node,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BestFirst")
+            .field("queued", &self.heap.len())
+            .field("errors", &self.errors)
+            .field("max_depth", &self.max_depth)
+            .field("allow_circles", &self.allow_circles)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<N, P> BestFirst<N, P>
+where
+    N: Node,
+    P: Ord,
+{
+    #[inline]
+    /// Creates a new [`BestFirst`] iterator.
+    ///
+    /// The search will be performed from the `root` node up to depth `max_depth`,
+    /// yielding the node with the highest `priority` next.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`BestFirst`]: struct@crate::sync::BestFirst
+    pub fn new<R, D, F>(root: R, max_depth: D, allow_circles: bool, priority: F) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+        F: Fn(&N) -> P + Send + Sync + 'static,
+    {
+        let root = root.into();
+        let mut best = Self {
+            heap: BinaryHeap::new(),
+            errors: VecDeque::new(),
+            priority: Box::new(priority),
+            seq: 0,
+            visited: HashSet::new(),
+            max_depth: max_depth.into(),
+            allow_circles,
+        };
+        // the root is expanded lazily but never yielded
+        best.add(0, root);
+        best
+    }
+
+    /// Adds `node` at `depth` unless it was already visited.
+    #[inline]
+    fn add(&mut self, depth: usize, node: N) {
+        if !self.allow_circles && !self.visited.insert(node.clone()) {
+            return;
+        }
+        self.heap.push(Entry {
+            priority: (self.priority)(&node),
+            seq: self.seq,
+            depth,
+            node,
+        });
+        self.seq += 1;
+    }
+
+    /// Adds the children of `node` at `depth`.
+    #[inline]
+    fn expand(&mut self, node: &N, depth: usize) {
+        match node.children(depth) {
+            Ok(children) => {
+                for child in children {
+                    match child {
+                        Ok(child) => self.add(depth, child),
+                        Err(err) => self.errors.push_back(err),
+                    }
+                }
+            }
+            Err(err) => self.errors.push_back(err),
+        }
+    }
+}
+
+impl<N, P> Iterator for BestFirst<N, P>
+where
+    N: Node,
+    P: Ord,
+{
+    type Item = Result<N, N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(err) = self.errors.pop_front() {
+                return Some(Err(err));
+            }
+            let Entry { depth, node, .. } = self.heap.pop()?;
+            let at_max_depth = self.max_depth.is_some_and(|max_depth| depth >= max_depth);
+            if !at_max_depth {
+                self.expand(&node, depth + 1);
+            }
+            // root node is only expanded
+            if depth > 0 {
+                return Some(Ok(node));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BestFirst;
+    use crate::utils::test::GraphNode;
+    use anyhow::Result;
+    use std::cmp::Reverse;
+
+    #[test]
+    fn test_best_first() -> Result<()> {
+        // 0 → 1 → 4
+        // 0 → 3 → 2
+        let edges: &[&[usize]] = &[&[1, 3], &[4], &[0], &[2], &[]];
+        let root = GraphNode::new(0, edges);
+        fn ids<P: Ord>(best: BestFirst<GraphNode, P>) -> Result<Vec<usize>> {
+            Ok(best
+                .map(|node| node.map(|node| node.id))
+                .collect::<Result<_, _>>()?)
+        }
+        similar_asserts::assert_eq!(
+            ids(BestFirst::new(root, None, false, |node: &GraphNode| node.id))?,
+            [3, 2, 1, 4]
+        );
+        similar_asserts::assert_eq!(
+            ids(BestFirst::new(root, None, false, |node: &GraphNode| {
+                Reverse(node.id)
+            }))?,
+            [1, 3, 2, 4]
+        );
+        similar_asserts::assert_eq!(
+            ids(BestFirst::new(root, 1, false, |node: &GraphNode| node.id))?,
+            [3, 1]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_best_first_equal_priority() -> Result<()> {
+        let edges: &[&[usize]] = &[&[1, 2, 3], &[], &[], &[]];
+        let root = GraphNode::new(0, edges);
+        let ids = BestFirst::<GraphNode, _>::new(root, None, false, |_| 0)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [1, 2, 3]);
+        Ok(())
+    }
+}
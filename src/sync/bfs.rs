@@ -1,5 +1,10 @@
+use super::options::traversal_options;
 use super::queue;
-use super::{ExtendQueue, FastNode, Node, Queue};
+use super::{
+    BidiNode, Depth, FastNode, Node, NodeIter, Queue, QueueBackend, SkipReason, Stats, VisitedSet,
+};
+use std::collections::{HashSet, VecDeque};
+use std::hash::BuildHasher;
 use std::iter::Iterator;
 
 #[allow(clippy::module_name_repetitions)]
@@ -9,6 +14,7 @@ use std::iter::Iterator;
 /// ### Example
 /// ```
 /// use par_dfs::sync::{Node, Bfs, NodeIter};
+/// use par_dfs::DepthBound;
 ///
 /// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 /// struct WordNode(String);
@@ -34,9 +40,10 @@ use std::iter::Iterator;
 ///
 /// let word = "Hello World";
 /// let root = WordNode(word.into());
+/// // the single characters are at depth ceil(log2(len)), the root is at depth 0
 /// let limit = (word.len() as f32).log2().ceil() as usize;
 ///
-/// let bfs = Bfs::<WordNode>::new(root, limit, true);
+/// let bfs = Bfs::<WordNode>::new(root, DepthBound::Inclusive(limit), true);
 /// let output = bfs.collect::<Result<Vec<_>, _>>().unwrap();
 /// let result = output[output.len()-word.len()..]
 ///     .into_iter().map(|s| s.0.as_str()).collect::<String>();
@@ -44,203 +51,1048 @@ use std::iter::Iterator;
 /// ```
 ///
 /// [`Node`]: trait@crate::sync::Node
-pub struct Bfs<N>
+pub struct Bfs<N, V = HashSet<N>, D = usize>
 where
-    N: Node,
+    N: Node<D>,
 {
-    queue: queue::Queue<N, N::Error>,
+    queue: queue::Queue<N, N::Error, V, D>,
     max_depth: Option<usize>,
+    stats: Stats,
 }
 
-impl<N> Bfs<N>
+impl<N, D> Bfs<N, HashSet<N>, D>
 where
-    N: Node,
+    N: Node<D>,
+    D: Depth,
 {
     #[inline]
     /// Creates a new [`Bfs`] iterator.
     ///
     /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    /// The bound is inclusive, unless a [`DepthBound`] says otherwise.
     ///
     /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    /// Otherwise, the root counts as visited, so a child equal to the root
+    /// is skipped as a [`Cycle`] instead of being yielded.
     ///
     /// [`Bfs`]: struct@crate::sync::Bfs
-    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    /// [`DepthBound`]: enum@crate::DepthBound
+    /// [`Cycle`]: enum@crate::sync::SkipReason
+    pub fn new<R, M>(root: R, max_depth: M, allow_circles: bool) -> Self
     where
         R: Into<N>,
-        D: Into<Option<usize>>,
+        M: Into<Option<usize>>,
+    {
+        Self::with_roots([root], max_depth, allow_circles)
+    }
+
+    #[inline]
+    /// Creates a new [`Bfs`] iterator from a `root` whose conversion can fail.
+    ///
+    /// Like [`new`](Self::new), but the `root` is converted with [`TryInto`],
+    /// e.g. for nodes that are parsed or read from the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the conversion if the `root` is not a valid node.
+    ///
+    /// [`Bfs`]: struct@crate::sync::Bfs
+    /// [`TryInto`]: trait@std::convert::TryInto
+    pub fn try_new<R, M>(root: R, max_depth: M, allow_circles: bool) -> Result<Self, R::Error>
+    where
+        R: TryInto<N>,
+        M: Into<Option<usize>>,
+    {
+        Ok(Self::new(root.try_into()?, max_depth, allow_circles))
+    }
+
+    #[inline]
+    /// Creates a new [`Bfs`] iterator starting from multiple `roots`.
+    ///
+    /// The BFS will be performed from all `roots` up to depth `max_depth`,
+    /// sharing one set of visited nodes, so nodes reachable from more than
+    /// one root are only yielded once.
+    /// Like a single root, the roots themselves are not yielded,
+    /// unless [`include_root`](Self::include_root) is set.
+    /// The roots are expanded in order, before any of their children,
+    /// so the children of the first root are yielded first.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`Bfs`]: struct@crate::sync::Bfs
+    pub fn with_roots<I, R, M>(roots: I, max_depth: M, allow_circles: bool) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        // the roots are expanded lazily but never yielded
+        let frontier = roots.into_iter().map(|root| (0, root.into()));
+        Self::from_frontier(frontier, max_depth, allow_circles)
+    }
+
+    #[inline]
+    /// Creates a new [`Bfs`] iterator from an explicit `frontier` of
+    /// nodes with their depths.
+    ///
+    /// The frontier is queued as-is, as if it was discovered by an earlier
+    /// traversal, e.g. to resume a traversal partially or to hand each worker
+    /// a slice of the frontier of a larger traversal.
+    /// Nodes at depth 0 are roots, which are only expanded, while deeper nodes
+    /// are yielded and expanded up to depth `max_depth`.
+    /// The nodes of the frontier are yielded in order, so they should be
+    /// ordered by depth.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    /// Otherwise, the nodes of the frontier are visited and yielded only once.
+    ///
+    /// [`Bfs`]: struct@crate::sync::Bfs
+    pub fn from_frontier<I, M>(frontier: I, max_depth: M, allow_circles: bool) -> Self
+    where
+        I: IntoIterator<Item = (usize, N)>,
+        M: Into<Option<usize>>,
     {
         let mut queue = queue::Queue::new(allow_circles);
+        let max_depth = max_depth.into();
+        for (depth, node) in frontier {
+            queue.add(depth, Ok(node));
+        }
+        Self {
+            queue,
+            max_depth,
+            stats: Stats::default(),
+        }
+    }
+
+    #[inline]
+    /// Creates a new [`Bfs`] iterator whose queue overflows into `backend`.
+    ///
+    /// Up to [`QueueBackend::memory_limit`] queued nodes are kept in memory,
+    /// while further nodes are appended to `backend` and read back once
+    /// the in-memory part of the queue drains.
+    /// The nodes are yielded in the same order as by [`Bfs::new`].
+    /// The visited set is still kept in memory, unless `allow_circles`.
+    ///
+    /// When the backend fails to store or read back nodes, the failure is
+    /// converted into the error of the node and yielded in their place.
+    ///
+    /// Clones of the traversal copy the backend.
+    /// Splits of a parallel traversal and checkpoints hold their nodes in
+    /// memory, and [`with_paths`](Self::with_paths) panics.
+    ///
+    /// [`Bfs`]: struct@crate::sync::Bfs
+    /// [`Bfs::new`]: fn@crate::sync::Bfs::new
+    /// [`QueueBackend::memory_limit`]: fn@crate::sync::QueueBackend::memory_limit
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use par_dfs::sync::backend::InMemory;
+    /// use par_dfs::sync::{Bfs, Node, NodeIter};
+    ///
+    /// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    /// struct Binary(usize);
+    ///
+    /// impl Node for Binary {
+    ///     type Error = std::io::Error;
+    ///
+    ///     fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+    ///         let children = [2 * self.0 + 1, 2 * self.0 + 2].map(|id| Ok(Binary(id)));
+    ///         Ok(Box::new(children.into_iter()))
+    ///     }
+    /// }
+    ///
+    /// let bfs: Bfs<Binary> = Bfs::with_backend(Binary(0), 3, false, InMemory::new(4));
+    /// let ids = bfs.map(|node| node.unwrap().0).collect::<Vec<_>>();
+    /// assert_eq!(ids, (1..15).collect::<Vec<_>>());
+    /// ```
+    pub fn with_backend<R, M, B>(root: R, max_depth: M, allow_circles: bool, backend: B) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+        B: QueueBackend<N> + 'static,
+        N::Error: From<std::io::Error>,
+    {
+        let mut bfs = Self::new(root, max_depth, allow_circles);
+        bfs.queue.set_backend(Box::new(backend), N::Error::from);
+        bfs
+    }
+
+    /// Returns the shortest path from `root` to the first node for which
+    /// `target` returns `true`.
+    ///
+    /// The path starts with `root` and ends with the matching node, or only
+    /// contains `root` when it matches itself.
+    /// Visited nodes are tracked, so nodes are discovered in nondecreasing
+    /// distance from `root` and the returned path is a shortest path.
+    /// The search stops at the first match, which is not expanded.
+    /// Returns the first error encountered before a match, or [`None`] when
+    /// no reachable node matches.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    pub fn shortest_path<R, F>(root: R, target: F) -> Option<Result<Vec<N>, N::Error>>
+    where
+        R: Into<N>,
+        F: Fn(&N) -> bool,
+    {
         let root = root.into();
+        if target(&root) {
+            return Some(Ok(vec![root]));
+        }
+        let mut bfs = Self::new(root, None, false);
+        bfs.queue.track_parents();
+        let mut found = false;
+        while let Some((_, node, _)) = bfs.next_node_until(|node| {
+            found = target(node);
+            found
+        }) {
+            match node {
+                Ok(node) if found => return Some(Ok(bfs.queue.path_to(node))),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
+
+impl<N, S, D> Bfs<N, HashSet<N, S>, D>
+where
+    N: Node<D>,
+    S: BuildHasher,
+    D: Depth,
+{
+    #[inline]
+    /// Creates a new [`Bfs`] iterator that hashes visited nodes using `hash_builder`.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// The default hasher is resistant to collision attacks, but comparatively slow
+    /// for nodes that are cheap to hash, e.g. integers, where hashing can dominate
+    /// the traversal.
+    ///
+    /// [`Bfs`]: struct@crate::sync::Bfs
+    pub fn with_hasher<R, M>(root: R, max_depth: M, hash_builder: S) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        Self::with_visited(root, max_depth, HashSet::with_hasher(hash_builder))
+    }
+}
+
+impl<N, V, D> Bfs<N, V, D>
+where
+    N: Node<D>,
+    V: VisitedSet<N>,
+    D: Depth,
+{
+    #[inline]
+    /// Creates a new [`Bfs`] iterator that tracks visited nodes in `visited`.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    /// Nodes that are already contained in `visited` are not yielded.
+    ///
+    /// [`Bfs`]: struct@crate::sync::Bfs
+    pub fn with_visited<R, M>(root: R, max_depth: M, visited: V) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        let mut queue = queue::Queue::with_visited(false, visited);
         let max_depth = max_depth.into();
+        // the root is expanded lazily but never yielded
+        queue.add(0, Ok(root.into()));
+        Self {
+            queue,
+            max_depth,
+            stats: Stats::default(),
+        }
+    }
 
-        let depth = 1;
-        match root.children(depth) {
-            Ok(children) => queue.add_all(depth, children),
-            Err(err) => queue.add(0, Err(err)),
+    /// Adds the children of `node` at `depth` to the queue.
+    #[inline]
+    fn expand(&mut self, node: &N, depth: usize) {
+        let span = crate::trace::ExpandSpan::enter(depth);
+        let len = self.queue.len();
+        let max_branching = self.queue.max_branching();
+        let on_panic = self.queue.on_panic();
+        let mut depth_queue = queue::QueueWrapper::new(depth, &mut self.queue).limit(max_branching);
+        let expanded = super::panic::catch(on_panic, depth, || {
+            node.children_into(queue::depth(depth), &mut depth_queue)
+        });
+        if let Err(err) = expanded {
+            // the error is added regardless of the branching limit
+            self.queue.add(depth, Err(err));
         }
+        span.children(self.queue.len() - len);
+        self.stats.expanded(self.queue.len());
+    }
+
+    /// Returns the next queued node without consuming it.
+    ///
+    /// Before the first call to `next`, this is the root, which is only
+    /// expanded and not yielded unless [`include_root`] is set.
+    /// The node is not yielded if it is filtered, e.g. by [`min_depth`].
+    ///
+    /// [`include_root`]: Self::include_root
+    /// [`min_depth`]: Self::min_depth
+    #[inline]
+    #[must_use]
+    pub fn peek_next(&self) -> Option<&Result<N, N::Error>> {
+        self.queue.front()
+    }
+
+    /// Starts the traversal over from `root`.
+    ///
+    /// See [`Dfs::reset`] for details.
+    ///
+    /// [`Dfs::reset`]: fn@crate::sync::Dfs::reset
+    #[inline]
+    pub fn reset<R>(&mut self, root: R)
+    where
+        R: Into<N>,
+        V: Default,
+    {
+        self.queue.reset([root.into()]);
+        self.stats = Stats::default();
+    }
 
-        Self { queue, max_depth }
+    /// Returns the next node with its depth and whether it is the last of its siblings.
+    #[inline]
+    fn next_node(&mut self) -> Option<(usize, Result<N, N::Error>, bool)> {
+        self.next_node_until(|_| false)
+    }
+
+    /// Returns the next node like [`next_node`](Self::next_node), but does not
+    /// expand it when `found` returns `true` for it.
+    #[inline]
+    fn next_node_until<F>(&mut self, mut found: F) -> Option<(usize, Result<N, N::Error>, bool)>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        loop {
+            if self.queue.exhausted() {
+                return None;
+            }
+            match self.queue.pop_front()? {
+                // root node is only expanded, unless it is at the depth limit
+                (0, Ok(root)) if !self.queue.yields_roots() => {
+                    if self.max_depth != Some(0) {
+                        let len = self.queue.len();
+                        self.expand(&root, 1);
+                        self.queue.end_children(len, &root);
+                    }
+                }
+                // next node failed
+                (depth, Err(err)) => {
+                    let last = self.queue.pop_sibling_front();
+                    self.queue.count_node()?;
+                    self.stats.errors += 1;
+                    return Some((depth, Err(err), last));
+                }
+                // next node succeeded
+                (depth, Ok(node)) => {
+                    // roots are not part of any group of siblings
+                    let last = depth > 0 && self.queue.pop_sibling_front();
+                    let yields = self.queue.yields(&node, depth);
+                    // the last node within the budget is not expanded
+                    let remaining = !yields || self.queue.count_node()?;
+                    if yields && found(&node) {
+                        self.stats.emitted += 1;
+                        return Some((depth, Ok(node), last));
+                    }
+                    if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                        self.queue.skip(&node, SkipReason::DepthLimit);
+                    } else if !remaining {
+                        self.queue.skip(&node, SkipReason::NodeLimit);
+                    } else if self.queue.prunes(&node, depth) {
+                        self.queue.skip(&node, SkipReason::Pruned);
+                    } else {
+                        let len = self.queue.len();
+                        self.expand(&node, depth + 1);
+                        self.queue.end_children(len, &node);
+                    }
+                    if yields {
+                        self.stats.emitted += 1;
+                        return Some((depth, Ok(node), last));
+                    }
+                }
+            }
+        }
     }
 }
 
-impl<N> Iterator for Bfs<N>
+impl<N, V, D> Iterator for Bfs<N, V, D>
 where
-    N: Node,
+    N: Node<D>,
+    V: VisitedSet<N>,
+    D: Depth,
 {
     type Item = Result<N, N::Error>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        match self.queue.pop_front() {
-            // next node failed
-            Some((_, Err(err))) => Some(Err(err)),
-            // next node succeeded
-            Some((depth, Ok(node))) => {
-                if let Some(max_depth) = self.max_depth {
-                    if depth >= max_depth {
-                        return Some(Ok(node));
+        self.next_node().map(|(_, node, _)| node)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.queue.size_hint()
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+/// Synchronous, fast breadth-first iterator for types implementing the [`FastNode`] trait.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::{FastNode, FastBfs, ExtendQueue, NodeIter};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct WordNode(String);
+///
+/// impl FastNode for WordNode {
+///     type Error = std::convert::Infallible;
+///
+///     fn add_children<E>(
+///         &self, _depth: usize, queue: &mut E
+///     ) -> Result<(), Self::Error>
+///     where
+///         E: ExtendQueue<Self, Self::Error>,
+///     {
+///         let len = self.0.len();
+///         if len > 1 {
+///             let mid = len/2;
+///             queue.add(Ok(Self(self.0[..mid].into())));
+///             queue.add(Ok(Self(self.0[mid..].into())));
+///         } else {
+///             assert!(len == 1);
+///             queue.add(Ok(Self(self.0.clone())));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let word = "Hello World";
+/// let root = WordNode(word.into());
+/// let limit = (word.len() as f32).log2().ceil() as usize;
+///
+/// let bfs = FastBfs::<WordNode>::new(root, limit, true);
+/// let output = bfs.collect::<Result<Vec<_>, _>>().unwrap();
+/// let result = output[output.len()-word.len()..]
+///     .into_iter().map(|s| s.0.as_str()).collect::<String>();
+/// assert_eq!(result, "Hello World");
+/// ```
+///
+/// [`FastNode`]: trait@crate::sync::FastNode
+pub struct FastBfs<N, V = HashSet<N>, D = usize>
+where
+    N: FastNode<D>,
+{
+    queue: queue::Queue<N, N::Error, V, D>,
+    max_depth: Option<usize>,
+    stats: Stats,
+}
+
+impl<N, D> FastBfs<N, HashSet<N>, D>
+where
+    N: FastNode<D>,
+    D: Depth,
+{
+    #[inline]
+    /// Creates a new [`FastBfs`] iterator.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    /// The bound is inclusive, unless a [`DepthBound`] says otherwise.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`FastBfs`]: struct@crate::sync::FastBfs
+    /// [`DepthBound`]: enum@crate::DepthBound
+    pub fn new<R, M>(root: R, max_depth: M, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        Self::with_roots([root], max_depth, allow_circles)
+    }
+
+    #[inline]
+    /// Creates a new [`FastBfs`] iterator from a `root` whose conversion can fail.
+    ///
+    /// Like [`new`](Self::new), but the `root` is converted with [`TryInto`],
+    /// e.g. for nodes that are parsed or read from the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the conversion if the `root` is not a valid node.
+    ///
+    /// [`FastBfs`]: struct@crate::sync::FastBfs
+    /// [`TryInto`]: trait@std::convert::TryInto
+    pub fn try_new<R, M>(root: R, max_depth: M, allow_circles: bool) -> Result<Self, R::Error>
+    where
+        R: TryInto<N>,
+        M: Into<Option<usize>>,
+    {
+        Ok(Self::new(root.try_into()?, max_depth, allow_circles))
+    }
+
+    #[inline]
+    /// Creates a new [`FastBfs`] iterator starting from multiple `roots`.
+    ///
+    /// The BFS will be performed from all `roots` up to depth `max_depth`,
+    /// sharing one set of visited nodes, so nodes reachable from more than
+    /// one root are only yielded once.
+    /// Like a single root, the roots themselves are not yielded,
+    /// unless [`include_root`](Self::include_root) is set.
+    /// The roots are expanded in order, before any of their children,
+    /// so the children of the first root are yielded first.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`FastBfs`]: struct@crate::sync::FastBfs
+    pub fn with_roots<I, R, M>(roots: I, max_depth: M, allow_circles: bool) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        // the roots are expanded lazily but never yielded
+        let frontier = roots.into_iter().map(|root| (0, root.into()));
+        Self::from_frontier(frontier, max_depth, allow_circles)
+    }
+
+    #[inline]
+    /// Creates a new [`FastBfs`] iterator from an explicit `frontier` of
+    /// nodes with their depths.
+    ///
+    /// The frontier is queued as-is, as if it was discovered by an earlier
+    /// traversal, e.g. to resume a traversal partially or to hand each worker
+    /// a slice of the frontier of a larger traversal.
+    /// Nodes at depth 0 are roots, which are only expanded, while deeper nodes
+    /// are yielded and expanded up to depth `max_depth`.
+    /// The nodes of the frontier are yielded in order, so they should be
+    /// ordered by depth.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    /// Otherwise, the nodes of the frontier are visited and yielded only once.
+    ///
+    /// [`FastBfs`]: struct@crate::sync::FastBfs
+    pub fn from_frontier<I, M>(frontier: I, max_depth: M, allow_circles: bool) -> Self
+    where
+        I: IntoIterator<Item = (usize, N)>,
+        M: Into<Option<usize>>,
+    {
+        let mut queue = queue::Queue::new(allow_circles);
+        let max_depth = max_depth.into();
+        for (depth, node) in frontier {
+            queue.add(depth, Ok(node));
+        }
+        Self {
+            queue,
+            max_depth,
+            stats: Stats::default(),
+        }
+    }
+}
+
+impl<N, S, D> FastBfs<N, HashSet<N, S>, D>
+where
+    N: FastNode<D>,
+    S: BuildHasher,
+    D: Depth,
+{
+    #[inline]
+    /// Creates a new [`FastBfs`] iterator that hashes visited nodes using `hash_builder`.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// The default hasher is resistant to collision attacks, but comparatively slow
+    /// for nodes that are cheap to hash, e.g. integers, where hashing can dominate
+    /// the traversal.
+    ///
+    /// [`FastBfs`]: struct@crate::sync::FastBfs
+    pub fn with_hasher<R, M>(root: R, max_depth: M, hash_builder: S) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        Self::with_visited(root, max_depth, HashSet::with_hasher(hash_builder))
+    }
+}
+
+impl<N, V, D> FastBfs<N, V, D>
+where
+    N: FastNode<D>,
+    V: VisitedSet<N>,
+    D: Depth,
+{
+    #[inline]
+    /// Creates a new [`FastBfs`] iterator that tracks visited nodes in `visited`.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    /// Nodes that are already contained in `visited` are not yielded.
+    ///
+    /// [`FastBfs`]: struct@crate::sync::FastBfs
+    pub fn with_visited<R, M>(root: R, max_depth: M, visited: V) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        let mut queue = queue::Queue::with_visited(false, visited);
+        let max_depth = max_depth.into();
+        // the root is expanded lazily but never yielded
+        queue.add(0, Ok(root.into()));
+        Self {
+            queue,
+            max_depth,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Adds the children of `node` at `depth` to the queue.
+    #[inline]
+    fn expand(&mut self, node: &N, depth: usize) {
+        let span = crate::trace::ExpandSpan::enter(depth);
+        let len = self.queue.len();
+        let max_branching = self.queue.max_branching();
+        let on_panic = self.queue.on_panic();
+        let mut depth_queue = queue::QueueWrapper::new(depth, &mut self.queue).limit(max_branching);
+        let expanded = super::panic::catch(on_panic, depth, || {
+            node.add_children(queue::depth(depth), &mut depth_queue)
+        });
+        if let Err(err) = expanded {
+            // the error is added regardless of the branching limit
+            self.queue.add(depth, Err(err));
+        }
+        span.children(self.queue.len() - len);
+        self.stats.expanded(self.queue.len());
+    }
+
+    /// Returns the next queued node without consuming it.
+    ///
+    /// Before the first call to `next`, this is the root, which is only
+    /// expanded and not yielded unless [`include_root`] is set.
+    /// The node is not yielded if it is filtered, e.g. by [`min_depth`].
+    ///
+    /// [`include_root`]: Self::include_root
+    /// [`min_depth`]: Self::min_depth
+    #[inline]
+    #[must_use]
+    pub fn peek_next(&self) -> Option<&Result<N, N::Error>> {
+        self.queue.front()
+    }
+
+    /// Returns the next node with its depth and whether it is the last of its siblings.
+    #[inline]
+    fn next_node(&mut self) -> Option<(usize, Result<N, N::Error>, bool)> {
+        self.next_node_until(|_| false)
+    }
+
+    /// Returns the next node like [`next_node`](Self::next_node), but does not
+    /// expand it when `found` returns `true` for it.
+    #[inline]
+    fn next_node_until<F>(&mut self, mut found: F) -> Option<(usize, Result<N, N::Error>, bool)>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        loop {
+            if self.queue.exhausted() {
+                return None;
+            }
+            match self.queue.pop_front()? {
+                // root node is only expanded, unless it is at the depth limit
+                (0, Ok(root)) if !self.queue.yields_roots() => {
+                    if self.max_depth != Some(0) {
+                        let len = self.queue.len();
+                        self.expand(&root, 1);
+                        self.queue.end_children(len, &root);
                     }
                 }
-                match node.children(depth + 1) {
-                    Ok(children) => {
-                        self.queue.add_all(depth + 1, children);
+                // next node failed
+                (depth, Err(err)) => {
+                    let last = self.queue.pop_sibling_front();
+                    self.queue.count_node()?;
+                    self.stats.errors += 1;
+                    return Some((depth, Err(err), last));
+                }
+                // next node succeeded
+                (depth, Ok(node)) => {
+                    // roots are not part of any group of siblings
+                    let last = depth > 0 && self.queue.pop_sibling_front();
+                    let yields = self.queue.yields(&node, depth);
+                    // the last node within the budget is not expanded
+                    let remaining = !yields || self.queue.count_node()?;
+                    if yields && found(&node) {
+                        self.stats.emitted += 1;
+                        return Some((depth, Ok(node), last));
                     }
-                    Err(err) => self.queue.add(depth + 1, Err(err)),
-                };
-                Some(Ok(node))
+                    if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                        self.queue.skip(&node, SkipReason::DepthLimit);
+                    } else if !remaining {
+                        self.queue.skip(&node, SkipReason::NodeLimit);
+                    } else if self.queue.prunes(&node, depth) {
+                        self.queue.skip(&node, SkipReason::Pruned);
+                    } else {
+                        let len = self.queue.len();
+                        self.expand(&node, depth + 1);
+                        self.queue.end_children(len, &node);
+                    }
+                    if yields {
+                        self.stats.emitted += 1;
+                        return Some((depth, Ok(node), last));
+                    }
+                }
             }
-            // no next node
-            None => None,
         }
     }
 }
 
+impl<N, V, D> Iterator for FastBfs<N, V, D>
+where
+    N: FastNode<D>,
+    V: VisitedSet<N>,
+    D: Depth,
+{
+    type Item = Result<N, N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_node().map(|(_, node, _)| node)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.queue.size_hint()
+    }
+}
+
+/// Synchronous breadth-first iterator for types implementing the [`Node`] trait,
+/// which yields all nodes of a depth together.
+///
+/// In contrast to [`Bfs`], every item is a level of the traversal, that is
+/// the depth together with all of its nodes, in the order [`Bfs`] yields them.
+/// All nodes of depth `d` are yielded before any node of depth `d + 1`,
+/// and the last yielded level is at most `max_depth`.
+/// Errors are yielded after the level they occurred in.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::{Node, LevelBfs, NodeIter};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct WordNode(String);
+///
+/// impl Node for WordNode {
+///     type Error = std::convert::Infallible;
+///
+///     fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+///         let len = self.0.len();
+///         let nodes: Vec<String> = if len > 1 {
+///             let mid = len/2;
+///             vec![self.0[..mid].into(), self.0[mid..].into()]
+///         } else {
+///             vec![]
+///         };
+///         let nodes = nodes.into_iter()
+///             .map(Self)
+///             .map(Result::Ok);
+///         Ok(Box::new(nodes))
+///     }
+/// }
+///
+/// let root = WordNode("Hello World".into());
+/// let levels = LevelBfs::<WordNode>::new(root, 2, true)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// let levels = levels
+///     .into_iter()
+///     .map(|(depth, nodes)| (depth, nodes.into_iter().map(|s| s.0).collect::<Vec<_>>()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(levels, [
+///     (1, vec!["Hello".to_string(), " World".to_string()]),
+///     (2, vec!["He".to_string(), "llo".to_string(), " Wo".to_string(), "rld".to_string()]),
+/// ]);
+/// ```
+///
+/// [`Bfs`]: struct@crate::sync::Bfs
+/// [`Node`]: trait@crate::sync::Node
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone)]
-/// Synchronous, fast breadth-first iterator for types implementing the [`FastNode`] trait.
+pub struct LevelBfs<N>
+where
+    N: Node,
+{
+    queue: queue::Queue<N, N::Error>,
+    errors: VecDeque<N::Error>,
+    max_depth: Option<usize>,
+}
+
+impl<N> LevelBfs<N>
+where
+    N: Node,
+{
+    #[inline]
+    /// Creates a new [`LevelBfs`] iterator.
+    ///
+    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`LevelBfs`]: struct@crate::sync::LevelBfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let mut queue = queue::Queue::new(allow_circles);
+        // the root is expanded lazily but never yielded
+        queue.add(0, Ok(root.into()));
+        Self {
+            queue,
+            errors: VecDeque::new(),
+            max_depth: max_depth.into(),
+        }
+    }
+}
+
+impl<N> Iterator for LevelBfs<N>
+where
+    N: Node,
+{
+    type Item = Result<(usize, Vec<N>), N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(err) = self.errors.pop_front() {
+                return Some(Err(err));
+            }
+            let depth = self.queue.front_depth()?;
+            let mut level = Vec::new();
+            while self.queue.front_depth() == Some(depth) {
+                match self.queue.pop_front()? {
+                    (_, Ok(node)) => level.push(node),
+                    (_, Err(err)) => self.errors.push_back(err),
+                }
+            }
+            // the next level is queued behind the current level
+            if self.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                for node in &level {
+                    match node.children(depth + 1) {
+                        Ok(children) => self.queue.add_all(depth + 1, children),
+                        Err(err) => self.queue.add(depth + 1, Err(err)),
+                    }
+                }
+            }
+            // the root level is only expanded
+            if depth > 0 && !level.is_empty() {
+                return Some(Ok((depth, level)));
+            }
+        }
+    }
+}
+
+/// The reverse of a [`BidiNode`], whose children are the parents of the node.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+struct Reversed<N>(N);
+
+impl<N> Node for Reversed<N>
+where
+    N: BidiNode + 'static,
+{
+    type Error = N::Error;
+
+    #[inline]
+    fn children(&self, depth: usize) -> NodeIter<Self, Self::Error> {
+        let parents = self.0.parents(depth)?;
+        Ok(Box::new(parents.map(|parent| parent.map(Reversed))))
+    }
+}
+
+/// Synchronous bidirectional breadth-first search for types implementing
+/// the [`BidiNode`] trait.
+///
+/// Searches for a path from `source` to `target` by running two [`Bfs`]
+/// traversals in turns, one forward from `source` along the children and
+/// one backward from `target` along the parents, until they meet on a
+/// shared node.
+/// This expands far fewer nodes than a single [`Bfs`] when the graph
+/// branches in both directions.
 ///
 /// ### Example
 /// ```
-/// use par_dfs::sync::{FastNode, FastBfs, ExtendQueue, NodeIter};
+/// use par_dfs::sync::{BidiNode, BidirectionalBfs, Node, NodeIter};
 ///
+/// /// The numbers below 100, where `n` has the children `2n` and `n + 1`.
 /// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
-/// struct WordNode(String);
+/// struct Number(u32);
 ///
-/// impl FastNode for WordNode {
+/// impl Node for Number {
 ///     type Error = std::convert::Infallible;
 ///
-///     fn add_children<E>(
-///         &self, _depth: usize, queue: &mut E
-///     ) -> Result<(), Self::Error>
-///     where
-///         E: ExtendQueue<Self, Self::Error>,
-///     {
-///         let len = self.0.len();
-///         if len > 1 {
-///             let mid = len/2;
-///             queue.add(Ok(Self(self.0[..mid].into())));
-///             queue.add(Ok(Self(self.0[mid..].into())));
-///         } else {
-///             assert!(len == 1);
-///             queue.add(Ok(Self(self.0.clone())));
-///         }
-///         Ok(())
+///     fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+///         let children = [self.0 * 2, self.0 + 1].into_iter().filter(|n| *n < 100);
+///         Ok(Box::new(children.map(Number).map(Ok)))
 ///     }
 /// }
 ///
-/// let word = "Hello World";
-/// let root = WordNode(word.into());
-/// let limit = (word.len() as f32).log2().ceil() as usize;
+/// impl BidiNode for Number {
+///     fn parents(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+///         let half = (self.0 % 2 == 0).then_some(self.0 / 2);
+///         let parents = half.into_iter().chain(self.0.checked_sub(1));
+///         Ok(Box::new(parents.map(Number).map(Ok)))
+///     }
+/// }
 ///
-/// let bfs = FastBfs::<WordNode>::new(root, limit, true);
-/// let output = bfs.collect::<Result<Vec<_>, _>>().unwrap();
-/// let result = output[output.len()-word.len()..]
-///     .into_iter().map(|s| s.0.as_str()).collect::<String>();
-/// assert_eq!(result, "Hello World");
+/// let path = BidirectionalBfs::<Number>::new(Number(1), Number(12), None)
+///     .path()
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(path.len(), 5);
+/// assert_eq!(path.first(), Some(&Number(1)));
+/// assert_eq!(path.last(), Some(&Number(12)));
+///
+/// // 12 is not reachable from 1 within 3 hops
+/// assert!(BidirectionalBfs::<Number>::new(Number(1), Number(12), 3).path().is_none());
 /// ```
 ///
-/// [`FastNode`]: trait@crate::sync::FastNode
-pub struct FastBfs<N>
+/// [`Bfs`]: struct@crate::sync::Bfs
+/// [`BidiNode`]: trait@crate::sync::BidiNode
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct BidirectionalBfs<N>
 where
-    N: FastNode,
+    N: BidiNode + 'static,
 {
-    queue: queue::Queue<N, N::Error>,
-    max_depth: Option<usize>,
+    forward: Bfs<N>,
+    backward: Bfs<Reversed<N>>,
+    source: N,
+    target: N,
 }
 
-impl<N> FastBfs<N>
+impl<N> BidirectionalBfs<N>
 where
-    N: FastNode,
+    N: BidiNode + 'static,
 {
     #[inline]
-    /// Creates a new [`FastBfs`] iterator.
-    ///
-    /// The BFS will be performed from the `root` node up to depth `max_depth`.
+    /// Creates a new [`BidirectionalBfs`] from `source` to `target`.
     ///
-    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    /// Only paths with at most `max_depth` edges are found, where the forward
+    /// traversal covers the first half of the path and the backward
+    /// traversal covers the second half.
     ///
-    /// [`FastBfs`]: struct@crate::sync::FastBfs
-    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    /// [`BidirectionalBfs`]: struct@crate::sync::BidirectionalBfs
+    pub fn new<R, M>(source: R, target: R, max_depth: M) -> Self
     where
         R: Into<N>,
-        D: Into<Option<usize>>,
+        M: Into<Option<usize>>,
     {
-        let mut queue = queue::Queue::new(allow_circles);
-        let root: N = root.into();
+        let source = source.into();
+        let target = target.into();
         let max_depth = max_depth.into();
-        let depth = 1;
-        let mut depth_queue = queue::QueueWrapper::new(depth, &mut queue);
-        if let Err(err) = root.add_children(depth, &mut depth_queue) {
-            depth_queue.add(Err(err));
+        let mut forward = Bfs::new(
+            source.clone(),
+            max_depth.map(|max_depth| max_depth.div_ceil(2)),
+            false,
+        );
+        let mut backward = Bfs::new(
+            Reversed(target.clone()),
+            max_depth.map(|max_depth| max_depth / 2),
+            false,
+        );
+        forward.queue.track_parents();
+        backward.queue.track_parents();
+        Self {
+            forward,
+            backward,
+            source,
+            target,
         }
-        Self { queue, max_depth }
     }
-}
-
-impl<N> Iterator for FastBfs<N>
-where
-    N: FastNode,
-{
-    type Item = Result<N, N::Error>;
 
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.queue.pop_front() {
-            // next node failed
-            Some((_, Err(err))) => Some(Err(err)),
-            // next node succeeded
-            Some((depth, Ok(node))) => {
-                if let Some(max_depth) = self.max_depth {
-                    if depth >= max_depth {
-                        return Some(Ok(node));
-                    }
+    /// Returns a path from `source` to `target`.
+    ///
+    /// The path starts with `source` and ends with `target`, or only
+    /// contains `source` when both are equal.
+    /// Both traversals advance in turns, so the path is not necessarily a
+    /// shortest path.
+    /// Returns the first error encountered before the traversals meet, or
+    /// [`None`] when `target` is not reachable from `source` within `max_depth`.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    pub fn path(mut self) -> Option<Result<Vec<N>, N::Error>> {
+        if self.source == self.target {
+            return Some(Ok(vec![self.source]));
+        }
+        loop {
+            let forward = self.forward.next();
+            let forward_done = forward.is_none();
+            match forward {
+                Some(Ok(node)) if self.backward.queue.discovered(&Reversed(node.clone())) => {
+                    return Some(Ok(self.join(node)));
                 }
-                let next_depth = depth + 1;
-                let mut depth_queue = queue::QueueWrapper::new(next_depth, &mut self.queue);
-                if let Err(err) = node.add_children(next_depth, &mut depth_queue) {
-                    depth_queue.add(Err(err));
+                Some(Err(err)) => return Some(Err(err)),
+                _ => {}
+            }
+            match self.backward.next() {
+                Some(Ok(Reversed(node))) if self.forward.queue.discovered(&node) => {
+                    return Some(Ok(self.join(node)));
                 }
-                Some(Ok(node))
+                Some(Err(err)) => return Some(Err(err)),
+                None if forward_done => return None,
+                _ => {}
             }
-            // no next node
-            None => None,
         }
     }
+
+    /// Joins the paths of both traversals to the shared `node`.
+    fn join(&self, node: N) -> Vec<N> {
+        let mut path = self.forward.queue.path_to(node.clone());
+        let backward = self.backward.queue.path_to(Reversed(node));
+        path.extend(backward.into_iter().rev().skip(1).map(|node| node.0));
+        path
+    }
 }
 
+traversal_options!(Bfs<Node>);
+traversal_options!(FastBfs<FastNode>);
+
 #[cfg(feature = "rayon")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
 mod par {
     use crate::sync::par::parallel_iterator;
     use crate::sync::{Bfs, FastBfs, FastNode, Node};
 
-    parallel_iterator!(Bfs<Node>);
-    parallel_iterator!(FastBfs<FastNode>);
+    parallel_iterator!(Bfs<Node>, breadth_first);
+    parallel_iterator!(FastBfs<FastNode>, breadth_first);
+}
+
+#[cfg(feature = "crossbeam")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crossbeam")))]
+mod channel {
+    use crate::sync::channel::channel_iterator;
+    use crate::sync::{Bfs, FastBfs, FastNode, Node};
+
+    channel_iterator!(Bfs<Node>);
+    channel_iterator!(FastBfs<FastNode>);
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+mod checkpoint {
+    use crate::sync::checkpoint::checkpoint_iterator;
+    use crate::sync::{Bfs, FastBfs, FastNode, Node};
+
+    checkpoint_iterator!(Bfs<Node>);
+    checkpoint_iterator!(FastBfs<FastNode>);
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Bfs, FastBfs};
+    use super::{Bfs, BidirectionalBfs, FastBfs, LevelBfs};
     use anyhow::Result;
 
     #[cfg(feature = "rayon")]
@@ -298,6 +1150,126 @@ mod tests {
         }
     }
 
+    test_depths!(
+        bfs_leaves:
+        (
+            Bfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true),
+            [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        bfs_max_depth_zero:
+        (
+            Bfs::<crate::utils::test::LeafNode>::new(0, 0, true),
+            [] as [usize; 0]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_bfs_leaves:
+        (
+            FastBfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true),
+            [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_bfs_max_depth_zero:
+        (
+            FastBfs::<crate::utils::test::LeafNode>::new(0, 0, true),
+            [] as [usize; 0]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        bfs_max_branching:
+        (
+            Bfs::<crate::utils::test::LeafNode>::new(0, 3, true).max_branching(1),
+            [1, 2, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_bfs_max_branching:
+        (
+            FastBfs::<crate::utils::test::LeafNode>::new(0, 3, true).max_branching(1),
+            [1, 2, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        bfs_include_root:
+        (
+            Bfs::<crate::utils::test::LeafNode>::new(0, 2, true).include_root(),
+            [0, 1, 1, 2, 2, 2, 2]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        bfs_include_root_max_depth_zero:
+        (
+            Bfs::<crate::utils::test::LeafNode>::new(0, 0, true).include_root(),
+            [0]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_bfs_include_root:
+        (
+            FastBfs::<crate::utils::test::LeafNode>::new(0, 2, true).include_root(),
+            [0, 1, 1, 2, 2, 2, 2]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_bfs_yield_if:
+        (
+            FastBfs::<crate::utils::test::Node>::new(0, 3, true).yield_if(|_, depth| depth == 3),
+            [3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        bfs_min_depth:
+        (
+            Bfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2),
+            [2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_bfs_min_depth:
+        (
+            FastBfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2),
+            [2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
     test_depths!(
         bfs:
         (
@@ -336,4 +1308,472 @@ mod tests {
         test_depths_serial,
         test_depths_parallel,
     );
+
+    #[test]
+    fn test_fast_bfs_with_last_sibling_flag() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        let edges: &[&[usize]] = &[&[1, 2], &[3, 4], &[5], &[], &[], &[]];
+        let root = GraphNode::new(0, edges);
+        let flags = FastBfs::<GraphNode>::new(root, None, false)
+            .with_last_sibling_flag()
+            .map(|node| node.map(|(node, last)| (node.id, last)))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(
+            flags,
+            [(1, false), (2, true), (3, false), (4, true), (5, true)]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_bfs_into_par_iter_ordered() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        // enough threads to split the traversal
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build()?;
+        let root = TreeNode(0);
+
+        let sequential = Bfs::<TreeNode>::new(root, 10, false).collect::<Result<Vec<_>, _>>()?;
+        let parallel = pool.install(|| {
+            Bfs::<TreeNode>::new(root, 10, false)
+                .into_par_iter_ordered()
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        similar_asserts::assert_eq!(parallel, sequential);
+
+        let sequential =
+            FastBfs::<TreeNode>::new(root, 10, false).collect::<Result<Vec<_>, _>>()?;
+        let parallel = pool.install(|| {
+            FastBfs::<TreeNode>::new(root, 10, false)
+                .into_par_iter_ordered()
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        similar_asserts::assert_eq!(parallel, sequential);
+
+        let sequential = Bfs::<TreeNode>::new(root, 10, false)
+            .with_depth()
+            .collect::<Result<Vec<_>, _>>()?;
+        let parallel = pool.install(|| {
+            Bfs::<TreeNode>::new(root, 10, false)
+                .with_depth()
+                .into_par_iter_ordered()
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        similar_asserts::assert_eq!(parallel, sequential);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_with_backend() -> Result<()> {
+        use crate::sync::backend::InMemory;
+        use crate::utils::test::TreeNode;
+
+        let root = TreeNode(0);
+        let expected = Bfs::<TreeNode>::new(root, 6, false)
+            .with_depth()
+            .collect::<Result<Vec<_>, _>>()?;
+        for memory_limit in [0, 1, 3, 16] {
+            let bfs = Bfs::<TreeNode>::with_backend(root, 6, false, InMemory::new(memory_limit));
+            let nodes = bfs.with_depth().collect::<Result<Vec<_>, _>>()?;
+            similar_asserts::assert_eq!(nodes, expected);
+        }
+
+        // clones copy the overflowing nodes
+        let mut bfs = Bfs::<TreeNode>::with_backend(root, 6, false, InMemory::new(2));
+        bfs.nth(10).transpose()?;
+        let clone = bfs.clone();
+        similar_asserts::assert_eq!(
+            clone.collect::<Result<Vec<_>, _>>()?,
+            bfs.collect::<Result<Vec<_>, _>>()?
+        );
+
+        #[cfg(feature = "spill")]
+        {
+            use crate::sync::backend::SpillFile;
+            let bfs = Bfs::<TreeNode>::with_backend(root, 6, false, SpillFile::new(4));
+            let nodes = bfs.with_depth().collect::<Result<Vec<_>, _>>()?;
+            similar_asserts::assert_eq!(nodes, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_with_failing_backend() {
+        use crate::sync::backend::InMemory;
+        use crate::sync::QueueBackend;
+        use crate::utils::test::{Error, TreeNode};
+
+        /// A backend that fails to store every third node.
+        #[derive(Clone)]
+        struct Flaky(InMemory<TreeNode>, usize);
+
+        impl QueueBackend<TreeNode> for Flaky {
+            fn memory_limit(&self) -> usize {
+                self.0.memory_limit()
+            }
+
+            fn push_back(&mut self, depth: usize, item: TreeNode) -> std::io::Result<()> {
+                self.1 += 1;
+                if self.1.is_multiple_of(3) {
+                    return Err(std::io::Error::other("disk full"));
+                }
+                self.0.push_back(depth, item)
+            }
+
+            fn pop_front(&mut self) -> std::io::Result<Option<(usize, TreeNode)>> {
+                self.0.pop_front()
+            }
+
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+
+            fn boxed_clone(&self) -> Box<dyn QueueBackend<TreeNode>> {
+                Box::new(self.clone())
+            }
+        }
+
+        // the lost nodes are yielded as errors in their place
+        let bfs = Bfs::<TreeNode>::with_backend(TreeNode(0), 3, false, Flaky(InMemory::new(4), 0));
+        let ids = bfs.map(|node| node.map(|node| node.0)).collect::<Vec<_>>();
+        let expected = (1..15).map(|id| {
+            if [10, 13].contains(&id) {
+                Err(Error)
+            } else {
+                Ok(id)
+            }
+        });
+        similar_asserts::assert_eq!(ids, expected.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_bfs_with_depth() -> Result<()> {
+        // the test nodes store their depth
+        let nodes = Bfs::<crate::utils::test::Node>::new(0, 3, true)
+            .with_depth()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(nodes.len(), 14);
+        assert!(nodes.iter().all(|(depth, node)| *depth == node.0));
+
+        #[cfg(feature = "rayon")]
+        {
+            let nodes = Bfs::<crate::utils::test::Node>::new(0, 3, true)
+                .with_depth()
+                .into_par_iter()
+                .collect::<Result<Vec<_>, _>>()?;
+            similar_asserts::assert_eq!(nodes.len(), 14);
+            assert!(nodes.iter().all(|(depth, node)| *depth == node.0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_level_bfs() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 3 → 0, 1 → 2, and 0 → 2 → 4
+        let edges: &[&[usize]] = &[&[1, 2], &[3, 2], &[4], &[0], &[]];
+        let levels = |max_depth: Option<usize>, allow_circles| {
+            LevelBfs::<GraphNode>::new(GraphNode::new(0, edges), max_depth, allow_circles)
+                .map(|level| {
+                    level.map(|(depth, nodes)| {
+                        (depth, nodes.into_iter().map(|node| node.id).collect())
+                    })
+                })
+                .collect::<Result<Vec<(usize, Vec<usize>)>, _>>()
+        };
+        similar_asserts::assert_eq!(levels(None, false)?, [(1, vec![1, 2]), (2, vec![3, 4])]);
+        similar_asserts::assert_eq!(levels(Some(1), false)?, [(1, vec![1, 2])]);
+        similar_asserts::assert_eq!(levels(Some(0), false)?, []);
+        similar_asserts::assert_eq!(
+            levels(Some(3), true)?,
+            [(1, vec![1, 2]), (2, vec![3, 2, 4]), (3, vec![0, 4])]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_from_frontier() {
+        use crate::utils::test::TreeNode;
+
+        let frontier = [(1, TreeNode(1)), (1, TreeNode(2))];
+        let ids: Vec<usize> = Bfs::<TreeNode>::from_frontier(frontier, 2, false)
+            .map(|node| node.unwrap().0)
+            .collect();
+        similar_asserts::assert_eq!(ids, [1, 2, 3, 4, 5, 6]);
+
+        let frontier = [(2, TreeNode(4)), (2, TreeNode(5))];
+        let ids: Vec<usize> = FastBfs::<TreeNode>::from_frontier(frontier, 3, true)
+            .map(|node| node.unwrap().0)
+            .collect();
+        similar_asserts::assert_eq!(ids, [4, 5, 9, 10, 11, 12]);
+    }
+
+    #[test]
+    fn test_bfs_start_depth() {
+        use crate::utils::test::TreeNode;
+
+        let nodes: Vec<(usize, usize)> = Bfs::<TreeNode>::new(TreeNode(0), 3, false)
+            .start_depth(2)
+            .with_depth()
+            .map(|node| node.map(|(depth, node)| (node.0, depth)).unwrap())
+            .collect();
+        similar_asserts::assert_eq!(nodes, [(1, 2), (2, 2), (3, 3), (4, 3), (5, 3), (6, 3)]);
+    }
+
+    #[test]
+    fn test_bfs_group_by() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        let ids = |nodes: Vec<TreeNode>| nodes.into_iter().map(|node| node.0).collect::<Vec<_>>();
+
+        // grouping by depth yields the levels
+        let levels = Bfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .group_by(|_, depth| depth)
+            .map(|group| group.map(|(depth, nodes)| (depth, ids(nodes))))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(levels, [(1, vec![1, 2]), (2, vec![3, 4, 5, 6])]);
+
+        // only consecutive nodes are grouped
+        let parities = Bfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .group_by(|node, _| node.0 % 2)
+            .map(|group| group.map(|(parity, nodes)| (parity, ids(nodes))))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(
+            parities,
+            [
+                (1, vec![1]),
+                (0, vec![2]),
+                (1, vec![3]),
+                (0, vec![4]),
+                (1, vec![5]),
+                (0, vec![6])
+            ]
+        );
+
+        let mut groups: Vec<_> = FastBfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .collect_groups(|node, _| node.0 % 2)?
+            .into_iter()
+            .map(|(parity, nodes)| (parity, ids(nodes)))
+            .collect();
+        groups.sort_unstable();
+        similar_asserts::assert_eq!(groups, [(0, vec![2, 4, 6]), (1, vec![1, 3, 5])]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_shard() {
+        use crate::utils::test::TreeNode;
+
+        let mut bfs = Bfs::<TreeNode>::new(TreeNode(0), 3, false);
+        let mut ids: Vec<usize> = bfs.by_ref().take(2).map(|node| node.unwrap().0).collect();
+        similar_asserts::assert_eq!(bfs.remaining(), 4);
+
+        let shards = bfs.shard(3);
+        let remaining: Vec<usize> = shards.iter().map(Bfs::remaining).collect();
+        similar_asserts::assert_eq!(remaining, [2, 1, 1]);
+        for shard in shards {
+            ids.extend(shard.map(|node| node.unwrap().0));
+        }
+        ids.sort_unstable();
+        similar_asserts::assert_eq!(ids, (1..15).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_bfs_skip_nodes() {
+        use crate::utils::test::TreeNode;
+
+        let mut bfs = FastBfs::<TreeNode>::new(TreeNode(0), 3, false);
+        // the subtrees of the skipped nodes 1 and 2 are lost
+        similar_asserts::assert_eq!(bfs.skip_nodes(2), 2);
+        similar_asserts::assert_eq!(bfs.next().map(|node| node.unwrap().0), None);
+
+        let mut bfs = Bfs::<TreeNode>::new(TreeNode(0), 3, false);
+        similar_asserts::assert_eq!(bfs.skip_nodes(1), 1);
+        let ids: Vec<usize> = bfs.map(|node| node.unwrap().0).collect();
+        similar_asserts::assert_eq!(ids, [2, 5, 6, 11, 12, 13, 14]);
+    }
+
+    #[test]
+    fn test_bfs_exact_len() {
+        use crate::utils::test::TreeNode;
+
+        let mut bfs = Bfs::<TreeNode>::new(TreeNode(0), 4, false);
+        similar_asserts::assert_eq!(bfs.exact_len(), Some(30));
+        // the frontier holds whole levels, each with its own subtree
+        let _ = bfs.nth(2);
+        similar_asserts::assert_eq!(bfs.exact_len(), Some(27));
+        similar_asserts::assert_eq!(bfs.count(), 27);
+
+        let fast = FastBfs::<TreeNode>::new(TreeNode(0), 4, false);
+        similar_asserts::assert_eq!(fast.exact_len(), Some(30));
+        let fast = FastBfs::<TreeNode>::new(TreeNode(0), 4, false).max_nodes(3);
+        similar_asserts::assert_eq!(fast.exact_len(), None);
+    }
+
+    #[test]
+    fn test_fast_bfs_max_nodes() -> Result<()> {
+        let iter = FastBfs::<crate::utils::test::Node>::new(0, None, true).max_nodes(4);
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 1, 2, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_prune_explain_skip() -> Result<()> {
+        use crate::sync::SkipReason;
+        use crate::utils::test::GraphNode;
+        use std::sync::{Arc, Mutex};
+
+        // 0 → 1 → 3
+        // 0 → 2 → 4
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[4], &[], &[]];
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let on_skip = {
+            let skipped = Arc::clone(&skipped);
+            move |node: &GraphNode, reason| skipped.lock().unwrap().push((node.id, reason))
+        };
+        let ids = Bfs::<GraphNode>::new(GraphNode::new(0, edges), None, false)
+            .prune(|node, _| node.id == 2)
+            .explain_skip(on_skip)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [1, 2, 3]);
+        similar_asserts::assert_eq!(*skipped.lock().unwrap(), [(2, SkipReason::Pruned)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_bfs_prune() -> Result<()> {
+        // the traversal is unbounded without pruning
+        let iter =
+            FastBfs::<crate::utils::test::Node>::new(0, None, true).prune(|_, depth| depth >= 2);
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 1, 2, 2, 2, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_bfs_with_hasher() -> Result<()> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        let hasher = BuildHasherDefault::<DefaultHasher>::default();
+        let iter = FastBfs::<crate::utils::test::Node, _>::with_hasher(0, 3, hasher);
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_with_roots() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 2, and 1 → 2 → 3
+        let edges: &[&[usize]] = &[&[2], &[2, 3], &[], &[]];
+        let roots = [0, 1].map(|id| GraphNode::new(id, edges));
+        let ids = Bfs::<GraphNode>::with_roots(roots, None, false)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_with_paths() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 3
+        // 0 → 2 → 3
+        // 0 → 2 → 0
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[3, 0], &[]];
+        let root = GraphNode::new(0, edges);
+        let paths = |bfs: Bfs<GraphNode>| -> Result<Vec<Vec<usize>>> {
+            Ok(bfs
+                .with_paths()
+                .map(|path| path.map(|path| path.iter().map(|node| node.id).collect()))
+                .collect::<Result<_, _>>()?)
+        };
+        similar_asserts::assert_eq!(
+            paths(Bfs::new(root, None, false))?,
+            [vec![0, 1], vec![0, 2], vec![0, 1, 3]]
+        );
+        // the first discovery of a node determines its path
+        similar_asserts::assert_eq!(
+            paths(Bfs::new(root, 3, true))?,
+            [
+                vec![0, 1],
+                vec![0, 2],
+                vec![0, 1, 3],
+                vec![0, 1, 3],
+                vec![0],
+                vec![0, 1],
+                vec![0, 2],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_shortest_path() -> Result<()> {
+        use crate::utils::test::{GraphNode, LeafNode, LEAF_DEPTH};
+
+        // 0 → 1 → 2 → 3 → 5
+        // 0 → 4 → 5 → 0
+        let edges: &[&[usize]] = &[&[1, 4], &[2], &[3], &[5], &[5], &[0]];
+        let root = GraphNode::new(0, edges);
+        let ids = |target: usize| -> Option<Result<Vec<usize>>> {
+            Bfs::<GraphNode>::shortest_path(root, |node| node.id == target)
+                .map(|path| Ok(path?.iter().map(|node| node.id).collect()))
+        };
+        similar_asserts::assert_eq!(ids(5).transpose()?, Some(vec![0, 4, 5]));
+        similar_asserts::assert_eq!(ids(3).transpose()?, Some(vec![0, 1, 2, 3]));
+        similar_asserts::assert_eq!(ids(0).transpose()?, Some(vec![0]));
+        assert!(ids(6).is_none());
+
+        // expanding a leaf panics, so the match must not be expanded
+        let path = Bfs::<LeafNode>::shortest_path(0, |node| node.0 == LEAF_DEPTH).transpose()?;
+        similar_asserts::assert_eq!(path, Some((0..=LEAF_DEPTH).map(LeafNode).collect()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bidirectional_bfs() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 2 → 3 → 4 → 5
+        // 0 → 6 → 5 → 7 → 0
+        let edges: &[&[usize]] = &[&[1, 6], &[2], &[3], &[4], &[5], &[7], &[5], &[0], &[]];
+        let node = |id| GraphNode::new(id, edges);
+        let path = |source, target, max_depth: Option<usize>| -> Result<Option<Vec<usize>>> {
+            let path =
+                BidirectionalBfs::<GraphNode>::new(node(source), node(target), max_depth).path();
+            Ok(path
+                .transpose()?
+                .map(|path| path.iter().map(|node| node.id).collect()))
+        };
+        similar_asserts::assert_eq!(path(0, 5, None)?, Some(vec![0, 6, 5]));
+        similar_asserts::assert_eq!(path(0, 7, Some(3))?, Some(vec![0, 6, 5, 7]));
+        similar_asserts::assert_eq!(path(1, 0, None)?, Some(vec![1, 2, 3, 4, 5, 7, 0]));
+        similar_asserts::assert_eq!(path(2, 2, None)?, Some(vec![2]));
+        assert!(path(1, 0, Some(5))?.is_none());
+        assert!(path(0, 8, None)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_peek_next() {
+        use crate::utils::test::Node;
+
+        let mut bfs = Bfs::<Node>::new(0, 3, true);
+        similar_asserts::assert_eq!(bfs.peek_next(), Some(&Ok(Node(0))));
+        similar_asserts::assert_eq!(bfs.next(), Some(Ok(Node(1))));
+        similar_asserts::assert_eq!(bfs.remaining(), 3);
+        while let Some(peeked) = bfs.peek_next().cloned() {
+            similar_asserts::assert_eq!(bfs.next(), Some(peeked));
+        }
+        similar_asserts::assert_eq!(bfs.remaining(), 0);
+    }
 }
@@ -0,0 +1,108 @@
+//! Support for consuming traversals through a bounded [`crossbeam_channel`].
+//!
+//! Calling `into_channel` on any of the synchronous iterators moves the
+//! traversal onto a background thread, which pushes each item into a bounded
+//! channel. When the channel is full, the traversal blocks until the consumer
+//! catches up, so no more than `capacity` items are buffered at any time.
+//!
+//! ### Shutdown
+//!
+//! The traversal thread stops as soon as it fails to send an item because
+//! the [`Receiver`] has been dropped. At most one additional node is expanded
+//! after the receiver is dropped.
+//!
+//! [`crossbeam_channel`]: mod@crossbeam_channel
+//! [`Receiver`]: struct@crossbeam_channel::Receiver
+
+use crossbeam_channel::{bounded, Receiver};
+use std::iter::Iterator;
+
+/// Spawns a thread that drives `iter` and sends its items into a bounded
+/// channel with the given `capacity`.
+///
+/// Returns the receiving half of the channel.
+pub fn into_channel<Iter>(iter: Iter, capacity: usize) -> Receiver<Iter::Item>
+where
+    Iter: Iterator + Send + 'static,
+    Iter::Item: Send + 'static,
+{
+    let (tx, rx) = bounded(capacity);
+    std::thread::spawn(move || {
+        for item in iter {
+            if tx.send(item).is_err() {
+                // the receiver was dropped
+                break;
+            }
+        }
+    });
+    rx
+}
+
+macro_rules! channel_iterator {
+    ($iter:ident<$node:ident>) => {
+        impl<N, V, D> $iter<N, V, D>
+        where
+            N: $node<D> + Send + 'static,
+            N::Error: Send + 'static,
+            V: $crate::sync::VisitedSet<N> + 'static,
+            D: $crate::sync::Depth,
+            Self: Send,
+        {
+            /// Drives the traversal on a background thread, sending each item into
+            /// a bounded channel with the given `capacity`.
+            ///
+            /// The traversal blocks while the channel is full and stops
+            /// once the returned [`Receiver`] is dropped.
+            ///
+            /// [`Receiver`]: struct@crossbeam_channel::Receiver
+            #[must_use]
+            pub fn into_channel(
+                self,
+                capacity: usize,
+            ) -> crossbeam_channel::Receiver<Result<N, N::Error>> {
+                $crate::sync::channel::into_channel(self, capacity)
+            }
+        }
+    };
+}
+pub(crate) use channel_iterator;
+
+#[cfg(test)]
+mod tests {
+    use crate::sync::{Bfs, Dfs, FastBfs, FastDfs};
+    use crate::utils::test::Node;
+    use anyhow::Result;
+
+    macro_rules! test_channel {
+        ($name:ident: $iter:ident) => {
+            paste::item! {
+                #[test]
+                fn [< test_ $name _ into_channel >] () -> Result<()> {
+                    let expected = $iter::<Node>::new(0, 3, true)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let received = $iter::<Node>::new(0, 3, true)
+                        .into_channel(1)
+                        .into_iter()
+                        .collect::<Result<Vec<_>, _>>()?;
+                    similar_asserts::assert_eq!(received, expected);
+                    Ok(())
+                }
+            }
+        };
+    }
+
+    test_channel!(dfs: Dfs);
+    test_channel!(bfs: Bfs);
+    test_channel!(fast_dfs: FastDfs);
+    test_channel!(fast_bfs: FastBfs);
+
+    #[test]
+    fn test_into_channel_unbounded_traversal() -> Result<()> {
+        // the traversal never ends, but must stop once the receiver is dropped
+        let rx = Dfs::<Node>::new(0, None, true).into_channel(4);
+        let received = rx.iter().take(100).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(received.len(), 100);
+        drop(rx);
+        Ok(())
+    }
+}
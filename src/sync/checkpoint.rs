@@ -0,0 +1,81 @@
+//! Support for persisting and resuming traversals using [`serde`].
+//!
+//! Calling `into_checkpoint` on any of the synchronous iterators returns a
+//! [`Checkpoint`] of its progress, which can be serialized and later passed to
+//! `from_checkpoint` to resume the traversal, e.g. after a restart.
+//! The resumed traversal yields exactly the nodes that the original traversal
+//! would have yielded.
+//!
+//! ### Limitations
+//!
+//! A checkpoint holds the pending nodes, the visited nodes and every option
+//! that is a plain value, e.g. `max_depth`, `min_depth`, `max_nodes`,
+//! `max_branching`, `preserve_child_order`, `include_root`, `unique`, the
+//! state of a shuffled traversal, and the tracked siblings and parents.
+//! The following is not part of a checkpoint:
+//!
+//! - Callbacks such as `explain_skip`, `on_cycle`, `yield_if`, `prune`, the
+//!   closure of `Dfs::from_fn` and the conversion of `catch_panics`, which
+//!   must be registered again on the resumed traversal.
+//! - The queue backend of `Bfs::with_backend`, whose nodes are moved into
+//!   the checkpoint instead.
+//! - Pending errors, which are dropped.
+//! - The `stats`, which start from zero.
+//! - The children of the last yielded node, so `skip_subtree` has no
+//!   effect before the resumed traversal yielded a node.
+//!
+//! [`serde`]: mod@serde
+//! [`Checkpoint`]: struct@self::Checkpoint
+
+use super::queue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// The serializable progress of a synchronous traversal.
+///
+/// Created by the `into_checkpoint` method of the traversals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint<N, V = HashSet<N>> {
+    pub(super) queue: queue::State<N, V>,
+    pub(super) max_depth: Option<usize>,
+}
+
+macro_rules! checkpoint_iterator {
+    ($iter:ident<$node:ident>) => {
+        impl<N, V, D> $iter<N, V, D>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N> + Clone,
+            D: $crate::sync::Depth,
+        {
+            /// Returns a [`Checkpoint`] of the progress of this traversal.
+            ///
+            /// The traversal can be resumed from the checkpoint using `from_checkpoint`.
+            /// Pending errors are dropped.
+            ///
+            /// [`Checkpoint`]: struct@crate::sync::checkpoint::Checkpoint
+            #[must_use]
+            pub fn into_checkpoint(self) -> $crate::sync::checkpoint::Checkpoint<N, V> {
+                $crate::sync::checkpoint::Checkpoint {
+                    queue: self.queue.into_state(),
+                    max_depth: self.max_depth,
+                }
+            }
+
+            /// Resumes a traversal from a [`Checkpoint`].
+            ///
+            /// Callbacks are not part of the checkpoint and must be registered again.
+            ///
+            /// [`Checkpoint`]: struct@crate::sync::checkpoint::Checkpoint
+            #[must_use]
+            pub fn from_checkpoint(checkpoint: $crate::sync::checkpoint::Checkpoint<N, V>) -> Self {
+                Self {
+                    queue: $crate::sync::queue::Queue::from_state(checkpoint.queue),
+                    max_depth: checkpoint.max_depth,
+                    stats: $crate::sync::Stats::default(),
+                }
+            }
+        }
+    };
+}
+pub(crate) use checkpoint_iterator;
@@ -1,9 +1,22 @@
+#[cfg(feature = "dot")]
+use super::graph::DotError;
+use super::graph::Graph;
+use super::options::traversal_options;
 use super::queue;
-use super::{ExtendQueue, FastNode, Node, Queue};
+use super::{
+    CirclePolicy, Depth, ExtendQueue, FastNode, Leaves, Node, NodeIter, OwnedNode, Queue,
+    SkipReason, Stats, VisitedSet,
+};
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
 use std::iter::Iterator;
+use std::ops::ControlFlow;
 
 /// Synchronous depth-first iterator for types implementing the [`Node`] trait.
 ///
+/// The children of every node are yielded in the order they are produced,
+/// like in a [`Bfs`], unless [`preserve_child_order`] is disabled.
+///
 /// ### Example
 /// ```
 /// use par_dfs::sync::{Node, Dfs, NodeIter};
@@ -20,7 +33,7 @@ use std::iter::Iterator;
 ///             vec![]
 ///         } else {
 ///             let mid = len/2;
-///             vec![self.0[mid..].into(), self.0[..mid].into()]
+///             vec![self.0[..mid].into(), self.0[mid..].into()]
 ///         };
 ///         let nodes = nodes.into_iter()
 ///             .map(Self)
@@ -39,75 +52,809 @@ use std::iter::Iterator;
 /// ```
 ///
 /// [`Node`]: trait@crate::sync::Node
+/// [`Bfs`]: struct@crate::sync::Bfs
+/// [`preserve_child_order`]: Self::preserve_child_order
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone)]
-pub struct Dfs<N>
+pub struct Dfs<N, V = HashSet<N>, D = usize>
 where
-    N: Node,
+    N: Node<D>,
 {
-    queue: queue::Queue<N, N::Error>,
+    queue: queue::Queue<N, N::Error, V, D>,
     max_depth: Option<usize>,
+    stats: Stats,
 }
 
-impl<N> Dfs<N>
+impl<N, D> Dfs<N, HashSet<N>, D>
 where
-    N: Node,
+    N: Node<D>,
+    D: Depth,
 {
     #[inline]
     /// Creates a new [`Dfs`] iterator.
     ///
     /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    /// The bound is inclusive, unless a [`DepthBound`] says otherwise.
     ///
     /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    /// Otherwise, the root counts as visited, so a child equal to the root
+    /// is skipped as a [`Cycle`] instead of being yielded.
     ///
     /// [`Dfs`]: struct@crate::sync::Dfs
-    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    /// [`DepthBound`]: enum@crate::DepthBound
+    /// [`Cycle`]: enum@crate::sync::SkipReason
+    pub fn new<R, M>(root: R, max_depth: M, allow_circles: bool) -> Self
     where
         R: Into<N>,
-        D: Into<Option<usize>>,
+        M: Into<Option<usize>>,
+    {
+        Self::with_roots([root], max_depth, allow_circles)
+    }
+
+    #[inline]
+    /// Creates a new [`Dfs`] iterator from a `root` whose conversion can fail.
+    ///
+    /// Like [`new`](Self::new), but the `root` is converted with [`TryInto`],
+    /// e.g. for nodes that are parsed or read from the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the conversion if the `root` is not a valid node.
+    ///
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    /// [`TryInto`]: trait@std::convert::TryInto
+    pub fn try_new<R, M>(root: R, max_depth: M, allow_circles: bool) -> Result<Self, R::Error>
+    where
+        R: TryInto<N>,
+        M: Into<Option<usize>>,
+    {
+        Ok(Self::new(root.try_into()?, max_depth, allow_circles))
+    }
+
+    #[inline]
+    /// Creates a new [`Dfs`] iterator starting from multiple `roots`.
+    ///
+    /// The DFS will be performed from all `roots` up to depth `max_depth`,
+    /// sharing one set of visited nodes, so nodes reachable from more than
+    /// one root are only yielded once.
+    /// Like a single root, the roots themselves are not yielded,
+    /// unless [`include_root`](Self::include_root) is set.
+    /// Since the queue is processed from the back, the roots are expanded in
+    /// reverse order: the subtree of the last root is traversed first and
+    /// the subtree of the first root last.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    pub fn with_roots<I, R, M>(roots: I, max_depth: M, allow_circles: bool) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        // the roots are expanded lazily but never yielded
+        let frontier = roots.into_iter().map(|root| (0, root.into()));
+        Self::from_frontier(frontier, max_depth, allow_circles)
+    }
+
+    #[inline]
+    /// Creates a new [`Dfs`] iterator from an explicit `frontier` of
+    /// nodes with their depths.
+    ///
+    /// The frontier is queued as-is, as if it was discovered by an earlier
+    /// traversal, e.g. to resume a traversal partially or to hand each worker
+    /// a slice of the frontier of a larger traversal.
+    /// Nodes at depth 0 are roots, which are only expanded, while deeper nodes
+    /// are yielded and expanded up to depth `max_depth`.
+    /// Since the queue is processed from the back, the last node of the
+    /// frontier is yielded first.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    /// Otherwise, the nodes of the frontier are visited and yielded only once.
+    ///
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    pub fn from_frontier<I, M>(frontier: I, max_depth: M, allow_circles: bool) -> Self
+    where
+        I: IntoIterator<Item = (usize, N)>,
+        M: Into<Option<usize>>,
     {
         let mut queue = queue::Queue::new(allow_circles);
-        let root = root.into();
         let max_depth = max_depth.into();
-        let depth = 1;
-        match root.children(depth) {
-            Ok(children) => queue.add_all(depth, children),
-            Err(err) => queue.add(depth, Err(err)),
+        for (depth, node) in frontier {
+            queue.add(depth, Ok(node));
         }
-        Self { queue, max_depth }
+        Self {
+            queue,
+            max_depth,
+            stats: Stats::default(),
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+    #[inline]
+    /// Creates a new [`Dfs`] iterator that expands children in a shuffled order.
+    ///
+    /// The children of each node are shuffled by a PRNG seeded with `seed`,
+    /// so the same seed always produces the same traversal.
+    /// Parallel splits draw their own seeds and are not reproducible.
+    ///
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    pub fn shuffled<R, M>(root: R, max_depth: M, allow_circles: bool, seed: u64) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        let mut iter = Self::new(root, max_depth, allow_circles);
+        iter.queue.set_shuffle(seed);
+        iter
+    }
+
+    #[inline]
+    /// Creates a new [`Dfs`] iterator whose children are produced by a closure.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`,
+    /// calling `children` with a node and the depth of its children in place
+    /// of [`Node::children`], which is never called.
+    /// In contrast to [`FnNode`], the closure is stored once in the iterator
+    /// instead of in every node, and since it is [`FnMut`], it can hold the
+    /// state of a crawl.
+    /// Clones and parallel splits of the iterator share the closure.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// ### Example
+    /// ```
+    /// use par_dfs::sync::{Dfs, Node, NodeIter};
+    ///
+    /// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    /// struct Number(u32);
+    ///
+    /// impl Node for Number {
+    ///     type Error = std::convert::Infallible;
+    ///
+    ///     fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+    ///         Ok(Box::new(std::iter::empty()))
+    ///     }
+    /// }
+    ///
+    /// // the numbers below 10 that are reachable by doubling or adding three
+    /// let dfs = Dfs::<Number>::from_fn(Number(1), None, false, |n: &Number, _depth| {
+    ///     let children = [n.0 * 2, n.0 + 3].into_iter().filter(|n| *n < 10);
+    ///     Ok(Box::new(children.map(Number).map(Ok)))
+    /// });
+    /// let mut numbers = dfs.map(|n| n.map(|n| n.0)).collect::<Result<Vec<_>, _>>().unwrap();
+    /// numbers.sort_unstable();
+    /// assert_eq!(numbers, [2, 4, 5, 7, 8]);
+    /// ```
+    ///
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    /// [`Node::children`]: fn@crate::sync::Node::children
+    /// [`FnNode`]: struct@crate::sync::FnNode
+    /// [`FnMut`]: trait@std::ops::FnMut
+    pub fn from_fn<R, M, F>(root: R, max_depth: M, allow_circles: bool, children: F) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+        F: FnMut(&N, D) -> NodeIter<N, N::Error> + Send + 'static,
+    {
+        let mut iter = Self::new(root, max_depth, allow_circles);
+        iter.queue.set_children(children);
+        iter
     }
 }
 
-impl<N> Iterator for Dfs<N>
+impl<N, S, D> Dfs<N, HashSet<N, S>, D>
 where
-    N: Node,
+    N: Node<D>,
+    S: BuildHasher,
+    D: Depth,
 {
-    type Item = Result<N, N::Error>;
+    #[inline]
+    /// Creates a new [`Dfs`] iterator that hashes visited nodes using `hash_builder`.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// The default hasher is resistant to collision attacks, but comparatively slow
+    /// for nodes that are cheap to hash, e.g. integers, where hashing can dominate
+    /// the traversal.
+    ///
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    pub fn with_hasher<R, M>(root: R, max_depth: M, hash_builder: S) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        Self::with_visited(root, max_depth, HashSet::with_hasher(hash_builder))
+    }
+}
 
+impl<N, V, D> Dfs<N, V, D>
+where
+    N: Node<D>,
+    V: VisitedSet<N>,
+    D: Depth,
+{
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.queue.pop_back() {
-            // next node failed
-            Some((_, Err(err))) => Some(Err(err)),
-            // next node succeeded
-            Some((depth, Ok(node))) => {
-                if let Some(max_depth) = self.max_depth {
-                    if depth >= max_depth {
-                        return Some(Ok(node));
-                    }
+    /// Creates a new [`Dfs`] iterator that tracks visited nodes in `visited`.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    /// Nodes that are already contained in `visited` are not yielded.
+    ///
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    pub fn with_visited<R, M>(root: R, max_depth: M, visited: V) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        let mut queue = queue::Queue::with_visited(false, visited);
+        let max_depth = max_depth.into();
+        // the root is expanded lazily but never yielded
+        queue.add(0, Ok(root.into()));
+        Self {
+            queue,
+            max_depth,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Searches for a cycle and returns its path.
+    ///
+    /// In contrast to the visited set of the traversal, this keeps track of the
+    /// ancestors along the current path from the root.
+    /// When a node has a child that is also one of its ancestors, the path from
+    /// that ancestor back to itself is returned, e.g. `[a, b, c, a]` for the
+    /// cycle `a → b → c → a`.
+    /// Returns [`None`] when no cycle is reachable within `max_depth`.
+    ///
+    /// Every node is expanded at most once, so the search terminates even when
+    /// `allow_circles` is set.
+    /// The children are requested like those of the iterator, so options such
+    /// as [`max_branching`] and [`prune`] apply, where pruned nodes are not
+    /// expanded.
+    /// Cycles through nodes that this iterator already yielded cannot be detected,
+    /// hence this should be called on a fresh traversal.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered while expanding nodes.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    /// [`max_branching`]: fn@crate::sync::Dfs::max_branching
+    /// [`prune`]: fn@crate::sync::Dfs::prune
+    pub fn cycle_path(self) -> Result<Option<Vec<N>>, N::Error> {
+        // the ancestors of the current node, indexed by depth
+        let mut path: Vec<N> = Vec::new();
+        self.walk(|depth, node, children| {
+            // the subtrees of all nodes at this depth or deeper are complete
+            path.truncate(depth);
+            path.push(node.clone());
+            for child in children.unwrap_or_default() {
+                if let Some(start) = path.iter().position(|ancestor| ancestor == child) {
+                    let mut cycle = path.split_off(start);
+                    cycle.push(child.clone());
+                    return ControlFlow::Break(cycle);
                 }
+            }
+            ControlFlow::Continue(())
+        })
+    }
 
-                match node.children(depth + 1) {
-                    Ok(children) => {
-                        self.queue.add_all(depth + 1, children);
+    /// Runs the traversal and returns the discovered graph as an adjacency map.
+    ///
+    /// Every expanded node, including the root, is mapped to all of its
+    /// children in the order they were produced, including children that
+    /// were already visited.
+    /// Nodes at `max_depth` are not expanded and are hence only present as
+    /// children.
+    /// Every node is expanded at most once, so the search terminates even when
+    /// `allow_circles` is set.
+    /// The children are requested like those of the iterator, so options such
+    /// as [`max_branching`] and [`prune`] apply, where pruned nodes are not
+    /// expanded.
+    ///
+    /// The returned map can be traversed again using [`BorrowedGraphDfs`].
+    /// This should be called on a fresh traversal.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered while expanding nodes.
+    ///
+    /// [`BorrowedGraphDfs`]: struct@crate::sync::graph::BorrowedGraphDfs
+    /// [`max_branching`]: fn@crate::sync::Dfs::max_branching
+    /// [`prune`]: fn@crate::sync::Dfs::prune
+    pub fn collect_adjacency(self) -> Result<HashMap<N, Vec<N>>, N::Error> {
+        let mut adjacency: HashMap<N, Vec<N>> = HashMap::new();
+        self.walk(|_, node, children| {
+            if let Some(children) = children {
+                adjacency.insert(node.clone(), children.to_vec());
+            }
+            ControlFlow::<()>::Continue(())
+        })?;
+        Ok(adjacency)
+    }
+
+    /// Runs the traversal and returns the discovered graph as a [`Graph`].
+    ///
+    /// Every node, including the root, is assigned the index of its first
+    /// discovery, and every relation between an expanded node and each of
+    /// its children becomes an edge between their indices, in the order they
+    /// are discovered.
+    /// Like [`collect_adjacency`](Self::collect_adjacency), every node is
+    /// expanded at most once, so edges that close a cycle are included as-is
+    /// and the search terminates even when `allow_circles` is set.
+    /// The children are requested like those of the iterator, so options such
+    /// as [`max_branching`] and [`prune`] apply, where pruned nodes are not
+    /// expanded.
+    ///
+    /// This should be called on a fresh traversal.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered while expanding nodes.
+    ///
+    /// [`Graph`]: struct@crate::sync::graph::Graph
+    /// [`max_branching`]: fn@crate::sync::Dfs::max_branching
+    /// [`prune`]: fn@crate::sync::Dfs::prune
+    pub fn collect_graph(self) -> Result<Graph<N>, N::Error> {
+        use std::collections::hash_map::Entry;
+
+        /// Returns the index of `node`, adding it when it is first seen.
+        fn index<N>(graph: &mut Graph<N>, indices: &mut HashMap<N, usize>, node: &N) -> usize
+        where
+            N: std::hash::Hash + Eq + Clone,
+        {
+            match indices.entry(node.clone()) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    graph.nodes.push(node.clone());
+                    *entry.insert(graph.nodes.len() - 1)
+                }
+            }
+        }
+
+        let mut graph = Graph::default();
+        let mut indices: HashMap<N, usize> = HashMap::new();
+        self.walk(|_, node, children| {
+            let parent = index(&mut graph, &mut indices, node);
+            for child in children.unwrap_or_default() {
+                let child = index(&mut graph, &mut indices, child);
+                graph.edges.push((parent, child));
+            }
+            ControlFlow::<()>::Continue(())
+        })?;
+        Ok(graph)
+    }
+
+    /// Runs the traversal and writes the discovered graph to `writer`
+    /// in the Graphviz [DOT] format.
+    ///
+    /// The graph is discovered using [`collect_graph`](Self::collect_graph)
+    /// and written using [`Graph::to_dot`], where every node is labeled by
+    /// `label`, e.g. using [`Node::display_label`].
+    ///
+    /// This should be called on a fresh traversal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DotError::Node`] with the first error encountered while
+    /// expanding nodes, in which case nothing is written, or
+    /// [`DotError::Io`] if writing fails.
+    ///
+    /// [DOT]: https://graphviz.org/doc/info/lang.html
+    /// [`Graph::to_dot`]: fn@crate::sync::graph::Graph::to_dot
+    /// [`Node::display_label`]: fn@crate::sync::Node::display_label
+    /// [`DotError::Node`]: enum@crate::sync::graph::DotError
+    /// [`DotError::Io`]: enum@crate::sync::graph::DotError
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use par_dfs::sync::{Dfs, Node, NodeIter};
+    ///
+    /// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    /// struct Mod3(usize);
+    ///
+    /// impl Node for Mod3 {
+    ///     type Error = std::convert::Infallible;
+    ///
+    ///     fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+    ///         Ok(Box::new(std::iter::once(Ok(Mod3((self.0 + 1) % 3)))))
+    ///     }
+    /// }
+    ///
+    /// let mut dot = Vec::new();
+    /// Dfs::<Mod3>::new(Mod3(0), None, true)
+    ///     .to_dot(&mut dot, |node| node.display_label().into_owned())
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(dot).unwrap(),
+    ///     "digraph {\n    0 [label=\"Mod3(0)\"];\n    1 [label=\"Mod3(1)\"];\n    \
+    ///      2 [label=\"Mod3(2)\"];\n    0 -> 1;\n    1 -> 2;\n    2 -> 0;\n}\n",
+    /// );
+    /// ```
+    #[cfg(feature = "dot")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dot")))]
+    pub fn to_dot<W, F>(self, writer: W, label: F) -> Result<(), DotError<N::Error>>
+    where
+        W: std::io::Write,
+        F: Fn(&N) -> String,
+    {
+        let graph = self.collect_graph().map_err(DotError::Node)?;
+        graph.to_dot(writer, label)?;
+        Ok(())
+    }
+
+    /// Returns the first node for which `f` returns `true`.
+    ///
+    /// In contrast to [`Iterator::find`], the matching node is not expanded,
+    /// so no more children are requested once a match is found.
+    /// Returns the first error encountered before a match, or [`None`] when
+    /// the traversal completes without a match.
+    ///
+    /// [`Iterator::find`]: fn@std::iter::Iterator::find
+    /// [`None`]: type@std::option::Option::None
+    pub fn find_map_node<F>(mut self, mut f: F) -> Option<Result<N, N::Error>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        let mut found = false;
+        while let Some((_, node, _)) = self.next_node_until(|node| {
+            found = f(node);
+            found
+        }) {
+            match node {
+                Ok(node) if found => return Some(Ok(node)),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+
+    /// Runs the traversal, expanding every node at most once, and calls
+    /// `visit` with the depth of every dequeued node, the node, and its
+    /// children in the order they were produced, or [`None`] if the node is
+    /// not expanded because of `max_depth` or [`prune`].
+    ///
+    /// The children are expanded like those of the iterator, but include
+    /// children that were already visited, which are not expanded again.
+    /// Stops at the first error, or when `visit` breaks with a value.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    /// [`prune`]: fn@crate::sync::Dfs::prune
+    fn walk<B, F>(mut self, mut visit: F) -> Result<Option<B>, N::Error>
+    where
+        F: FnMut(usize, &N, Option<&[N]>) -> ControlFlow<B>,
+    {
+        let mut stack = Vec::new();
+        while let Some(item) = self.queue.pop_front() {
+            stack.push(item);
+        }
+        // visited children are kept as edges, and only the expansion is deduplicated
+        self.queue.set_circle_policy(CirclePolicy::Always);
+        let mut expanded: HashSet<N> = HashSet::new();
+        let mut children = Vec::new();
+
+        while let Some((depth, node)) = stack.pop() {
+            let node = node?;
+            if expanded.contains(&node) {
+                continue;
+            }
+            if self.max_depth.is_some_and(|max_depth| depth >= max_depth)
+                || self.queue.prunes(&node, depth)
+            {
+                if let ControlFlow::Break(value) = visit(depth, &node, None) {
+                    return Ok(Some(value));
+                }
+                continue;
+            }
+            let len = self.queue.len();
+            self.expand(&node, depth + 1);
+            children.clear();
+            // the children are queued in reverse, so the first child is at the back
+            while self.queue.len() > len {
+                if let Some((_, child)) = self.queue.pop_back() {
+                    children.push(child?);
+                }
+            }
+            expanded.insert(node.clone());
+            if let ControlFlow::Break(value) = visit(depth, &node, Some(&children)) {
+                return Ok(Some(value));
+            }
+            for child in children.drain(..).rev() {
+                if !expanded.contains(&child) {
+                    stack.push((depth + 1, Ok(child)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Adds the children of `node` at `depth` to the queue and returns the
+    /// number of children it produced, including already visited children.
+    #[inline]
+    fn expand(&mut self, node: &N, depth: usize) -> usize {
+        let span = crate::trace::ExpandSpan::enter(depth);
+        let len = self.queue.len();
+        let max_branching = self.queue.max_branching();
+        let on_panic = self.queue.on_panic();
+        let children = self.queue.children();
+        let mut depth_queue = queue::QueueWrapper::new(depth, &mut self.queue).limit(max_branching);
+        let expanded = super::panic::catch(on_panic, depth, || match &children {
+            Some(children) => children
+                .call(node, queue::depth(depth))
+                .map(|children| depth_queue.add_all(children)),
+            None => node.children_into(queue::depth(depth), &mut depth_queue),
+        });
+        let mut produced = depth_queue.produced();
+        // the queue is processed from the back, so the children are reversed to pop them in order
+        self.queue.reverse_from(len);
+        if let Err(err) = expanded {
+            // the error is added regardless of the branching limit
+            self.queue.add(depth, Err(err));
+            produced += 1;
+        }
+        #[cfg(feature = "rand")]
+        self.queue.shuffle_from(len);
+        span.children(self.queue.len() - len);
+        self.stats.expanded(self.queue.len());
+        produced
+    }
+
+    /// Expands `node` at `depth` and returns the number of its children
+    /// that would be queued, without queueing them.
+    #[inline]
+    fn count_children(&mut self, node: &N, depth: usize) -> usize {
+        let len = self.queue.len();
+        self.expand(node, depth);
+        let added = self.queue.len() - len;
+        self.queue.truncate(len);
+        added
+    }
+
+    /// Sets whether the children of a node are yielded in the order they are produced.
+    ///
+    /// This is enabled by default, so [`children`] can be implemented
+    /// the same for a [`Dfs`] and a [`Bfs`].
+    /// Since the queue of a depth-first traversal is processed from the back,
+    /// the children of every node are reversed when they are queued.
+    /// When disabled, the children are yielded in reverse order, which was the
+    /// behavior of earlier versions and avoids reversing them.
+    /// Errors while producing the children are yielded before them either way.
+    ///
+    /// This should be called on a fresh iterator.
+    ///
+    /// [`children`]: fn@crate::sync::Node::children
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    /// [`Bfs`]: struct@crate::sync::Bfs
+    #[inline]
+    #[must_use]
+    pub fn preserve_child_order(mut self, preserve_child_order: bool) -> Self {
+        self.queue.set_preserve_child_order(preserve_child_order);
+        self
+    }
+
+    /// Returns the next queued node without consuming it.
+    ///
+    /// Before the first call to `next`, this is the root, which is only
+    /// expanded and not yielded unless [`include_root`] is set.
+    /// The node is not yielded if it is filtered, e.g. by [`min_depth`].
+    ///
+    /// [`include_root`]: Self::include_root
+    /// [`min_depth`]: Self::min_depth
+    #[inline]
+    #[must_use]
+    pub fn peek_next(&self) -> Option<&Result<N, N::Error>> {
+        self.queue.back()
+    }
+
+    /// Queues `node` at `depth` such that it is the next node.
+    ///
+    /// The node is pushed to the current frontier of the traversal, i.e. the
+    /// back of the queue, so the next call to `next` yields it, unless it is
+    /// filtered, e.g. by [`min_depth`], and expands it as usual.
+    /// The node bypasses the visited set, so a node that was already
+    /// yielded can be pushed back, e.g. to revisit it after backtracking.
+    /// Its children are still checked against the visited set.
+    /// A node at depth 0 is treated as a root, which is only expanded
+    /// unless [`include_root`] is set.
+    ///
+    /// [`min_depth`]: Self::min_depth
+    /// [`include_root`]: Self::include_root
+    #[inline]
+    pub fn push_back_node(&mut self, node: N, depth: usize) {
+        self.queue.inject(depth, node);
+    }
+
+    /// Only yields the leaves, i.e. the nodes without children.
+    ///
+    /// Every node is still expanded, but only yielded when it produced no
+    /// children, including children that were already visited.
+    /// Nodes at `max_depth` or pruned by [`prune`] are leaves, since they
+    /// are not expanded.
+    /// A node whose children failed is not a leaf, while the error is yielded.
+    ///
+    /// [`prune`]: Self::prune
+    #[must_use]
+    pub fn leaves(self) -> Leaves<Self> {
+        Leaves(self)
+    }
+
+    /// Discards the children of the last yielded node, so its subtree is
+    /// not traversed.
+    ///
+    /// The children of a node are queued when it is yielded, so this removes
+    /// them from the queue again, e.g. when a constraint of a search fails
+    /// for the node.
+    /// The discarded children are also removed from the visited set, so
+    /// they are found again through other nodes, or when the node is pushed
+    /// back with [`push_back_node`](Self::push_back_node).
+    ///
+    /// Does nothing when the last node was an error or was not expanded,
+    /// when its subtree was already skipped, or after [`push_back_node`](Self::push_back_node).
+    #[inline]
+    pub fn skip_subtree(&mut self) {
+        self.queue.skip_subtree();
+    }
+
+    /// Starts the traversal over from `root`.
+    ///
+    /// The queue, the visited set and the [`stats`](Self::stats) are cleared,
+    /// while all options are kept.
+    /// In contrast to creating a new iterator, the allocated capacity of the
+    /// queue and the visited set is reused, which avoids reallocating them
+    /// when running many traversals in a loop.
+    #[inline]
+    pub fn reset<R>(&mut self, root: R)
+    where
+        R: Into<N>,
+        V: Default,
+    {
+        self.queue.reset([root.into()]);
+        self.stats = Stats::default();
+    }
+
+    /// Returns the next node with its depth and whether it is the last of its siblings.
+    #[inline]
+    fn next_node(&mut self) -> Option<(usize, Result<N, N::Error>, bool)> {
+        self.next_node_until(|_| false)
+    }
+
+    /// Returns the next node like [`next_node`](Self::next_node), but does not
+    /// expand it when `found` returns `true` for it.
+    #[inline]
+    fn next_node_until<F>(&mut self, mut found: F) -> Option<(usize, Result<N, N::Error>, bool)>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        loop {
+            if self.queue.exhausted() {
+                return None;
+            }
+            match self.queue.pop_back()? {
+                // root node is only expanded, unless it is at the depth limit
+                (0, Ok(root)) if !self.queue.yields_roots() => {
+                    if self.max_depth != Some(0) {
+                        let len = self.queue.len();
+                        self.expand(&root, 1);
+                        self.queue.end_children(len, &root);
+                    }
+                }
+                // next node failed
+                (depth, Err(err)) => {
+                    let last = self.queue.pop_sibling_back();
+                    self.queue.count_node()?;
+                    self.stats.errors += 1;
+                    self.queue.set_subtree(None);
+                    return Some((depth, Err(err), last));
+                }
+                // next node succeeded
+                (depth, Ok(node)) => {
+                    // roots are not part of any group of siblings
+                    let last = depth > 0 && self.queue.pop_sibling_back();
+                    let yields = self.queue.yields(&node, depth);
+                    // the last node within the budget is not expanded
+                    let remaining = !yields || self.queue.count_node()?;
+                    let len = self.queue.len();
+                    if yields && found(&node) {
+                        self.stats.emitted += 1;
+                        self.queue.set_subtree(None);
+                        return Some((depth, Ok(node), last));
                     }
-                    Err(err) => self.queue.add(depth + 1, Err(err)),
-                };
-                Some(Ok(node))
+                    let mut produced = 0;
+                    if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                        self.queue.skip(&node, SkipReason::DepthLimit);
+                    } else if !remaining {
+                        self.queue.skip(&node, SkipReason::NodeLimit);
+                    } else if self.queue.prunes(&node, depth) {
+                        self.queue.skip(&node, SkipReason::Pruned);
+                    } else {
+                        produced = self.expand(&node, depth + 1);
+                        self.queue.end_children(len, &node);
+                    }
+                    if yields {
+                        self.stats.emitted += 1;
+                        // the children of the yielded node can still be skipped
+                        self.queue.set_subtree(Some(len));
+                        self.queue.set_produced(produced);
+                        return Some((depth, Ok(node), last));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<N, V, D> Iterator for Leaves<Dfs<N, V, D>>
+where
+    N: Node<D>,
+    V: VisitedSet<N>,
+    D: Depth,
+{
+    type Item = Result<N, N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.0.next_node()? {
+                (_, Err(err), _) => return Some(Err(err)),
+                // a yielded node is expanded before it is returned
+                (_, Ok(node), _) if self.0.queue.produced() == 0 => return Some(Ok(node)),
+                (_, Ok(_), _) => {}
+            }
+        }
+    }
+}
+
+impl<N, V, D> Iterator for Dfs<N, V, D>
+where
+    N: Node<D>,
+    V: VisitedSet<N>,
+    D: Depth,
+{
+    type Item = Result<N, N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_node().map(|(_, node, _)| node)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.queue.size_hint()
+    }
+
+    /// Counts the remaining nodes.
+    ///
+    /// Unless an option inspects individual nodes, the children at the
+    /// depth limit are only counted, as they are never expanded.
+    #[inline]
+    fn count(mut self) -> usize {
+        if !self.queue.is_plain() {
+            return self.fold(0, |count, _| count + 1);
+        }
+        let mut count = 0;
+        while let Some((depth, node)) = self.queue.pop_back() {
+            let Ok(node) = node else {
+                count += 1;
+                continue;
+            };
+            // the root is never yielded
+            if depth > 0 {
+                count += 1;
+            }
+            match self.max_depth {
+                Some(max_depth) if depth >= max_depth => {}
+                Some(max_depth) if depth + 1 == max_depth => {
+                    count += self.count_children(&node, depth + 1);
+                }
+                _ => {
+                    self.expand(&node, depth + 1);
+                }
             }
-            // no next node
-            None => None,
         }
+        count
     }
 }
 
@@ -134,8 +881,8 @@ where
 ///         let len = self.0.len();
 ///         if len > 1 {
 ///             let mid = len/2;
-///             queue.add(Ok(Self(self.0[mid..].into())));
 ///             queue.add(Ok(Self(self.0[..mid].into())));
+///             queue.add(Ok(Self(self.0[mid..].into())));
 ///         }
 ///         Ok(())
 ///     }
@@ -151,153 +898,867 @@ where
 /// ```
 ///
 /// [`FastNode`]: trait@crate::sync::FastNode
-pub struct FastDfs<N>
+pub struct FastDfs<N, V = HashSet<N>, D = usize>
 where
-    N: FastNode,
+    N: FastNode<D>,
 {
-    queue: queue::Queue<N, N::Error>,
+    queue: queue::Queue<N, N::Error, V, D>,
     max_depth: Option<usize>,
+    stats: Stats,
 }
 
-impl<N> FastDfs<N>
+impl<N, D> FastDfs<N, HashSet<N>, D>
 where
-    N: FastNode,
+    N: FastNode<D>,
+    D: Depth,
 {
     #[inline]
     /// Creates a new [`FastDfs`] iterator.
     ///
     /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    /// The bound is inclusive, unless a [`DepthBound`] says otherwise.
+    ///
+    /// Like [`Dfs`], the root is expanded at depth 1 but not yielded itself,
+    /// unless [`include_root`](Self::include_root) is set.
     ///
     /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
     ///
     /// [`FastDfs`]: struct@crate::sync::FastDfs
-    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    /// [`DepthBound`]: enum@crate::DepthBound
+    pub fn new<R, M>(root: R, max_depth: M, allow_circles: bool) -> Self
     where
         R: Into<N>,
-        D: Into<Option<usize>>,
+        M: Into<Option<usize>>,
     {
-        let mut queue = queue::Queue::new(allow_circles);
-        let root: N = root.into();
-        let max_depth = max_depth.into();
-        let mut depth_queue = queue::QueueWrapper::new(0, &mut queue);
-        depth_queue.add(Ok(root));
-        Self { queue, max_depth }
+        Self::with_roots([root], max_depth, allow_circles)
+    }
+
+    #[inline]
+    /// Creates a new [`FastDfs`] iterator from a `root` whose conversion can fail.
+    ///
+    /// Like [`new`](Self::new), but the `root` is converted with [`TryInto`],
+    /// e.g. for nodes that are parsed or read from the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the conversion if the `root` is not a valid node.
+    ///
+    /// [`FastDfs`]: struct@crate::sync::FastDfs
+    /// [`TryInto`]: trait@std::convert::TryInto
+    pub fn try_new<R, M>(root: R, max_depth: M, allow_circles: bool) -> Result<Self, R::Error>
+    where
+        R: TryInto<N>,
+        M: Into<Option<usize>>,
+    {
+        Ok(Self::new(root.try_into()?, max_depth, allow_circles))
+    }
+
+    #[inline]
+    /// Creates a new [`FastDfs`] iterator starting from multiple `roots`.
+    ///
+    /// The DFS will be performed from all `roots` up to depth `max_depth`,
+    /// sharing one set of visited nodes, so nodes reachable from more than
+    /// one root are only yielded once.
+    /// Like a single root, the roots themselves are not yielded,
+    /// unless [`include_root`](Self::include_root) is set.
+    /// Since the queue is processed from the back, the roots are expanded in
+    /// reverse order: the subtree of the last root is traversed first and
+    /// the subtree of the first root last.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`FastDfs`]: struct@crate::sync::FastDfs
+    pub fn with_roots<I, R, M>(roots: I, max_depth: M, allow_circles: bool) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        // the roots are expanded lazily but never yielded
+        let frontier = roots.into_iter().map(|root| (0, root.into()));
+        Self::from_frontier(frontier, max_depth, allow_circles)
+    }
+
+    #[inline]
+    /// Creates a new [`FastDfs`] iterator from an explicit `frontier` of
+    /// nodes with their depths.
+    ///
+    /// The frontier is queued as-is, as if it was discovered by an earlier
+    /// traversal, e.g. to resume a traversal partially or to hand each worker
+    /// a slice of the frontier of a larger traversal.
+    /// Nodes at depth 0 are roots, which are only expanded, while deeper nodes
+    /// are yielded and expanded up to depth `max_depth`.
+    /// Since the queue is processed from the back, the last node of the
+    /// frontier is yielded first.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    /// Otherwise, the nodes of the frontier are visited and yielded only once.
+    ///
+    /// [`FastDfs`]: struct@crate::sync::FastDfs
+    pub fn from_frontier<I, M>(frontier: I, max_depth: M, allow_circles: bool) -> Self
+    where
+        I: IntoIterator<Item = (usize, N)>,
+        M: Into<Option<usize>>,
+    {
+        let mut queue = queue::Queue::new(allow_circles);
+        let max_depth = max_depth.into();
+        for (depth, node) in frontier {
+            queue.add(depth, Ok(node));
+        }
+        Self {
+            queue,
+            max_depth,
+            stats: Stats::default(),
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+    #[inline]
+    /// Creates a new [`FastDfs`] iterator that expands children in a shuffled order.
+    ///
+    /// The children of each node are shuffled by a PRNG seeded with `seed`,
+    /// so the same seed always produces the same traversal.
+    /// Parallel splits draw their own seeds and are not reproducible.
+    ///
+    /// [`FastDfs`]: struct@crate::sync::FastDfs
+    pub fn shuffled<R, M>(root: R, max_depth: M, allow_circles: bool, seed: u64) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        let mut iter = Self::new(root, max_depth, allow_circles);
+        iter.queue.set_shuffle(seed);
+        iter
     }
 }
 
-impl<N> Iterator for FastDfs<N>
+impl<N, S, D> FastDfs<N, HashSet<N, S>, D>
 where
-    N: FastNode,
+    N: FastNode<D>,
+    S: BuildHasher,
+    D: Depth,
 {
-    type Item = Result<N, N::Error>;
+    #[inline]
+    /// Creates a new [`FastDfs`] iterator that hashes visited nodes using `hash_builder`.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// The default hasher is resistant to collision attacks, but comparatively slow
+    /// for nodes that are cheap to hash, e.g. integers, where hashing can dominate
+    /// the traversal.
+    ///
+    /// [`FastDfs`]: struct@crate::sync::FastDfs
+    pub fn with_hasher<R, M>(root: R, max_depth: M, hash_builder: S) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        Self::with_visited(root, max_depth, HashSet::with_hasher(hash_builder))
+    }
+}
 
+impl<N, V, D> FastDfs<N, V, D>
+where
+    N: FastNode<D>,
+    V: VisitedSet<N>,
+    D: Depth,
+{
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.queue.pop_back() {
-            // next node failed
-            Some((_, Err(err))) => Some(Err(err)),
-            // next node succeeded
-            Some((depth, Ok(node))) => {
-                if let Some(max_depth) = self.max_depth {
-                    if depth >= max_depth {
-                        return Some(Ok(node));
+    /// Creates a new [`FastDfs`] iterator that tracks visited nodes in `visited`.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    /// Nodes that are already contained in `visited` are not yielded.
+    ///
+    /// [`FastDfs`]: struct@crate::sync::FastDfs
+    pub fn with_visited<R, M>(root: R, max_depth: M, visited: V) -> Self
+    where
+        R: Into<N>,
+        M: Into<Option<usize>>,
+    {
+        let mut queue = queue::Queue::with_visited(false, visited);
+        let max_depth = max_depth.into();
+        // the root is expanded lazily but never yielded
+        queue.add(0, Ok(root.into()));
+        Self {
+            queue,
+            max_depth,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Returns the first node for which `f` returns `true`.
+    ///
+    /// In contrast to [`Iterator::find`], the matching node is not expanded,
+    /// so no more children are requested once a match is found.
+    /// Returns the first error encountered before a match, or [`None`] when
+    /// the traversal completes without a match.
+    ///
+    /// [`Iterator::find`]: fn@std::iter::Iterator::find
+    /// [`None`]: type@std::option::Option::None
+    pub fn find_map_node<F>(mut self, mut f: F) -> Option<Result<N, N::Error>>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        let mut found = false;
+        while let Some((_, node, _)) = self.next_node_until(|node| {
+            found = f(node);
+            found
+        }) {
+            match node {
+                Ok(node) if found => return Some(Ok(node)),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+
+    /// Adds the children of `node` at `depth` to the queue.
+    #[inline]
+    fn expand(&mut self, node: &N, depth: usize) {
+        let span = crate::trace::ExpandSpan::enter(depth);
+        let len = self.queue.len();
+        let max_branching = self.queue.max_branching();
+        let on_panic = self.queue.on_panic();
+        let mut depth_queue = queue::QueueWrapper::new(depth, &mut self.queue).limit(max_branching);
+        let expanded = super::panic::catch(on_panic, depth, || {
+            node.add_children(queue::depth(depth), &mut depth_queue)
+        });
+        // the queue is processed from the back, so the children are reversed to pop them in order
+        self.queue.reverse_from(len);
+        if let Err(err) = expanded {
+            // the error is added regardless of the branching limit
+            self.queue.add(depth, Err(err));
+        }
+        // the children are added to the queue directly and shuffled in place
+        #[cfg(feature = "rand")]
+        self.queue.shuffle_from(len);
+        span.children(self.queue.len() - len);
+        self.stats.expanded(self.queue.len());
+    }
+
+    /// Expands `node` at `depth` and returns the number of its children
+    /// that would be queued, without queueing them.
+    #[inline]
+    fn count_children(&mut self, node: &N, depth: usize) -> usize {
+        let len = self.queue.len();
+        self.expand(node, depth);
+        let added = self.queue.len() - len;
+        self.queue.truncate(len);
+        added
+    }
+
+    /// Sets whether the children of a node are yielded in the order they are added.
+    ///
+    /// See [`Dfs::preserve_child_order`] for details.
+    ///
+    /// [`Dfs::preserve_child_order`]: fn@crate::sync::Dfs::preserve_child_order
+    #[inline]
+    #[must_use]
+    pub fn preserve_child_order(mut self, preserve_child_order: bool) -> Self {
+        self.queue.set_preserve_child_order(preserve_child_order);
+        self
+    }
+
+    /// Returns the next queued node without consuming it.
+    ///
+    /// Before the first call to `next`, this is the root, which is only
+    /// expanded and not yielded unless [`include_root`] is set.
+    /// The node is not yielded if it is filtered, e.g. by [`min_depth`].
+    ///
+    /// [`include_root`]: Self::include_root
+    /// [`min_depth`]: Self::min_depth
+    #[inline]
+    #[must_use]
+    pub fn peek_next(&self) -> Option<&Result<N, N::Error>> {
+        self.queue.back()
+    }
+
+    /// Returns the next node with its depth and whether it is the last of its siblings.
+    #[inline]
+    fn next_node(&mut self) -> Option<(usize, Result<N, N::Error>, bool)> {
+        self.next_node_until(|_| false)
+    }
+
+    /// Returns the next node like [`next_node`](Self::next_node), but does not
+    /// expand it when `found` returns `true` for it.
+    #[inline]
+    fn next_node_until<F>(&mut self, mut found: F) -> Option<(usize, Result<N, N::Error>, bool)>
+    where
+        F: FnMut(&N) -> bool,
+    {
+        loop {
+            if self.queue.exhausted() {
+                return None;
+            }
+            match self.queue.pop_back()? {
+                // root node is only expanded, unless it is at the depth limit
+                (0, Ok(root)) if !self.queue.yields_roots() => {
+                    if self.max_depth != Some(0) {
+                        let len = self.queue.len();
+                        self.expand(&root, 1);
+                        self.queue.end_children(len, &root);
                     }
                 }
-                let next_depth = depth + 1;
-                let mut depth_queue = queue::QueueWrapper::new(next_depth, &mut self.queue);
-                if let Err(err) = node.add_children(next_depth, &mut depth_queue) {
-                    depth_queue.add(Err(err));
+                // next node failed
+                (depth, Err(err)) => {
+                    let last = self.queue.pop_sibling_back();
+                    self.queue.count_node()?;
+                    self.stats.errors += 1;
+                    return Some((depth, Err(err), last));
+                }
+                // next node succeeded
+                (depth, Ok(node)) => {
+                    // roots are not part of any group of siblings
+                    let last = depth > 0 && self.queue.pop_sibling_back();
+                    let yields = self.queue.yields(&node, depth);
+                    // the last node within the budget is not expanded
+                    let remaining = !yields || self.queue.count_node()?;
+                    if yields && found(&node) {
+                        self.stats.emitted += 1;
+                        return Some((depth, Ok(node), last));
+                    }
+                    if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                        self.queue.skip(&node, SkipReason::DepthLimit);
+                    } else if !remaining {
+                        self.queue.skip(&node, SkipReason::NodeLimit);
+                    } else if self.queue.prunes(&node, depth) {
+                        self.queue.skip(&node, SkipReason::Pruned);
+                    } else {
+                        let len = self.queue.len();
+                        self.expand(&node, depth + 1);
+                        self.queue.end_children(len, &node);
+                    }
+                    if yields {
+                        self.stats.emitted += 1;
+                        return Some((depth, Ok(node), last));
+                    }
                 }
-                Some(Ok(node))
             }
-            // no next node
-            None => None,
         }
     }
 }
 
-#[cfg(feature = "rayon")]
-#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
-mod par {
-    use crate::sync::par::parallel_iterator;
-    use crate::sync::{Dfs, FastDfs, FastNode, Node};
-
-    parallel_iterator!(Dfs<Node>);
-    parallel_iterator!(FastDfs<FastNode>);
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{Dfs, FastDfs};
-    use anyhow::Result;
+impl<N, V, D> Iterator for FastDfs<N, V, D>
+where
+    N: FastNode<D>,
+    V: VisitedSet<N>,
+    D: Depth,
+{
+    type Item = Result<N, N::Error>;
 
-    #[cfg(feature = "rayon")]
-    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_node().map(|(_, node, _)| node)
+    }
 
-    macro_rules! depths {
-        ($iter:ident) => {{
-            $iter
-                // fail on first error
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                // get depth
-                .map(|item| item.0)
-                .collect::<Vec<_>>()
-        }};
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.queue.size_hint()
     }
 
-    macro_rules! test_depths_serial {
-        ($name:ident: $values:expr) => {
-            paste::item! {
-                #[test]
-                fn [< test_ $name _ serial >] () -> Result<()> {
-                    let (iter, expected_depths) = $values;
-                    let depths = depths!(iter);
-                    similar_asserts::assert_eq!(depths, expected_depths);
-                    Ok(())
+    /// Counts the remaining nodes.
+    ///
+    /// Unless an option inspects individual nodes, the children at the
+    /// depth limit are only counted, as they are never expanded.
+    #[inline]
+    fn count(mut self) -> usize {
+        if !self.queue.is_plain() {
+            return self.fold(0, |count, _| count + 1);
+        }
+        let mut count = 0;
+        while let Some((depth, node)) = self.queue.pop_back() {
+            let Ok(node) = node else {
+                count += 1;
+                continue;
+            };
+            // the root is never yielded
+            if depth > 0 {
+                count += 1;
+            }
+            match self.max_depth {
+                Some(max_depth) if depth >= max_depth => {}
+                Some(max_depth) if depth + 1 == max_depth => {
+                    count += self.count_children(&node, depth + 1);
                 }
+                _ => self.expand(&node, depth + 1),
             }
-        };
+        }
+        count
     }
+}
 
-    macro_rules! test_depths_parallel {
-        ($name:ident: $values:expr) => {
-            paste::item! {
-                #[cfg(feature = "rayon")]
-                #[test]
-                fn [< test_ $name _ parallel >] () -> Result<()> {
-                    let (iter, expected_depths) = $values;
-                    let iter = iter.into_par_iter();
-                    let depths = depths!(iter);
-                    crate::utils::test::assert_eq_sorted!(depths, expected_depths);
-                    Ok(())
+/// Synchronous post-order depth-first iterator for types implementing the [`Node`] trait.
+///
+/// In contrast to [`Dfs`], a node is only yielded after all of its descendants
+/// have been yielded, e.g. to resolve dependencies before their dependents.
+/// The same nodes are yielded as by [`Dfs`] with the same arguments, and the
+/// root is not yielded either.
+/// Like a [`Dfs`], the subtrees of the children of every node are traversed
+/// in the order the children are produced.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::{Node, PostOrderDfs, NodeIter};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct WordNode(String);
+///
+/// impl Node for WordNode {
+///     type Error = std::convert::Infallible;
+///
+///     fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+///         let len = self.0.len();
+///         let nodes: Vec<String> = if len < 2 {
+///             vec![]
+///         } else {
+///             let mid = len/2;
+///             vec![self.0[..mid].into(), self.0[mid..].into()]
+///         };
+///         let nodes = nodes.into_iter()
+///             .map(Self)
+///             .map(Result::Ok);
+///         Ok(Box::new(nodes))
+///     }
+/// }
+///
+/// let root = WordNode("Hello World".into());
+/// let dfs = PostOrderDfs::<WordNode>::new(root, None, true);
+/// let output = dfs.collect::<Result<Vec<_>, _>>().unwrap();
+/// // the children of the root come last
+/// assert_eq!(output.last().unwrap().0, " World");
+/// let result = output.into_iter()
+///     .filter_map(|s| if s.0.len() == 1 { Some(s.0) } else { None })
+///     .collect::<String>();
+/// assert_eq!(result, "Hello World");
+/// ```
+///
+/// [`Dfs`]: struct@crate::sync::Dfs
+/// [`Node`]: trait@crate::sync::Node
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+pub struct PostOrderDfs<N>
+where
+    N: Node,
+{
+    /// Nodes with their depth and whether their children were already pushed.
+    stack: Vec<(usize, Result<N, N::Error>, bool)>,
+    visited: HashSet<N>,
+    max_depth: Option<usize>,
+    allow_circles: bool,
+}
+
+impl<N> PostOrderDfs<N>
+where
+    N: Node,
+{
+    #[inline]
+    /// Creates a new [`PostOrderDfs`] iterator.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`PostOrderDfs`]: struct@crate::sync::PostOrderDfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let root = root.into();
+        let mut visited = HashSet::new();
+        if !allow_circles {
+            visited.insert(root.clone());
+        }
+        Self {
+            stack: vec![(0, Ok(root), false)],
+            visited,
+            max_depth: max_depth.into(),
+            allow_circles,
+        }
+    }
+
+    /// Pushes the node back followed by its children at `depth`.
+    #[inline]
+    fn expand(&mut self, node: N, depth: usize) {
+        let children = node.children(depth);
+        self.stack.push((depth - 1, Ok(node), true));
+        let len = self.stack.len();
+        match children {
+            Ok(children) => {
+                for child in children {
+                    match child {
+                        Ok(child) if !self.allow_circles => {
+                            if self.visited.insert(child.clone()) {
+                                self.stack.push((depth, Ok(child), false));
+                            }
+                        }
+                        child => self.stack.push((depth, child, false)),
+                    }
                 }
+                // the stack is processed from the back, so the children are reversed to pop them in order
+                self.stack[len..].reverse();
             }
-        };
+            Err(err) => self.stack.push((depth, Err(err), false)),
+        }
     }
+}
 
-    macro_rules! test_depths {
-        ($name:ident: $values:expr, $($macro:ident,)*) => {
-            $(
-                $macro!($name: $values);
-            )*
+impl<N> Iterator for PostOrderDfs<N>
+where
+    N: Node,
+{
+    type Item = Result<N, N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop()? {
+                // next node failed
+                (_, Err(err), _) => return Some(Err(err)),
+                // root node is never yielded
+                (0, Ok(_), true) => {}
+                // all descendants of the node have been yielded
+                (_, Ok(node), true) => return Some(Ok(node)),
+                // node at the depth limit is not expanded
+                (depth, Ok(node), false)
+                    if self.max_depth.is_some_and(|max_depth| depth >= max_depth) =>
+                {
+                    if depth > 0 {
+                        return Some(Ok(node));
+                    }
+                }
+                (depth, Ok(node), false) => self.expand(node, depth + 1),
+            }
         }
     }
+}
 
-    test_depths!(
-        dfs:
-        (
-            Dfs::<crate::utils::test::Node>::new(0, 3, true),
-            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
-        ),
-        test_depths_serial,
-        test_depths_parallel,
-    );
+/// Synchronous depth-first iterator for types implementing the [`OwnedNode`] trait.
+///
+/// Every node is moved into [`into_children`] to expand it, so nodes are
+/// never cloned, and the [`Item`] it returns is yielded in its place,
+/// in the same order as a [`Dfs`].
+/// Nodes at the depth limit are not expanded and yield [`into_item`].
+/// Visited nodes are not tracked, hence `max_depth` should be set for
+/// graphs with cycles.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::{NodeIter, OwnedDfs, OwnedNode};
+///
+/// /// A chunk of a buffer, whose halves are split off without copying.
+/// struct Chunk(Vec<u8>);
+///
+/// impl OwnedNode for Chunk {
+///     type Item = usize;
+///     type Error = std::convert::Infallible;
+///
+///     fn into_children(mut self, _depth: usize) -> (usize, NodeIter<Self, Self::Error>) {
+///         let len = self.0.len();
+///         if len < 2 {
+///             return (len, Ok(Box::new(std::iter::empty())));
+///         }
+///         let right = Chunk(self.0.split_off(len / 2));
+///         (len, Ok(Box::new([Ok(self), Ok(right)].into_iter())))
+///     }
+///
+///     fn into_item(self) -> usize {
+///         self.0.len()
+///     }
+/// }
+///
+/// let dfs = OwnedDfs::<Chunk>::new(Chunk(vec![0; 4]), None);
+/// let lens = dfs.collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(lens, [2, 1, 1, 2, 1, 1]);
+/// ```
+///
+/// [`OwnedNode`]: trait@crate::sync::OwnedNode
+/// [`into_children`]: fn@crate::sync::OwnedNode::into_children
+/// [`into_item`]: fn@crate::sync::OwnedNode::into_item
+/// [`Item`]: type@crate::sync::OwnedNode::Item
+/// [`Dfs`]: struct@crate::sync::Dfs
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct OwnedDfs<N>
+where
+    N: OwnedNode,
+{
+    stack: Vec<(usize, Result<N, N::Error>)>,
+    max_depth: Option<usize>,
+}
+
+impl<N> OwnedDfs<N>
+where
+    N: OwnedNode,
+{
+    #[inline]
+    /// Creates a new [`OwnedDfs`] iterator.
+    ///
+    /// The DFS will be performed from the `root` node up to depth `max_depth`.
+    /// The root is expanded but its item is not yielded.
+    ///
+    /// [`OwnedDfs`]: struct@crate::sync::OwnedDfs
+    pub fn new<R, D>(root: R, max_depth: D) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        Self {
+            stack: vec![(0, Ok(root.into()))],
+            max_depth: max_depth.into(),
+        }
+    }
+}
+
+impl<N> Iterator for OwnedDfs<N>
+where
+    N: OwnedNode,
+{
+    type Item = Result<N::Item, N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, node) = self.stack.pop()?;
+            let node = match node {
+                Ok(node) => node,
+                Err(err) => return Some(Err(err)),
+            };
+            let item = if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                node.into_item()
+            } else {
+                let (item, children) = node.into_children(depth + 1);
+                let len = self.stack.len();
+                match children {
+                    Ok(children) => {
+                        self.stack.extend(children.map(|child| (depth + 1, child)));
+                        // the stack is processed from the back, so the children are reversed to pop them in order
+                        self.stack[len..].reverse();
+                    }
+                    Err(err) => self.stack.push((depth + 1, Err(err))),
+                }
+                item
+            };
+            // the root is never yielded
+            if depth > 0 {
+                return Some(Ok(item));
+            }
+        }
+    }
+}
+
+/// Synchronous iterative deepening depth-first iterator for types implementing the [`Node`] trait.
+///
+/// Runs a depth-limited [`Dfs`] with the limits 1, 2, 3, … and yields the
+/// nodes at the limit of each run in depth-first order.
+/// Hence, nodes are yielded level by level like [`Bfs`], while only the memory
+/// of a [`Dfs`] is required.
+///
+/// The deepening stops when a run does not reach its limit, i.e. there are
+/// no deeper nodes, or after the run with limit `max_depth`.
+/// When `allow_circles` is set, cyclic graphs are never exhausted and
+/// `max_depth` should be set.
+/// Without `allow_circles`, nodes that were already yielded in an earlier
+/// run are not yielded again, which requires remembering all yielded nodes.
+///
+/// Every run expands all nodes above its limit again, so the nodes at depth `d`
+/// are expanded once for every deeper limit.
+/// For graphs with a branching factor of at least two, the total cost is
+/// dominated by the last run.
+///
+/// [`Bfs`]: struct@crate::sync::Bfs
+/// [`Dfs`]: struct@crate::sync::Dfs
+/// [`Node`]: trait@crate::sync::Node
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct IterativeDeepeningDfs<N>
+where
+    N: Node,
+{
+    root: N,
+    current: Dfs<N>,
+    depth: usize,
+    reached_depth: bool,
+    max_depth: Option<usize>,
+    allow_circles: bool,
+    yielded: HashSet<N>,
+}
+
+impl<N> IterativeDeepeningDfs<N>
+where
+    N: Node,
+{
+    #[inline]
+    /// Creates a new [`IterativeDeepeningDfs`] iterator.
+    ///
+    /// The deepening starts from the `root` node and stops at depth `max_depth`
+    /// or when there are no deeper nodes.
+    ///
+    /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+    ///
+    /// [`IterativeDeepeningDfs`]: struct@crate::sync::IterativeDeepeningDfs
+    pub fn new<R, D>(root: R, max_depth: D, allow_circles: bool) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let root = root.into();
+        let max_depth = max_depth.into();
+        let depth = 1;
+        Self {
+            current: Self::run(&root, depth, allow_circles),
+            root,
+            depth,
+            reached_depth: false,
+            max_depth,
+            allow_circles,
+            yielded: HashSet::new(),
+        }
+    }
+
+    /// Returns the run that only yields the nodes at `depth`.
+    #[inline]
+    fn run(root: &N, depth: usize, allow_circles: bool) -> Dfs<N> {
+        Dfs::new(root.clone(), depth, allow_circles).min_depth(depth)
+    }
+}
+
+impl<N> Iterator for IterativeDeepeningDfs<N>
+where
+    N: Node,
+{
+    type Item = Result<N, N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.current.next_node() {
+                // errors above the limit were yielded by earlier runs
+                Some((depth, Err(err), _)) => {
+                    if depth == self.depth {
+                        return Some(Err(err));
+                    }
+                }
+                Some((_, Ok(node), _)) => {
+                    self.reached_depth = true;
+                    if self.allow_circles || self.yielded.insert(node.clone()) {
+                        return Some(Ok(node));
+                    }
+                }
+                None => {
+                    let at_max_depth = self
+                        .max_depth
+                        .is_some_and(|max_depth| self.depth >= max_depth);
+                    if !self.reached_depth || at_max_depth {
+                        return None;
+                    }
+                    self.depth += 1;
+                    self.reached_depth = false;
+                    self.current = Self::run(&self.root, self.depth, self.allow_circles);
+                }
+            }
+        }
+    }
+}
+
+traversal_options!(Dfs<Node>);
+traversal_options!(FastDfs<FastNode>);
+
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+mod par {
+    use crate::sync::par::parallel_iterator;
+    use crate::sync::{Dfs, FastDfs, FastNode, Node};
+
+    parallel_iterator!(Dfs<Node>, depth_first);
+    parallel_iterator!(FastDfs<FastNode>, depth_first);
+}
+
+#[cfg(feature = "crossbeam")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crossbeam")))]
+mod channel {
+    use crate::sync::channel::channel_iterator;
+    use crate::sync::{Dfs, FastDfs, FastNode, Node};
+
+    channel_iterator!(Dfs<Node>);
+    channel_iterator!(FastDfs<FastNode>);
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+mod checkpoint {
+    use crate::sync::checkpoint::checkpoint_iterator;
+    use crate::sync::{Dfs, FastDfs, FastNode, Node};
+
+    checkpoint_iterator!(Dfs<Node>);
+    checkpoint_iterator!(FastDfs<FastNode>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dfs, FastDfs, IterativeDeepeningDfs, PostOrderDfs};
+    use anyhow::Result;
+
+    #[cfg(feature = "rayon")]
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    macro_rules! depths {
+        ($iter:ident) => {{
+            $iter
+                // fail on first error
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                // get depth
+                .map(|item| item.0)
+                .collect::<Vec<_>>()
+        }};
+    }
+
+    macro_rules! test_depths_serial {
+        ($name:ident: $values:expr) => {
+            paste::item! {
+                #[test]
+                fn [< test_ $name _ serial >] () -> Result<()> {
+                    let (iter, expected_depths) = $values;
+                    let depths = depths!(iter);
+                    similar_asserts::assert_eq!(depths, expected_depths);
+                    Ok(())
+                }
+            }
+        };
+    }
+
+    macro_rules! test_depths_parallel {
+        ($name:ident: $values:expr) => {
+            paste::item! {
+                #[cfg(feature = "rayon")]
+                #[test]
+                fn [< test_ $name _ parallel >] () -> Result<()> {
+                    let (iter, expected_depths) = $values;
+                    let iter = iter.into_par_iter();
+                    let depths = depths!(iter);
+                    crate::utils::test::assert_eq_sorted!(depths, expected_depths);
+                    Ok(())
+                }
+            }
+        };
+    }
+
+    macro_rules! test_depths {
+        ($name:ident: $values:expr, $($macro:ident,)*) => {
+            $(
+                $macro!($name: $values);
+            )*
+        }
+    }
 
     test_depths!(
-        fast_dfs:
+        dfs_leaves:
         (
-            FastDfs::<crate::utils::test::Node>::new(0, 3, true),
+            Dfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true),
             [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
         ),
         test_depths_serial,
@@ -305,21 +1766,1642 @@ mod tests {
     );
 
     test_depths!(
-        fast_dfs_no_circles:
+        dfs_max_depth_zero:
         (
-            FastDfs::<crate::utils::test::Node>::new(0, 3, false),
+            Dfs::<crate::utils::test::LeafNode>::new(0, 0, true),
+            [] as [usize; 0]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_dfs_leaves:
+        (
+            FastDfs::<crate::utils::test::LeafNode>::new(0, crate::utils::test::LEAF_DEPTH, true),
+            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_dfs_max_depth_zero:
+        (
+            FastDfs::<crate::utils::test::LeafNode>::new(0, 0, true),
+            [] as [usize; 0]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        dfs_exclusive_depth_bound:
+        (
+            Dfs::<crate::utils::test::LeafNode>::new(0, crate::DepthBound::Exclusive(3), true),
+            [1, 2, 2, 1, 2, 2]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        dfs_max_branching:
+        (
+            Dfs::<crate::utils::test::LeafNode>::new(0, 3, true).max_branching(1),
             [1, 2, 3]
         ),
         test_depths_serial,
+        test_depths_parallel,
     );
 
     test_depths!(
-        dfs_no_circles:
+        fast_dfs_max_branching:
         (
-            Dfs::<crate::utils::test::Node>::new(0, 3, false),
+            FastDfs::<crate::utils::test::LeafNode>::new(0, 3, true).max_branching(1),
             [1, 2, 3]
         ),
         test_depths_serial,
         test_depths_parallel,
     );
+
+    test_depths!(
+        dfs_include_root:
+        (
+            Dfs::<crate::utils::test::LeafNode>::new(0, 2, true).include_root(),
+            [0, 1, 2, 2, 1, 2, 2]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        dfs_include_root_max_depth_zero:
+        (
+            Dfs::<crate::utils::test::LeafNode>::new(0, 0, true).include_root(),
+            [0]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_dfs_include_root:
+        (
+            FastDfs::<crate::utils::test::LeafNode>::new(0, 2, true).include_root(),
+            [0, 1, 2, 2, 1, 2, 2]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        dfs_yield_if:
+        (
+            Dfs::<crate::utils::test::Node>::new(0, 3, true).yield_if(|_, depth| depth == 3),
+            [3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        dfs_min_depth:
+        (
+            Dfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2),
+            [2, 3, 3, 2, 3, 3, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_dfs_min_depth:
+        (
+            FastDfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2),
+            [2, 3, 3, 2, 3, 3, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        post_order_dfs:
+        (
+            PostOrderDfs::<crate::utils::test::Node>::new(0, 3, true),
+            [3, 3, 2, 3, 3, 2, 1, 3, 3, 2, 3, 3, 2, 1]
+        ),
+        test_depths_serial,
+    );
+
+    test_depths!(
+        post_order_dfs_no_circles:
+        (
+            PostOrderDfs::<crate::utils::test::Node>::new(0, 3, false),
+            [3, 2, 1]
+        ),
+        test_depths_serial,
+    );
+
+    test_depths!(
+        iterative_deepening_dfs:
+        (
+            IterativeDeepeningDfs::<crate::utils::test::Node>::new(0, 3, true),
+            [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 3, 3]
+        ),
+        test_depths_serial,
+    );
+
+    test_depths!(
+        iterative_deepening_dfs_no_circles:
+        (
+            IterativeDeepeningDfs::<crate::utils::test::Node>::new(0, 3, false),
+            [1, 2, 3]
+        ),
+        test_depths_serial,
+    );
+
+    test_depths!(
+        dfs:
+        (
+            Dfs::<crate::utils::test::Node>::new(0, 3, true),
+            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_dfs:
+        (
+            FastDfs::<crate::utils::test::Node>::new(0, 3, true),
+            [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    test_depths!(
+        fast_dfs_no_circles:
+        (
+            FastDfs::<crate::utils::test::Node>::new(0, 3, false),
+            [1, 2, 3]
+        ),
+        test_depths_serial,
+    );
+
+    test_depths!(
+        dfs_no_circles:
+        (
+            Dfs::<crate::utils::test::Node>::new(0, 3, false),
+            [1, 2, 3]
+        ),
+        test_depths_serial,
+        test_depths_parallel,
+    );
+
+    #[test]
+    fn test_dfs_explain_skip() -> Result<()> {
+        use crate::sync::SkipReason;
+        use std::sync::{Arc, Mutex};
+
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let on_skip = {
+            let skipped = Arc::clone(&skipped);
+            move |node: &crate::utils::test::Node, reason| {
+                skipped.lock().unwrap().push((node.0, reason));
+            }
+        };
+        let iter = Dfs::<crate::utils::test::Node>::new(0, 3, false).explain_skip(on_skip);
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 2, 3]);
+        similar_asserts::assert_eq!(
+            *skipped.lock().unwrap(),
+            [
+                (1, SkipReason::Cycle),
+                (2, SkipReason::Cycle),
+                (3, SkipReason::Cycle),
+                (3, SkipReason::DepthLimit),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_explain_skip_root() -> Result<()> {
+        use crate::sync::SkipReason;
+        use crate::utils::test::GraphNode;
+        use std::sync::{Arc, Mutex};
+
+        // 0 → 1 → 0
+        let edges: &[&[usize]] = &[&[1], &[0]];
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let on_skip = {
+            let skipped = Arc::clone(&skipped);
+            move |node: &GraphNode, reason| skipped.lock().unwrap().push((node.id, reason))
+        };
+        let ids = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false)
+            .explain_skip(on_skip)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        // the root is visited, so it is not yielded again as a child
+        similar_asserts::assert_eq!(ids, [1]);
+        similar_asserts::assert_eq!(*skipped.lock().unwrap(), [(0, SkipReason::Cycle)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_explain_skip_after_panic() {
+        use crate::sync::SkipReason;
+        use crate::utils::test::GraphNode;
+        use std::sync::{Arc, Mutex};
+
+        // 0 → 1 → 0, and 0 → 2 → 0
+        let edges: &[&[usize]] = &[&[1, 2], &[0], &[0]];
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let on_skip = {
+            let skipped = Arc::clone(&skipped);
+            move |node: &GraphNode, reason| {
+                let mut skipped = skipped.lock().unwrap();
+                skipped.push((node.id, reason));
+                let first = skipped.len() == 1;
+                drop(skipped);
+                assert!(!first, "first skip");
+            }
+        };
+        let mut dfs =
+            Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false).explain_skip(on_skip);
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| dfs.next()));
+        assert!(panicked.is_err());
+        // a callback that panicked before is still called
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| dfs.by_ref().count()));
+        similar_asserts::assert_eq!(
+            *skipped.lock().unwrap(),
+            [(0, SkipReason::Cycle), (0, SkipReason::Cycle)]
+        );
+    }
+
+    #[test]
+    fn test_dfs_exact_len() {
+        use crate::utils::test::TreeNode;
+
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 3, false);
+        // 2 + 4 + 8 nodes below the root
+        similar_asserts::assert_eq!(dfs.exact_len(), Some(14));
+        let mut remaining = 14;
+        while dfs.next().is_some() {
+            remaining -= 1;
+            similar_asserts::assert_eq!(dfs.exact_len(), Some(remaining));
+        }
+        similar_asserts::assert_eq!(remaining, 0);
+
+        let fast = FastDfs::<TreeNode>::new(TreeNode(0), 3, false);
+        similar_asserts::assert_eq!(fast.exact_len(), Some(14));
+        similar_asserts::assert_eq!(fast.count(), 14);
+
+        similar_asserts::assert_eq!(
+            Dfs::<TreeNode>::new(TreeNode(0), 0, false).exact_len(),
+            Some(0)
+        );
+        similar_asserts::assert_eq!(
+            Dfs::<TreeNode>::new(TreeNode(0), None, false).exact_len(),
+            None
+        );
+        let dfs = Dfs::<TreeNode>::new(TreeNode(0), 3, false).min_depth(2);
+        similar_asserts::assert_eq!(dfs.exact_len(), None);
+        let dfs = Dfs::<TreeNode>::new(TreeNode(0), usize::MAX, false);
+        similar_asserts::assert_eq!(dfs.exact_len(), None);
+    }
+
+    #[test]
+    fn test_dfs_set_max_depth() {
+        use crate::utils::test::TreeNode;
+
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 1, false);
+        similar_asserts::assert_eq!(dfs.next().map(|node| node.unwrap().0), Some(1));
+        // the yielded node at the old limit is not expanded retroactively
+        dfs.set_max_depth(2);
+        let ids: Vec<usize> = dfs.map(|node| node.unwrap().0).collect();
+        similar_asserts::assert_eq!(ids, [2, 5, 6]);
+
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), None, false);
+        similar_asserts::assert_eq!(dfs.next().map(|node| node.unwrap().0), Some(1));
+        dfs.set_max_depth(1);
+        let ids: Vec<usize> = dfs.map(|node| node.unwrap().0).collect();
+        similar_asserts::assert_eq!(ids, [3, 4, 2]);
+    }
+
+    #[test]
+    fn test_dfs_try_new() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        /// A root given as the decimal id of a node.
+        struct Id(&'static str);
+
+        impl TryFrom<Id> for TreeNode {
+            type Error = std::num::ParseIntError;
+
+            fn try_from(id: Id) -> Result<Self, Self::Error> {
+                id.0.parse().map(TreeNode)
+            }
+        }
+
+        let ids: Vec<usize> = Dfs::<TreeNode>::try_new(Id("2"), 1, false)?
+            .map(|node| node.unwrap().0)
+            .collect();
+        similar_asserts::assert_eq!(ids, [5, 6]);
+        assert!(FastDfs::<TreeNode>::try_new(Id("two"), 1, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_drain_matching() {
+        use crate::utils::test::TreeNode;
+
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 3, false);
+        similar_asserts::assert_eq!(dfs.next().map(|node| node.unwrap().0), Some(1));
+        let drained = dfs.drain_matching(|node| node.0 == 2 || node.0 == 4);
+        similar_asserts::assert_eq!(drained, [TreeNode(2), TreeNode(4)]);
+        similar_asserts::assert_eq!(dfs.remaining(), 1);
+        let ids: Vec<usize> = dfs.map(|node| node.unwrap().0).collect();
+        similar_asserts::assert_eq!(ids, [3, 7, 8]);
+    }
+
+    #[test]
+    fn test_dfs_catch_panics() {
+        use crate::sync::Bfs;
+        use crate::utils::test::{Error, LeafNode, LEAF_DEPTH};
+
+        // expanding a leaf panics, which ends the traversal with an error
+        let expected: Vec<_> = (1..=LEAF_DEPTH)
+            .map(|depth| Ok(LeafNode(depth)))
+            .chain([Err(Error)])
+            .collect();
+        let nodes: Vec<_> = Dfs::<LeafNode>::new(0, None, false)
+            .catch_panics()
+            .collect();
+        similar_asserts::assert_eq!(nodes, expected);
+        let nodes: Vec<_> = FastDfs::<LeafNode>::new(0, None, false)
+            .catch_panics()
+            .collect();
+        similar_asserts::assert_eq!(nodes, expected);
+        let nodes: Vec<_> = Bfs::<LeafNode>::new(0, None, false)
+            .catch_panics()
+            .collect();
+        similar_asserts::assert_eq!(nodes, expected);
+
+        let panicked = std::panic::catch_unwind(|| Dfs::<LeafNode>::new(0, None, false).count());
+        assert!(panicked.is_err());
+    }
+
+    #[test]
+    fn test_dfs_into_depth_map() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        let ids = |depths: std::collections::BTreeMap<usize, Vec<TreeNode>>| {
+            depths
+                .into_iter()
+                .map(|(depth, nodes)| (depth, nodes.into_iter().map(|node| node.0).collect()))
+                .collect::<Vec<(usize, Vec<usize>)>>()
+        };
+        let depths = Dfs::<TreeNode>::new(TreeNode(0), 2, false).into_depth_map()?;
+        similar_asserts::assert_eq!(ids(depths), [(1, vec![1, 2]), (2, vec![3, 4, 5, 6])]);
+        let depths = FastDfs::<TreeNode>::new(TreeNode(0), 3, false)
+            .min_depth(3)
+            .into_depth_map()?;
+        similar_asserts::assert_eq!(ids(depths), [(3, (7..15).collect())]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_from_fn() -> Result<()> {
+        use crate::utils::test::{Error, GraphNode, TreeNode};
+        use std::sync::{Arc, Mutex};
+
+        // the closure records the crawl in its own state
+        let expanded = Arc::new(Mutex::new(Vec::new()));
+        let dfs = Dfs::<TreeNode>::from_fn(TreeNode(0), 2, false, {
+            let expanded = Arc::clone(&expanded);
+            move |node: &TreeNode, depth| {
+                expanded.lock().unwrap().push((node.0, depth));
+                Ok(Box::new(node.children().into_iter().map(Ok)))
+            }
+        });
+        let ids = dfs.map(|node| node.map(|node| node.0));
+        similar_asserts::assert_eq!(ids.collect::<Result<Vec<_>, _>>()?, [1, 3, 4, 2, 5, 6]);
+        similar_asserts::assert_eq!(*expanded.lock().unwrap(), [(0, 1), (1, 2), (2, 2)]);
+
+        // the options of the traversal apply to the children of the closure
+        let edges: &[&[usize]] = &[&[1, 2], &[2, 0], &[]];
+        let dfs = Dfs::<GraphNode>::from_fn(GraphNode::new(0, edges), None, false, |node, _| {
+            if node.id == 2 {
+                return Err(Error);
+            }
+            Ok(Box::new(node.neighbors().map(Ok)))
+        })
+        .max_branching(1);
+        let ids = dfs.map(|node| node.map(|node| node.id));
+        similar_asserts::assert_eq!(ids.collect::<Vec<_>>(), [Ok(1), Ok(2), Err(Error)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_max_branching_explain_skip() -> Result<()> {
+        use crate::sync::{ExtendQueue, FastNode, SkipReason};
+        use crate::utils::test::TreeNode;
+        use std::sync::{Arc, Mutex};
+
+        type Skipped = Arc<Mutex<Vec<(usize, SkipReason)>>>;
+        fn on_skip<N>(skipped: &Skipped, id: fn(&N) -> usize) -> impl FnMut(&N, SkipReason) {
+            let skipped = Arc::clone(skipped);
+            move |node, reason| skipped.lock().unwrap().push((id(node), reason))
+        }
+
+        // the children beyond the limit are produced to report them
+        let skipped = Skipped::default();
+        let ids = Dfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .max_branching(1)
+            .explain_skip(on_skip(&skipped, |node: &TreeNode| node.0))
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [1, 3]);
+        similar_asserts::assert_eq!(
+            *skipped.lock().unwrap(),
+            [
+                (2, SkipReason::BreadthLimit),
+                (4, SkipReason::BreadthLimit),
+                (3, SkipReason::DepthLimit),
+            ]
+        );
+
+        #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+        struct WideNode(usize);
+
+        impl FastNode for WideNode {
+            type Error = std::convert::Infallible;
+
+            fn add_children<E>(&self, _depth: usize, queue: &mut E) -> Result<(), Self::Error>
+            where
+                E: ExtendQueue<Self, Self::Error>,
+            {
+                for i in 1..=3 {
+                    queue.add(Ok(Self(self.0 * 3 + i)));
+                }
+                Ok(())
+            }
+        }
+
+        // the children of a fast node beyond the limit are added one by one
+        let skipped = Skipped::default();
+        let ids = FastDfs::<WideNode>::new(WideNode(0), 1, false)
+            .max_branching(1)
+            .explain_skip(on_skip(&skipped, |node: &WideNode| node.0))
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [1]);
+        similar_asserts::assert_eq!(
+            *skipped.lock().unwrap(),
+            [
+                (2, SkipReason::BreadthLimit),
+                (3, SkipReason::BreadthLimit),
+                (1, SkipReason::DepthLimit),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_start_depth() {
+        use crate::utils::test::TreeNode;
+
+        let nodes: Vec<(usize, usize)> = Dfs::<TreeNode>::new(TreeNode(0), 6, false)
+            .start_depth(5)
+            .with_depth()
+            .map(|node| node.map(|(depth, node)| (node.0, depth)).unwrap())
+            .collect();
+        similar_asserts::assert_eq!(nodes, [(1, 5), (3, 6), (4, 6), (2, 5), (5, 6), (6, 6)]);
+
+        // the children of the roots are beyond the depth limit
+        let mut dfs = FastDfs::<TreeNode>::new(TreeNode(0), 4, false).start_depth(5);
+        assert!(dfs.next().is_none());
+    }
+
+    #[test]
+    fn test_dfs_group_by_subtree() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        // every subtree is yielded contiguously, so grouping by the
+        // ancestor at depth 1 yields one group per subtree
+        let subtree = |node: &TreeNode, depth: usize| (1..depth).fold(node.0, |id, _| (id - 1) / 2);
+        let groups = Dfs::<TreeNode>::new(TreeNode(0), 3, false)
+            .group_by(subtree)
+            .map(|group| group.map(|(root, nodes)| (root, nodes.len())))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(groups, [(1, 7), (2, 7)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_from_frontier() {
+        use crate::utils::test::TreeNode;
+
+        let frontier = [(1, TreeNode(1)), (1, TreeNode(2)), (1, TreeNode(2))];
+        let ids: Vec<usize> = Dfs::<TreeNode>::from_frontier(frontier, 2, false)
+            .map(|node| node.unwrap().0)
+            .collect();
+        similar_asserts::assert_eq!(ids, [2, 5, 6, 1, 3, 4]);
+
+        // a frontier of roots is only expanded
+        let ids: Vec<usize> = FastDfs::<TreeNode>::from_frontier([(0, TreeNode(0))], 2, false)
+            .map(|node| node.unwrap().0)
+            .collect();
+        similar_asserts::assert_eq!(ids, [1, 3, 4, 2, 5, 6]);
+    }
+
+    #[test]
+    fn test_dfs_skip_nodes() {
+        use crate::utils::test::TreeNode;
+
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 2, false);
+        // the subtree of the skipped node 1 is lost
+        similar_asserts::assert_eq!(dfs.skip_nodes(1), 1);
+        let ids: Vec<usize> = dfs.map(|node| node.unwrap().0).collect();
+        similar_asserts::assert_eq!(ids, [2, 5, 6]);
+
+        let mut fast = FastDfs::<TreeNode>::new(TreeNode(0), 2, false);
+        similar_asserts::assert_eq!(fast.skip_nodes(10), 2);
+        similar_asserts::assert_eq!(fast.next(), None);
+    }
+
+    #[test]
+    fn test_dfs_on_cycle() -> Result<()> {
+        use std::sync::{Arc, Mutex};
+
+        let revisited = Arc::new(Mutex::new(Vec::new()));
+        let on_cycle = {
+            let revisited = Arc::clone(&revisited);
+            move |node: &crate::utils::test::Node| revisited.lock().unwrap().push(node.0)
+        };
+        let iter = Dfs::<crate::utils::test::Node>::new(0, 3, false)
+            .explain_skip(|_, _| {})
+            .on_cycle(on_cycle.clone());
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 2, 3]);
+        similar_asserts::assert_eq!(*revisited.lock().unwrap(), [1, 2, 3]);
+
+        // visited nodes are not tracked when circles are allowed
+        revisited.lock().unwrap().clear();
+        let iter = Dfs::<crate::utils::test::Node>::new(0, 3, true).on_cycle(on_cycle);
+        let _ = depths!(iter);
+        assert!(revisited.lock().unwrap().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_events() -> Result<()> {
+        use crate::sync::TraversalEvent::{Cycle, Frontier, Visited};
+        use crate::utils::test::GraphNode;
+
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[3], &[0]];
+        let events = |max_depth: Option<usize>| {
+            Dfs::<GraphNode>::new(GraphNode::new(0, edges), max_depth, false)
+                .events()
+                .map(|event| {
+                    event.map(|event| match event {
+                        Visited(node) => Visited(node.id),
+                        Frontier(node) => Frontier(node.id),
+                        Cycle(node) => Cycle(node.id),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        similar_asserts::assert_eq!(
+            events(Some(2))?,
+            [Visited(1), Frontier(3), Visited(2), Cycle(3)]
+        );
+        similar_asserts::assert_eq!(
+            events(None)?,
+            [Visited(1), Visited(3), Cycle(0), Visited(2), Cycle(3)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_max_nodes() -> Result<()> {
+        use crate::sync::SkipReason;
+        use std::sync::{Arc, Mutex};
+
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let on_skip = {
+            let skipped = Arc::clone(&skipped);
+            move |node: &crate::utils::test::Node, reason| {
+                skipped.lock().unwrap().push((node.0, reason));
+            }
+        };
+        // the traversal is unbounded without the node limit
+        let iter = Dfs::<crate::utils::test::Node>::new(0, None, true)
+            .max_nodes(3)
+            .explain_skip(on_skip);
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 2, 3]);
+        similar_asserts::assert_eq!(*skipped.lock().unwrap(), [(3, SkipReason::NodeLimit)]);
+
+        #[cfg(feature = "rayon")]
+        {
+            let count = Dfs::<crate::utils::test::Node>::new(0, 3, true)
+                .max_nodes(5)
+                .into_par_iter()
+                .count();
+            similar_asserts::assert_eq!(count, 5);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dfs_checkpoint() -> Result<()> {
+        use crate::utils::test::Node;
+
+        for allow_circles in [true, false] {
+            let iter = Dfs::<Node>::new(0, 3, allow_circles);
+            let expected = depths!(iter);
+            for at in 0..=expected.len() {
+                let mut iter = Dfs::<Node>::new(0, 3, allow_circles);
+                let head = iter.by_ref().take(at);
+                let mut depths = depths!(head);
+                let checkpoint = serde_json::to_string(&iter.into_checkpoint())?;
+                let resumed = Dfs::<Node>::from_checkpoint(serde_json::from_str(&checkpoint)?);
+                depths.extend(depths!(resumed));
+                similar_asserts::assert_eq!(depths, expected);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dfs_checkpoint_keeps_options() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        let ids = |iter: &mut dyn Iterator<Item = Result<TreeNode, _>>| {
+            iter.map(|node| node.map(|node| node.0))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        type Options = fn(Dfs<TreeNode>) -> Dfs<TreeNode>;
+
+        let options: [(Options, &[usize]); 2] = [
+            (|dfs| dfs.max_branching(1).include_root(), &[0, 1, 3]),
+            (
+                |dfs| dfs.preserve_child_order(false).include_root(),
+                &[0, 2, 6, 5, 1, 4, 3],
+            ),
+        ];
+        for (options, expected) in options {
+            let dfs = || options(Dfs::<TreeNode>::new(TreeNode(0), 2, false));
+            similar_asserts::assert_eq!(ids(&mut dfs())?, expected);
+            for at in 0..=expected.len() {
+                let mut iter = dfs();
+                let mut nodes = ids(&mut iter.by_ref().take(at))?;
+                // the options are restored without being set again
+                let checkpoint = serde_json::to_string(&iter.into_checkpoint())?;
+                let mut resumed =
+                    Dfs::<TreeNode>::from_checkpoint(serde_json::from_str(&checkpoint)?);
+                nodes.extend(ids(&mut resumed)?);
+                similar_asserts::assert_eq!(nodes, expected);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_with_visited() -> Result<()> {
+        use crate::utils::test::Node;
+        use std::collections::BTreeSet;
+
+        let iter = Dfs::<Node, _>::with_visited(0, 3, BTreeSet::new());
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 2, 3]);
+
+        // nodes that are already visited are not yielded or expanded
+        let iter = Dfs::<Node, _>::with_visited(0, 3, BTreeSet::from([Node(2)]));
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_into_visited() -> Result<()> {
+        use crate::utils::test::GraphNode;
+        use std::collections::{BTreeSet, HashSet};
+
+        // 0 → 1 → 2 → 0, and 3 is not reachable
+        let edges: &[&[usize]] = &[&[1], &[2], &[0], &[0]];
+        let root = GraphNode::new(0, edges);
+        let ids = |visited: &HashSet<GraphNode>| {
+            visited.iter().map(|node| node.id).collect::<BTreeSet<_>>()
+        };
+
+        let mut dfs = Dfs::<GraphNode>::new(root, None, false);
+        similar_asserts::assert_eq!(ids(&dfs.visited()), BTreeSet::from([0]));
+        dfs.by_ref().collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids(&dfs.visited()), BTreeSet::from([0, 1, 2]));
+        similar_asserts::assert_eq!(ids(&dfs.into_visited()), BTreeSet::from([0, 1, 2]));
+
+        // visited nodes are not tracked when circles are allowed
+        let mut dfs = Dfs::<GraphNode>::new(root, 5, true);
+        dfs.by_ref().collect::<Result<Vec<_>, _>>()?;
+        assert!(dfs.into_visited().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_with_roots() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 2, and 1 → 2 → 3
+        let edges: &[&[usize]] = &[&[2], &[2, 3], &[], &[]];
+        let roots = [0, 1].map(|id| GraphNode::new(id, edges));
+        let ids = Dfs::<GraphNode>::with_roots(roots, None, false)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        // the last root is expanded first
+        similar_asserts::assert_eq!(ids, [2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_size_hint() {
+        let mut iter = Dfs::<crate::utils::test::Node>::new(0, 3, true);
+        // the root is never yielded
+        similar_asserts::assert_eq!(iter.size_hint(), (0, None));
+        iter.next();
+        similar_asserts::assert_eq!(iter.size_hint(), (3, None));
+        iter.by_ref().for_each(drop);
+        similar_asserts::assert_eq!(iter.size_hint(), (0, Some(0)));
+
+        let mut iter = Dfs::<crate::utils::test::Node>::new(0, 3, true).max_nodes(2);
+        iter.next();
+        similar_asserts::assert_eq!(iter.size_hint(), (1, Some(1)));
+
+        let mut iter = Dfs::<crate::utils::test::Node>::new(0, 3, true).min_depth(2);
+        iter.next();
+        similar_asserts::assert_eq!(iter.size_hint(), (0, None));
+    }
+
+    #[cfg(feature = "dot")]
+    #[test]
+    fn test_dfs_to_dot() -> Result<()> {
+        use crate::sync::graph::DotError;
+        use crate::utils::test::{Error, TreeNode};
+
+        let mut dot = Vec::new();
+        Dfs::<TreeNode>::new(TreeNode(0), 1, false)
+            .to_dot(&mut dot, |node| format!("\"{}\"", node.0))?;
+        similar_asserts::assert_eq!(
+            String::from_utf8(dot)?,
+            r#"digraph {
+    0 [label="\"0\""];
+    1 [label="\"1\""];
+    2 [label="\"2\""];
+    0 -> 1;
+    0 -> 2;
+}
+"#
+        );
+
+        // the error of the node is returned as-is, without writing anything
+        let mut dot = Vec::new();
+        let err = Dfs::<TreeNode>::from_fn(TreeNode(0), 1, false, |_, _| Err(Error))
+            .to_dot(&mut dot, |node| node.0.to_string())
+            .unwrap_err();
+        assert!(matches!(err, DotError::Node(Error)));
+        assert!(dot.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_skip_subtree_and_push_back_node() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 2, false);
+        similar_asserts::assert_eq!(dfs.next().transpose()?, Some(TreeNode(1)));
+        dfs.skip_subtree();
+        // skipping twice does not discard the subtree of the sibling
+        dfs.skip_subtree();
+        let ids = dfs
+            .by_ref()
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [2, 5, 6]);
+
+        // the skipped children are no longer visited
+        dfs.push_back_node(TreeNode(1), 1);
+        let ids = dfs
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [1, 3, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset() -> Result<()> {
+        use crate::sync::Bfs;
+        use crate::utils::test::TreeNode;
+
+        let expected = Dfs::<TreeNode>::new(TreeNode(1), 2, false)
+            .include_root()
+            .max_nodes(4)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(expected, [1, 3, 7, 8]);
+
+        let mut dfs = Dfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .include_root()
+            .max_nodes(4);
+        // stop in the middle of the traversal, with nodes still queued
+        similar_asserts::assert_eq!(dfs.by_ref().take(2).count(), 2);
+        dfs.reset(TreeNode(1));
+        similar_asserts::assert_eq!(dfs.stats().emitted, 0);
+        let ids = dfs
+            .by_ref()
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, expected);
+        similar_asserts::assert_eq!(dfs.stats().emitted, 4);
+
+        // nodes visited by the previous traversal are visited again
+        dfs.reset(TreeNode(1));
+        similar_asserts::assert_eq!(dfs.count(), 4);
+
+        let mut bfs = Bfs::<TreeNode>::new(TreeNode(0), 2, false);
+        similar_asserts::assert_eq!(bfs.by_ref().count(), 6);
+        bfs.reset(TreeNode(1));
+        let ids = bfs
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [3, 4, 7, 8, 9, 10]);
+        Ok(())
+    }
+
+    /// A node that only pushes its children directly.
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct DirectNode(usize);
+
+    impl crate::sync::Node for DirectNode {
+        type Error = crate::utils::test::Error;
+
+        fn children(&self, _depth: usize) -> crate::sync::NodeIter<Self, Self::Error> {
+            unreachable!("children of {self:?} are pushed directly")
+        }
+
+        fn children_into<Q>(&self, depth: usize, queue: &mut Q) -> Result<(), Self::Error>
+        where
+            Q: crate::sync::ExtendQueue<Self, Self::Error>,
+        {
+            queue.add_all([Ok(Self(depth)), Ok(Self(depth))]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_dfs_children_into() -> Result<()> {
+        let iter = Dfs::<DirectNode>::new(DirectNode(0), 3, true);
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]);
+        Ok(())
+    }
+
+    /// A node that counts its depth using `u8`.
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    struct ByteNode(u8);
+
+    impl crate::sync::Node<u8> for ByteNode {
+        type Error = crate::utils::test::Error;
+
+        fn children(&self, depth: u8) -> crate::sync::NodeIter<Self, Self::Error> {
+            Ok(Box::new([Ok(Self(depth)), Ok(Self(depth))].into_iter()))
+        }
+    }
+
+    #[test]
+    fn test_dfs_u8_depth() -> Result<()> {
+        let iter = Dfs::<ByteNode, _, u8>::new(ByteNode(0), 3, true);
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 2, 3, 3, 2, 3, 3, 1, 2, 3, 3, 2, 3, 3]);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "depth 256 exceeds the range of the depth type")]
+    fn test_dfs_u8_depth_overflow() {
+        let _ = Dfs::<ByteNode, _, u8>::new(ByteNode(0), None, false).count();
+    }
+
+    #[test]
+    fn test_dfs_find_map_node_does_not_expand_match() -> Result<()> {
+        use crate::utils::test::{LeafNode, LEAF_DEPTH};
+
+        // expanding a leaf panics, so the match must not be expanded
+        let found = Dfs::<LeafNode>::new(0, None, true).find_map_node(|node| node.0 == LEAF_DEPTH);
+        similar_asserts::assert_eq!(found.transpose()?, Some(LeafNode(LEAF_DEPTH)));
+        let found =
+            FastDfs::<LeafNode>::new(0, None, true).find_map_node(|node| node.0 == LEAF_DEPTH);
+        similar_asserts::assert_eq!(found.transpose()?, Some(LeafNode(LEAF_DEPTH)));
+
+        let found =
+            Dfs::<crate::utils::test::Node>::new(0, 3, true).find_map_node(|node| node.0 > 3);
+        assert!(found.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_count_matches_yielded_nodes() {
+        use crate::utils::test::{GraphNode, Node};
+
+        // 0 → 1 → 3, 0 → 2 → 3 → 0
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[3, 0], &[0]];
+        let root = GraphNode::new(0, edges);
+        for max_depth in [None, Some(0), Some(1), Some(2), Some(3)] {
+            for allow_circles in [false, true] {
+                let dfs = Dfs::<Node>::new(0, max_depth.or(Some(4)), allow_circles);
+                similar_asserts::assert_eq!(dfs.clone().count(), dfs.fold(0, |n, _| n + 1));
+                let dfs = FastDfs::<Node>::new(0, max_depth.or(Some(4)), allow_circles);
+                similar_asserts::assert_eq!(dfs.clone().count(), dfs.fold(0, |n, _| n + 1));
+            }
+            let dfs = Dfs::<GraphNode>::new(root, max_depth, false).max_nodes(2);
+            similar_asserts::assert_eq!(dfs.clone().count(), dfs.fold(0, |n, _| n + 1));
+            let dfs = Dfs::<GraphNode>::new(root, max_depth, false);
+            similar_asserts::assert_eq!(dfs.clone().count(), dfs.fold(0, |n, _| n + 1));
+            let dfs = FastDfs::<GraphNode>::new(root, max_depth, false);
+            similar_asserts::assert_eq!(dfs.clone().count(), dfs.fold(0, |n, _| n + 1));
+        }
+    }
+
+    #[test]
+    fn test_dfs_unique() -> Result<()> {
+        use crate::utils::test::{GraphNode, Node};
+
+        let iter = Dfs::<Node>::new(0, 3, true).unique();
+        let depths = depths!(iter);
+        similar_asserts::assert_eq!(depths, [1, 2, 3]);
+
+        // 0 → 1 → 5 → 2 → 3, 0 → 4 → 2
+        let edges: &[&[usize]] = &[&[1, 4], &[5], &[3], &[], &[2], &[2]];
+        let root = GraphNode::new(0, edges);
+        let ids = |dfs: Dfs<GraphNode>| -> Result<Vec<usize>> {
+            Ok(dfs
+                .map(|node| node.map(|node| node.id))
+                .collect::<Result<_, _>>()?)
+        };
+        // 2 is first visited at the depth limit and hence never expanded
+        similar_asserts::assert_eq!(ids(Dfs::new(root, 3, false))?, [1, 5, 2, 4]);
+        // 2 is also expanded when reached through 4, but yielded only once
+        similar_asserts::assert_eq!(ids(Dfs::new(root, 3, true))?, [1, 5, 2, 4, 2, 3]);
+        similar_asserts::assert_eq!(ids(Dfs::new(root, 3, true).unique())?, [1, 5, 2, 4, 3]);
+        Ok(())
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_dfs_shuffled() -> Result<()> {
+        use crate::utils::test::TreeNode;
+        use std::collections::HashSet;
+
+        let ids = |nodes: Vec<TreeNode>| nodes.into_iter().map(|node| node.0).collect::<Vec<_>>();
+        let root = TreeNode(0);
+        let ordered = ids(Dfs::<TreeNode>::new(root, 6, false).collect::<Result<_, _>>()?);
+        let shuffled =
+            ids(Dfs::<TreeNode>::shuffled(root, 6, false, 42).collect::<Result<_, _>>()?);
+        let again = ids(Dfs::<TreeNode>::shuffled(root, 6, false, 42).collect::<Result<_, _>>()?);
+        similar_asserts::assert_eq!(shuffled, again);
+        assert_ne!(shuffled, ordered);
+        similar_asserts::assert_eq!(
+            shuffled.iter().collect::<HashSet<_>>(),
+            ordered.iter().collect::<HashSet<_>>()
+        );
+
+        let fast =
+            ids(FastDfs::<TreeNode>::shuffled(root, 6, false, 42).collect::<Result<_, _>>()?);
+        let again =
+            ids(FastDfs::<TreeNode>::shuffled(root, 6, false, 42).collect::<Result<_, _>>()?);
+        similar_asserts::assert_eq!(fast, again);
+        assert_ne!(fast, ordered);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_peek_next() {
+        use crate::utils::test::Node;
+
+        let mut dfs = Dfs::<Node>::new(0, 3, true);
+        similar_asserts::assert_eq!(dfs.peek_next(), Some(&Ok(Node(0))));
+        similar_asserts::assert_eq!(dfs.remaining(), 1);
+        similar_asserts::assert_eq!(dfs.next(), Some(Ok(Node(1))));
+        similar_asserts::assert_eq!(dfs.remaining(), 3);
+        while let Some(peeked) = dfs.peek_next().cloned() {
+            similar_asserts::assert_eq!(dfs.next(), Some(peeked));
+        }
+        similar_asserts::assert_eq!(dfs.remaining(), 0);
+        similar_asserts::assert_eq!(dfs.next(), None);
+    }
+
+    #[test]
+    fn test_dfs_stats() {
+        use crate::sync::Stats;
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 2, and 0 → 2
+        let edges: &[&[usize]] = &[&[1, 2], &[2], &[]];
+        let mut iter = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false);
+        similar_asserts::assert_eq!(iter.stats(), Stats::default());
+        iter.by_ref().for_each(drop);
+        similar_asserts::assert_eq!(
+            iter.stats(),
+            Stats {
+                emitted: 2,
+                expanded: 3,
+                skipped_visited: 1,
+                errors: 0,
+                peak_queue_len: 2,
+            }
+        );
+    }
+
+    /// Returns a graph of `n` nodes where many paths lead to every node.
+    fn dense_graph(n: usize) -> &'static [&'static [usize]] {
+        let edges = (0..n)
+            .map(|i| {
+                let neighbors = vec![(2 * i + 1) % n, (3 * i + 2) % n, (i + 1) % n];
+                &*Box::leak(neighbors.into_boxed_slice())
+            })
+            .collect::<Vec<_>>();
+        Box::leak(edges.into_boxed_slice())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_dfs_parallel_no_circles_visits_once() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        let edges = dense_graph(500);
+        for _ in 0..10 {
+            let mut ids = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false)
+                .into_par_iter()
+                .map(|node| node.map(|node| node.id))
+                .collect::<Result<Vec<_>, _>>()?;
+            ids.sort_unstable();
+            similar_asserts::assert_eq!(ids, (1..500).collect::<Vec<_>>());
+
+            let mut ids = FastDfs::<GraphNode>::new(GraphNode::new(0, edges), None, false)
+                .into_par_iter()
+                .map(|node| node.map(|node| node.id))
+                .collect::<Result<Vec<_>, _>>()?;
+            ids.sort_unstable();
+            similar_asserts::assert_eq!(ids, (1..500).collect::<Vec<_>>());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "dashmap")]
+    #[test]
+    fn test_dfs_dedup_parallel_visits_once() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        let edges = dense_graph(500);
+        for _ in 0..10 {
+            let mut ids = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false)
+                .dedup_parallel()
+                .into_par_iter()
+                .map(|node| node.map(|node| node.id))
+                .collect::<Result<Vec<_>, _>>()?;
+            ids.sort_unstable();
+            similar_asserts::assert_eq!(ids, (1..500).collect::<Vec<_>>());
+
+            let mut ids = FastDfs::<GraphNode>::new(GraphNode::new(0, edges), None, false)
+                .dedup_parallel()
+                .into_par_iter()
+                .map(|node| node.map(|node| node.id))
+                .collect::<Result<Vec<_>, _>>()?;
+            ids.sort_unstable();
+            similar_asserts::assert_eq!(ids, (1..500).collect::<Vec<_>>());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_dfs_parallel_split_config() -> Result<()> {
+        use crate::utils::test::TreeNode;
+        use std::collections::HashSet;
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build()?;
+        // the threads that yielded a node of a traversal of 2^10 - 2 nodes
+        let threads = |iter: crate::sync::par::ParallelSplittableIterator<Dfs<TreeNode>>| {
+            let threads = pool.install(|| {
+                iter.map(|node| node.map(|_| rayon::current_thread_index()))
+                    .collect::<Result<Vec<_>, _>>()
+            })?;
+            similar_asserts::assert_eq!(threads.len(), 1022);
+            Ok::<_, anyhow::Error>(threads.into_iter().collect::<HashSet<_>>().len())
+        };
+        let dfs = || Dfs::<TreeNode>::new(TreeNode(0), 9, true);
+
+        // without splits, the traversal stays on a single thread
+        similar_asserts::assert_eq!(threads(dfs().into_par_iter().splits(0))?, 1);
+        // the queue of the traversal never grows to this length
+        let iter = dfs().into_par_iter().min_split_len(usize::MAX);
+        similar_asserts::assert_eq!(threads(iter)?, 1);
+        let iter = crate::sync::par::ParallelSplittableIterator::with_config(dfs(), 2, 0);
+        similar_asserts::assert_eq!(threads(iter)?, 1);
+        let _ = threads(dfs().into_par_iter().min_split_len(4).splits(8))?;
+        // a single split, and stolen halves are not split any further
+        let iter = dfs().into_par_iter().splits(1).steal_reset_splits(0);
+        assert!(threads(iter)? <= 2);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_dfs_into_par_iter_ordered() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        // enough threads to split the traversal
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build()?;
+        let root = TreeNode(0);
+
+        let sequential = Dfs::<TreeNode>::new(root, 10, false).collect::<Result<Vec<_>, _>>()?;
+        let parallel = pool.install(|| {
+            Dfs::<TreeNode>::new(root, 10, false)
+                .into_par_iter_ordered()
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        similar_asserts::assert_eq!(parallel, sequential);
+
+        let sequential =
+            FastDfs::<TreeNode>::new(root, 10, false).collect::<Result<Vec<_>, _>>()?;
+        let parallel = pool.install(|| {
+            FastDfs::<TreeNode>::new(root, 10, false)
+                .into_par_iter_ordered()
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        similar_asserts::assert_eq!(parallel, sequential);
+
+        let sequential = Dfs::<TreeNode>::new(root, 10, false)
+            .with_depth()
+            .collect::<Result<Vec<_>, _>>()?;
+        let parallel = pool.install(|| {
+            Dfs::<TreeNode>::new(root, 10, false)
+                .with_depth()
+                .into_par_iter_ordered()
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        similar_asserts::assert_eq!(parallel, sequential);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_clone_is_independent() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        let edges = dense_graph(50);
+        let iter = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false).max_nodes(20);
+        let cloned = iter.clone();
+        similar_asserts::assert_eq!(iter.count(), 20);
+        similar_asserts::assert_eq!(cloned.count(), 20);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_prune() -> Result<()> {
+        use crate::sync::SkipReason;
+        use crate::utils::test::GraphNode;
+        use std::sync::{Arc, Mutex};
+
+        // 0 → 1 → 3
+        // 0 → 2 → 4
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[4], &[], &[]];
+        let root = GraphNode::new(0, edges);
+        let ids = Dfs::<GraphNode>::new(root, None, false)
+            .prune(|node, _| node.id == 2)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [1, 3, 2]);
+
+        // pruned nodes are reported as skipped
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let on_skip = {
+            let skipped = Arc::clone(&skipped);
+            move |node: &GraphNode, reason| skipped.lock().unwrap().push((node.id, reason))
+        };
+        let count = Dfs::<GraphNode>::new(root, None, false)
+            .prune(|node, _| node.id == 2)
+            .explain_skip(on_skip)
+            .count();
+        similar_asserts::assert_eq!(count, 3);
+        similar_asserts::assert_eq!(*skipped.lock().unwrap(), [(2, SkipReason::Pruned)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_cycle_path() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 2 → 3 → 1, and 0 → 3
+        let edges: &[&[usize]] = &[&[1, 3], &[2], &[3], &[1]];
+        let cycle = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false).cycle_path()?;
+        let cycle = cycle.map(|cycle| cycle.into_iter().map(|node| node.id).collect::<Vec<_>>());
+        // the children are searched in the order they are produced
+        similar_asserts::assert_eq!(cycle, Some(vec![1, 2, 3, 1]));
+
+        // no cycle within the depth limit
+        let cycle = Dfs::<GraphNode>::new(GraphNode::new(0, edges), 2, false).cycle_path()?;
+        assert!(cycle.is_none());
+
+        // pruned nodes are not expanded
+        let cycle = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false)
+            .prune(|node, _| node.id == 2)
+            .cycle_path()?;
+        assert!(cycle.is_none());
+
+        // acyclic
+        let edges: &[&[usize]] = &[&[1, 2], &[2], &[]];
+        let cycle = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, true).cycle_path()?;
+        assert!(cycle.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_prune_visited() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 2 → 3 → 0
+        let edges: &[&[usize]] = &[&[1], &[2], &[3], &[0]];
+        let ids = |dfs: Dfs<GraphNode>| -> Result<Vec<usize>> {
+            Ok(dfs
+                .map(|node| node.map(|node| node.id))
+                .collect::<Result<_, _>>()?)
+        };
+        let root = GraphNode::new(0, edges);
+        similar_asserts::assert_eq!(ids(Dfs::new(root, 8, false))?, [1, 2, 3]);
+        similar_asserts::assert_eq!(ids(Dfs::new(root, 8, false).prune_visited(4))?, [1, 2, 3]);
+        similar_asserts::assert_eq!(
+            ids(Dfs::new(root, 8, false).prune_visited(2))?,
+            [1, 2, 3, 0, 1, 2, 3, 0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_leaves() -> Result<()> {
+        use crate::utils::test::{GraphNode, TreeNode};
+
+        // nodes at the depth limit are leaves
+        let leaves = Dfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .leaves()
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(leaves, [3, 4, 5, 6]);
+
+        // the children of 2 were already visited, but it is not a leaf
+        let edges: &[&[usize]] = &[&[1, 2, 4], &[3], &[3, 0], &[], &[]];
+        let leaves = Dfs::<GraphNode>::new(GraphNode::new(0, edges), None, false)
+            .leaves()
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(leaves, [3, 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_circle_policy() -> Result<()> {
+        use crate::sync::CirclePolicy;
+        use crate::utils::test::GraphNode;
+
+        // 3 is reachable from both 1 and 2
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[3], &[4], &[]];
+        let root = GraphNode::new(0, edges);
+        let traverse = |policy: CirclePolicy| -> Result<(Vec<usize>, usize)> {
+            let mut dfs = Dfs::<GraphNode>::new(root, None, false).circle_policy(policy);
+            let ids = dfs
+                .by_ref()
+                .map(|node| node.map(|node| node.id))
+                .collect::<Result<_, _>>()?;
+            let visited = dfs.visited().len();
+            Ok((ids, visited))
+        };
+        similar_asserts::assert_eq!(traverse(CirclePolicy::Never)?, (vec![1, 3, 4, 2], 5));
+        similar_asserts::assert_eq!(traverse(CirclePolicy::Always)?, (vec![1, 3, 4, 2, 3, 4], 1));
+        // only the root and the nodes at depth 1 are tracked
+        similar_asserts::assert_eq!(
+            traverse(CirclePolicy::BelowDepth(1))?,
+            (vec![1, 3, 4, 2, 3, 4], 3)
+        );
+        similar_asserts::assert_eq!(
+            traverse(CirclePolicy::BelowDepth(2))?,
+            (vec![1, 3, 4, 2], 4)
+        );
+        similar_asserts::assert_eq!(CirclePolicy::from(true), CirclePolicy::Always);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_run_to_fixpoint() -> Result<()> {
+        use crate::sync::{Node, NodeIter};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static PASSES: AtomicUsize = AtomicUsize::new(0);
+
+        /// Node `id` reaches `id + 1` once `id` is below the number of passes.
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        struct ReachNode(usize);
+
+        impl Node for ReachNode {
+            type Error = std::convert::Infallible;
+
+            fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+                if self.0 == 0 {
+                    PASSES.fetch_add(1, Ordering::SeqCst);
+                }
+                let passes = PASSES.load(Ordering::SeqCst).min(4);
+                let children = (self.0 < passes).then(|| ReachNode(self.0 + 1));
+                Ok(Box::new(children.into_iter().map(Ok)))
+            }
+        }
+
+        let ids = |nodes: std::collections::HashSet<ReachNode>| {
+            let mut ids: Vec<_> = nodes.into_iter().map(|node| node.0).collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        let nodes = Dfs::new(ReachNode(0), None, false).run_to_fixpoint(100)?;
+        similar_asserts::assert_eq!(ids(nodes), [1, 2, 3, 4]);
+        // the last pass did not discover new nodes
+        similar_asserts::assert_eq!(PASSES.swap(0, Ordering::SeqCst), 5);
+
+        let nodes = Dfs::new(ReachNode(0), None, false).run_to_fixpoint(2)?;
+        similar_asserts::assert_eq!(ids(nodes), [1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_split_errors() {
+        use crate::sync::{Node, NodeIter};
+        use crate::utils::test::Error;
+
+        /// Every node has a successful child and a failing sibling.
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        struct FailingNode(usize);
+
+        impl Node for FailingNode {
+            type Error = Error;
+
+            fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+                Ok(Box::new(
+                    [Err(Error), Ok(FailingNode(self.0 + 1))].into_iter(),
+                ))
+            }
+        }
+
+        let (nodes, errors) = Dfs::<FailingNode>::new(FailingNode(0), 3, false).split_errors();
+        let nodes: Vec<_> = nodes.map(|node| node.0).collect();
+        similar_asserts::assert_eq!(nodes, [1, 2, 3]);
+        similar_asserts::assert_eq!(*errors.lock().unwrap(), [Error, Error, Error]);
+    }
+
+    #[test]
+    fn test_dfs_collect_adjacency() -> Result<()> {
+        use crate::sync::graph::BorrowedGraphDfs;
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 3 → 0
+        // 0 → 2 → 3
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[3], &[0]];
+        let root = GraphNode::new(0, edges);
+        let adjacency = Dfs::<GraphNode>::new(root, None, false).collect_adjacency()?;
+        let mut ids: Vec<(usize, Vec<usize>)> = adjacency
+            .iter()
+            .map(|(node, children)| (node.id, children.iter().map(|c| c.id).collect()))
+            .collect();
+        ids.sort_unstable();
+        similar_asserts::assert_eq!(
+            ids,
+            [(0, vec![1, 2]), (1, vec![3]), (2, vec![3]), (3, vec![0])]
+        );
+
+        // replaying the adjacency yields the same nodes
+        let replayed: Vec<usize> = BorrowedGraphDfs::new(&adjacency, &root, None, false)
+            .map(|node| node.id)
+            .collect();
+        let expected = Dfs::<GraphNode>::new(root, None, false)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(replayed, expected);
+
+        // nodes at the depth limit are not expanded
+        let adjacency = Dfs::<GraphNode>::new(root, 1, true).collect_adjacency()?;
+        similar_asserts::assert_eq!(adjacency.len(), 1);
+
+        // pruned nodes are not expanded
+        let adjacency = Dfs::<GraphNode>::new(root, None, false)
+            .prune(|node, _| node.id == 3)
+            .collect_adjacency()?;
+        similar_asserts::assert_eq!(adjacency.len(), 3);
+        assert!(!adjacency.contains_key(&GraphNode::new(3, edges)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_collect_graph() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 3 → 0
+        // 0 → 2 → 3
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[3], &[0]];
+        let root = GraphNode::new(0, edges);
+        let graph = Dfs::<GraphNode>::new(root, None, false).collect_graph()?;
+        let ids: Vec<usize> = graph.nodes.iter().map(|node| node.id).collect();
+        similar_asserts::assert_eq!(ids, [0, 1, 2, 3]);
+        similar_asserts::assert_eq!(graph.edges, [(0, 1), (0, 2), (1, 3), (3, 0), (2, 3)]);
+
+        // nodes at the depth limit are not expanded
+        let graph = Dfs::<GraphNode>::new(root, 1, true).collect_graph()?;
+        similar_asserts::assert_eq!(graph.nodes.len(), 3);
+        similar_asserts::assert_eq!(graph.edges, [(0, 1), (0, 2)]);
+
+        // the children are requested like those of the iterator
+        let graph = Dfs::<GraphNode>::new(root, None, false)
+            .max_branching(1)
+            .collect_graph()?;
+        similar_asserts::assert_eq!(graph.edges, [(0, 1), (1, 2), (2, 0)]);
+        let graph = Dfs::<GraphNode>::new(root, None, false)
+            .prune(|node, _| node.id == 1)
+            .collect_graph()?;
+        similar_asserts::assert_eq!(graph.edges, [(0, 1), (0, 2), (2, 3), (3, 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfs_with_last_sibling_flag() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        let edges: &[&[usize]] = &[&[1, 2], &[3, 4], &[], &[], &[], &[]];
+        let root = GraphNode::new(0, edges);
+        let flags = Dfs::<GraphNode>::new(root, None, false)
+            .with_last_sibling_flag()
+            .map(|node| node.map(|(node, last)| (node.id, last)))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(flags, [(1, false), (3, false), (4, true), (2, true)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_dfs_with_depth() -> Result<()> {
+        // the test nodes store their depth
+        let nodes = FastDfs::<crate::utils::test::Node>::new(0, 3, true)
+            .with_depth()
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(nodes.len(), 14);
+        assert!(nodes.iter().all(|(depth, node)| *depth == node.0));
+
+        #[cfg(feature = "rayon")]
+        {
+            let nodes = FastDfs::<crate::utils::test::Node>::new(0, 3, true)
+                .with_depth()
+                .into_par_iter()
+                .collect::<Result<Vec<_>, _>>()?;
+            similar_asserts::assert_eq!(nodes.len(), 14);
+            assert!(nodes.iter().all(|(depth, node)| *depth == node.0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_dfs_expands_root() -> Result<()> {
+        use crate::utils::test::TreeNode;
+
+        // like Dfs, the root is expanded at depth 1 instead of being yielded at depth 0
+        let nodes = FastDfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .with_depth()
+            .map(|node| node.map(|(depth, node)| (depth, node.0)))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(nodes, [(1, 1), (2, 3), (2, 4), (1, 2), (2, 5), (2, 6)]);
+
+        let dfs_nodes = Dfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .with_depth()
+            .map(|node| node.map(|(depth, node)| (depth, node.0)))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(nodes, dfs_nodes);
+
+        // the root is only yielded on request
+        let nodes = FastDfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .include_root()
+            .with_depth()
+            .map(|node| node.map(|(depth, node)| (depth, node.0)))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(nodes.first(), Some(&(0, 0)));
+        similar_asserts::assert_eq!(nodes.len(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_owned_dfs_does_not_clone() -> Result<()> {
+        use super::OwnedDfs;
+        use crate::sync::{Node, NodeIter, OwnedNode};
+        use crate::utils::test::Error;
+        use std::cell::Cell;
+
+        thread_local! {
+            static CLONES: Cell<usize> = const { Cell::new(0) };
+        }
+
+        /// A binary tree node that counts how often it is cloned.
+        #[derive(PartialEq, Eq, Hash, Debug)]
+        struct CountedNode(usize);
+
+        impl Clone for CountedNode {
+            fn clone(&self) -> Self {
+                CLONES.with(|clones| clones.set(clones.get() + 1));
+                Self(self.0)
+            }
+        }
+
+        impl CountedNode {
+            fn children(&self) -> impl Iterator<Item = Result<Self, Error>> {
+                let id = self.0;
+                (1..=2).map(move |i| Ok(Self(2 * id + i)))
+            }
+        }
+
+        impl Node for CountedNode {
+            type Error = Error;
+
+            fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+                Ok(Box::new(Self::children(self)))
+            }
+        }
+
+        impl OwnedNode for CountedNode {
+            type Item = usize;
+            type Error = Error;
+
+            fn into_children(self, _depth: usize) -> (usize, NodeIter<Self, Self::Error>) {
+                (self.0, Ok(Box::new(Self::children(&self))))
+            }
+
+            fn into_item(self) -> usize {
+                self.0
+            }
+        }
+
+        let expected = Dfs::<CountedNode>::new(CountedNode(0), 3, false)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        // every node is cloned into the visited set
+        similar_asserts::assert_eq!(CLONES.with(Cell::take), 15);
+
+        let ids = OwnedDfs::<CountedNode>::new(CountedNode(0), 3).collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, expected);
+        similar_asserts::assert_eq!(CLONES.with(Cell::take), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_post_order_dfs_matches_dfs() -> Result<()> {
+        use crate::utils::test::{GraphNode, TreeNode};
+
+        let edges: &[&[usize]] = &[&[1, 2, 3], &[4, 5], &[5, 6], &[1, 6], &[0], &[], &[2]];
+        let root = GraphNode::new(0, edges);
+        for allow_circles in [false, true] {
+            let ids = |iter: &mut dyn Iterator<Item = Result<GraphNode, _>>| {
+                iter.map(|node| node.map(|node| node.id))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            let expected = ids(&mut Dfs::<GraphNode>::new(root, 4, allow_circles))?;
+            let post_order = ids(&mut PostOrderDfs::<GraphNode>::new(root, 4, allow_circles))?;
+            crate::utils::test::assert_eq_sorted!(post_order, expected);
+        }
+
+        // the children are traversed in the order they are produced
+        let post_order = PostOrderDfs::<TreeNode>::new(TreeNode(0), 2, false)
+            .map(|node| node.map(|node| node.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(post_order, [3, 4, 1, 5, 6, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iterative_deepening_dfs_terminates() -> Result<()> {
+        use crate::utils::test::GraphNode;
+
+        // 0 → 1 → 3 → 0
+        // 0 → 2 → 3
+        let edges: &[&[usize]] = &[&[1, 2], &[3], &[3], &[0]];
+        let root = GraphNode::new(0, edges);
+        let ids = IterativeDeepeningDfs::<GraphNode>::new(root, None, false)
+            .map(|node| node.map(|node| node.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        similar_asserts::assert_eq!(ids, [1, 2, 3]);
+        Ok(())
+    }
 }
@@ -0,0 +1,317 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::iter::Iterator;
+use std::ops::Add;
+
+/// A fallible [`Iterator`] over the children of a [`WeightedNode`] and the
+/// costs of the edges to them.
+///
+/// [`Iterator`]: trait@std::iter::Iterator
+/// [`WeightedNode`]: trait@crate::sync::dijkstra::WeightedNode
+pub type WeightedNodeIter<I, C, E> = Result<Box<dyn Iterator<Item = Result<(I, C), E>>>, E>;
+
+/// A node whose edges to its children have a cost.
+///
+/// The cost of a path is the sum of the costs of its edges, starting from
+/// [`Default::default`] at the root, so costs should never be negative.
+///
+/// [`Default::default`]: fn@std::default::Default::default
+pub trait WeightedNode
+where
+    Self: Hash + Eq + Clone + std::fmt::Debug,
+{
+    /// The type of the cost of edges and paths.
+    type Cost: Ord + Add<Output = Self::Cost> + Clone + Default;
+
+    /// The type of the error when producing children fails.
+    type Error: std::fmt::Debug;
+
+    /// Returns an [`Iterator`] over its children and the costs of the edges to them.
+    ///
+    /// # Errors
+    ///
+    /// Should return [`Self::Error`] if the iterator cannot be created.
+    ///
+    /// [`Iterator`]: trait@std::iter::Iterator
+    /// [`Self::Error`]: type@crate::sync::dijkstra::WeightedNode::Error
+    fn children_weighted(&self, depth: usize) -> WeightedNodeIter<Self, Self::Cost, Self::Error>;
+}
+
+/// A queued node ordered by its cumulative cost, lowest first.
+///
+/// Nodes with equal cost are ordered by insertion, so that
+/// earlier nodes come first.
+pub(super) struct Entry<N, C> {
+    pub(super) cost: C,
+    pub(super) seq: u64,
+    pub(super) depth: usize,
+    pub(super) node: N,
+}
+
+impl<N, C> PartialEq for Entry<N, C>
+where
+    C: Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N, C> Eq for Entry<N, C> where C: Ord {}
+
+impl<N, C> PartialOrd for Entry<N, C>
+where
+    C: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C> Ord for Entry<N, C>
+where
+    C: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .cmp(&self.cost)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Synchronous lowest-cost-first iterator for types implementing the [`WeightedNode`] trait.
+///
+/// Every reachable node is yielded once, together with the minimal cumulative
+/// cost of a path from the root to it, in nondecreasing order of cost.
+/// Once a node is yielded, its cost is final, so it is never yielded again.
+/// Nodes with equal cost are yielded in the order they were discovered.
+/// Errors are yielded as soon as they occur.
+///
+/// The depth of a node is the number of edges on the cheapest path found to it.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::dijkstra::{Dijkstra, WeightedNode, WeightedNodeIter};
+///
+/// #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+/// struct City(&'static str);
+///
+/// impl WeightedNode for City {
+///     type Cost = u32;
+///     type Error = std::convert::Infallible;
+///
+///     fn children_weighted(&self, _depth: usize) -> WeightedNodeIter<Self, u32, Self::Error> {
+///         let roads: &[(&str, u32)] = match self.0 {
+///             "a" => &[("b", 7), ("c", 2)],
+///             "c" => &[("b", 3), ("d", 8)],
+///             "b" => &[("d", 1)],
+///             _ => &[],
+///         };
+///         let roads = roads.iter().map(|&(city, cost)| Ok((City(city), cost)));
+///         Ok(Box::new(roads))
+///     }
+/// }
+///
+/// let costs = Dijkstra::<City>::new(City("a"), None)
+///     .map(|node| node.map(|(city, cost)| (city.0, cost)))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(costs, [("c", 2), ("b", 5), ("d", 6)]);
+/// ```
+///
+/// [`WeightedNode`]: trait@crate::sync::dijkstra::WeightedNode
+pub struct Dijkstra<N>
+where
+    N: WeightedNode,
+{
+    heap: BinaryHeap<Entry<N, N::Cost>>,
+    errors: VecDeque<N::Error>,
+    seq: u64,
+    costs: HashMap<N, N::Cost>,
+    settled: HashSet<N>,
+    max_depth: Option<usize>,
+}
+
+impl<N> std::fmt::Debug for Dijkstra<N>
+where
+    N: WeightedNode,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dijkstra")
+            .field("queued", &self.heap.len())
+            .field("settled", &self.settled.len())
+            .field("errors", &self.errors)
+            .field("max_depth", &self.max_depth)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<N> Dijkstra<N>
+where
+    N: WeightedNode,
+{
+    #[inline]
+    /// Creates a new [`Dijkstra`] iterator.
+    ///
+    /// The search will be performed from the `root` node, following paths
+    /// of up to `max_depth` edges.
+    /// Nodes are not expanded beyond `max_depth`, even if a more expensive
+    /// but shorter path to them exists.
+    ///
+    /// [`Dijkstra`]: struct@crate::sync::dijkstra::Dijkstra
+    pub fn new<R, D>(root: R, max_depth: D) -> Self
+    where
+        R: Into<N>,
+        D: Into<Option<usize>>,
+    {
+        let mut dijkstra = Self {
+            heap: BinaryHeap::new(),
+            errors: VecDeque::new(),
+            seq: 0,
+            costs: HashMap::new(),
+            settled: HashSet::new(),
+            max_depth: max_depth.into(),
+        };
+        // the root is expanded lazily but never yielded
+        dijkstra.relax(0, root.into(), N::Cost::default());
+        dijkstra
+    }
+
+    /// Queues `node` at `depth` with `cost`, unless a path that is at most
+    /// as expensive was already found.
+    #[inline]
+    fn relax(&mut self, depth: usize, node: N, cost: N::Cost) {
+        if self.settled.contains(&node) {
+            return;
+        }
+        if let Some(known) = self.costs.get(&node) {
+            if *known <= cost {
+                return;
+            }
+        }
+        self.costs.insert(node.clone(), cost.clone());
+        self.heap.push(Entry {
+            cost,
+            seq: self.seq,
+            depth,
+            node,
+        });
+        self.seq += 1;
+    }
+
+    /// Adds the children of `node` at `depth`, which is reached with `cost`.
+    #[inline]
+    fn expand(&mut self, node: &N, depth: usize, cost: &N::Cost) {
+        match node.children_weighted(depth) {
+            Ok(children) => {
+                for child in children {
+                    match child {
+                        Ok((child, edge)) => self.relax(depth, child, cost.clone() + edge),
+                        Err(err) => self.errors.push_back(err),
+                    }
+                }
+            }
+            Err(err) => self.errors.push_back(err),
+        }
+    }
+}
+
+impl<N> Iterator for Dijkstra<N>
+where
+    N: WeightedNode,
+{
+    type Item = Result<(N, N::Cost), N::Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(err) = self.errors.pop_front() {
+                return Some(Err(err));
+            }
+            let Entry {
+                cost, depth, node, ..
+            } = self.heap.pop()?;
+            // a node is queued again for every cheaper path,
+            // so only the first pop is final
+            if !self.settled.insert(node.clone()) {
+                continue;
+            }
+            self.costs.remove(&node);
+            let at_max_depth = self.max_depth.is_some_and(|max_depth| depth >= max_depth);
+            if !at_max_depth {
+                self.expand(&node, depth + 1, &cost);
+            }
+            // root node is only expanded
+            if depth > 0 {
+                return Some(Ok((node, cost)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dijkstra, WeightedNode, WeightedNodeIter};
+    use anyhow::Result;
+
+    /// A node of a weighted graph given by its edges and their costs.
+    #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+    struct WeightedGraphNode {
+        id: usize,
+        edges: &'static [&'static [(usize, u64)]],
+    }
+
+    impl WeightedNode for WeightedGraphNode {
+        type Cost = u64;
+        type Error = std::convert::Infallible;
+
+        fn children_weighted(&self, _depth: usize) -> WeightedNodeIter<Self, u64, Self::Error> {
+            let edges = self.edges;
+            let children = edges[self.id]
+                .iter()
+                .map(move |&(id, cost)| Ok((Self { id, edges }, cost)));
+            Ok(Box::new(children))
+        }
+    }
+
+    fn costs(dijkstra: Dijkstra<WeightedGraphNode>) -> Result<Vec<(usize, u64)>> {
+        Ok(dijkstra
+            .map(|node| node.map(|(node, cost)| (node.id, cost)))
+            .collect::<Result<_, _>>()?)
+    }
+
+    #[test]
+    fn test_dijkstra() -> Result<()> {
+        // the direct edge 0 → 3 is more expensive than 0 → 1 → 2 → 3,
+        // and 4 is reachable from itself and back to the root
+        let edges: &[&[(usize, u64)]] = &[
+            &[(3, 10), (1, 1), (4, 20)],
+            &[(2, 2), (0, 1)],
+            &[(3, 3), (1, 0)],
+            &[(4, 1)],
+            &[(4, 0), (0, 5)],
+        ];
+        let root = WeightedGraphNode { id: 0, edges };
+        similar_asserts::assert_eq!(
+            costs(Dijkstra::new(root, None))?,
+            [(1, 1), (2, 3), (3, 6), (4, 7)]
+        );
+        // with at most two edges, 3 is only reached directly
+        similar_asserts::assert_eq!(
+            costs(Dijkstra::new(root, 2))?,
+            [(1, 1), (2, 3), (3, 10), (4, 11)]
+        );
+        similar_asserts::assert_eq!(costs(Dijkstra::new(root, 0))?, []);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dijkstra_equal_costs() -> Result<()> {
+        let edges: &[&[(usize, u64)]] = &[&[(2, 1), (1, 1), (3, 0)], &[], &[], &[]];
+        let root = WeightedGraphNode { id: 0, edges };
+        similar_asserts::assert_eq!(costs(Dijkstra::new(root, None))?, [(3, 0), (2, 1), (1, 1)]);
+        Ok(())
+    }
+}
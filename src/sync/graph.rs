@@ -0,0 +1,240 @@
+//! Discovered graphs and traversals over borrowed adjacency maps.
+
+use super::queue;
+use super::Queue;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::hash::Hash;
+use std::iter::Iterator;
+
+/// An adjacency map from each node to its neighbors.
+pub type Adjacency<K> = HashMap<K, Vec<K>>;
+
+/// A snapshot of a discovered graph, with each node interned by its index.
+///
+/// See [`Dfs::collect_graph`].
+///
+/// [`Dfs::collect_graph`]: fn@crate::sync::Dfs::collect_graph
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Graph<N> {
+    /// The unique nodes in the order they were discovered, starting with the root.
+    pub nodes: Vec<N>,
+    /// The edges from each expanded node to each of its children,
+    /// as pairs of indices into [`nodes`](Self::nodes).
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<N> Graph<N> {
+    /// Writes the graph to `writer` in the Graphviz [DOT] format.
+    ///
+    /// The graph is written as a `digraph`, declaring every node by its index
+    /// and labeled by `label`, e.g. using [`Node::display_label`], followed by
+    /// all edges in the order they were discovered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    ///
+    /// [DOT]: https://graphviz.org/doc/info/lang.html
+    /// [`Node::display_label`]: fn@crate::sync::Node::display_label
+    #[cfg(feature = "dot")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dot")))]
+    pub fn to_dot<W, F>(&self, mut writer: W, label: F) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+        F: Fn(&N) -> String,
+    {
+        writeln!(writer, "digraph {{")?;
+        for (id, node) in self.nodes.iter().enumerate() {
+            let label = label(node)
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            writeln!(writer, "    {id} [label=\"{label}\"];")?;
+        }
+        for (parent, child) in &self.edges {
+            writeln!(writer, "    {parent} -> {child};")?;
+        }
+        writeln!(writer, "}}")
+    }
+}
+
+/// The error of exporting a traversal in the DOT format.
+///
+/// See [`Dfs::to_dot`].
+///
+/// [`Dfs::to_dot`]: fn@crate::sync::Dfs::to_dot
+#[cfg(feature = "dot")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dot")))]
+#[derive(thiserror::Error, Debug)]
+pub enum DotError<E> {
+    /// A node could not be expanded.
+    #[error("failed to expand node: {0:?}")]
+    Node(E),
+    /// Writing the graph failed.
+    #[error("failed to write graph")]
+    Io(#[from] std::io::Error),
+}
+
+macro_rules! borrowed_graph_iterator {
+    ($name:ident, $pop:ident, $reverse:literal, $kind:literal) => {
+        #[doc = concat!("Synchronous ", $kind, " iterator over a borrowed adjacency map.")]
+        ///
+        /// Yields references to the keys reachable from the root by following
+        /// the edges of the map, without implementing the [`Node`] trait or
+        /// constructing owned nodes.
+        /// Keys that are not in the map do not have any neighbors.
+        ///
+        /// The iterator borrows the map for its lifetime `'a`, so the map
+        /// cannot be modified while it is being traversed, and the yielded
+        /// references remain valid after the iterator is dropped.
+        ///
+        /// [`Node`]: trait@crate::sync::Node
+        ///
+        /// ### Example
+        /// ```
+        /// use std::collections::HashMap;
+        #[doc = concat!("use par_dfs::sync::graph::", stringify!($name), ";")]
+        ///
+        /// let graph = HashMap::from([(0, vec![1, 2]), (1, vec![2])]);
+        #[doc = concat!("let nodes: Vec<_> = ", stringify!($name), "::new(&graph, &0, None, false).collect();")]
+        /// assert_eq!(nodes.len(), 2);
+        /// ```
+        #[derive(Debug, Clone)]
+        pub struct $name<'a, K> {
+            graph: &'a Adjacency<K>,
+            queue: queue::Queue<&'a K, Infallible>,
+            max_depth: Option<usize>,
+        }
+
+        impl<'a, K> $name<'a, K>
+        where
+            K: Hash + Eq,
+        {
+            #[doc = concat!("Creates a new [`", stringify!($name), "`] iterator.")]
+            ///
+            /// The traversal starts from `root` and follows the edges of `graph`
+            /// up to depth `max_depth`.
+            ///
+            /// When `allow_circles`, visited nodes will not be tracked, which can lead to cycles.
+            #[inline]
+            pub fn new<D>(
+                graph: &'a Adjacency<K>,
+                root: &'a K,
+                max_depth: D,
+                allow_circles: bool,
+            ) -> Self
+            where
+                D: Into<Option<usize>>,
+            {
+                let mut queue = queue::Queue::new(allow_circles);
+                // the root is expanded lazily but never yielded
+                queue.add(0, Ok(root));
+                Self {
+                    graph,
+                    queue,
+                    max_depth: max_depth.into(),
+                }
+            }
+
+            /// Adds the neighbors of `key` at `depth` to the queue.
+            #[inline]
+            fn expand(&mut self, key: &'a K, depth: usize) {
+                if let Some(neighbors) = self.graph.get(key) {
+                    let len = self.queue.len();
+                    self.queue.add_all(depth, neighbors.iter().map(Ok));
+                    // neighbors are popped in order, like the children of a `Dfs`
+                    if $reverse {
+                        self.queue.reverse_from(len);
+                    }
+                }
+            }
+        }
+
+        impl<'a, K> Iterator for $name<'a, K>
+        where
+            K: Hash + Eq,
+        {
+            type Item = &'a K;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                loop {
+                    let (depth, key) = self.queue.$pop()?;
+                    let Ok(key) = key;
+                    if depth == 0 {
+                        // root node is only expanded, unless it is at the depth limit
+                        if self.max_depth != Some(0) {
+                            self.expand(key, 1);
+                        }
+                        continue;
+                    }
+                    if let Some(max_depth) = self.max_depth {
+                        if depth >= max_depth {
+                            return Some(key);
+                        }
+                    }
+                    self.expand(key, depth + 1);
+                    return Some(key);
+                }
+            }
+        }
+    };
+}
+
+borrowed_graph_iterator!(BorrowedGraphDfs, pop_back, true, "depth-first");
+borrowed_graph_iterator!(BorrowedGraphBfs, pop_front, false, "breadth-first");
+
+#[cfg(test)]
+mod tests {
+    use super::{Adjacency, BorrowedGraphBfs, BorrowedGraphDfs};
+
+    fn graph() -> Adjacency<&'static str> {
+        // a → b → d
+        // a → c → d → a
+        Adjacency::from([
+            ("a", vec!["b", "c"]),
+            ("b", vec!["d"]),
+            ("c", vec!["d"]),
+            ("d", vec!["a"]),
+        ])
+    }
+
+    #[test]
+    fn test_borrowed_graph_dfs() {
+        let graph = graph();
+        let nodes: Vec<_> = BorrowedGraphDfs::new(&graph, &"a", None, false)
+            .copied()
+            .collect();
+        similar_asserts::assert_eq!(nodes, ["b", "d", "c"]);
+        let nodes: Vec<_> = BorrowedGraphDfs::new(&graph, &"a", 3, true)
+            .copied()
+            .collect();
+        similar_asserts::assert_eq!(nodes, ["b", "d", "a", "c", "d", "a"]);
+    }
+
+    #[test]
+    fn test_borrowed_graph_bfs() {
+        let graph = graph();
+        let nodes: Vec<_> = BorrowedGraphBfs::new(&graph, &"a", None, false)
+            .copied()
+            .collect();
+        similar_asserts::assert_eq!(nodes, ["b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_borrowed_graph_missing_root() {
+        let graph = graph();
+        let nodes: Vec<_> = BorrowedGraphDfs::new(&graph, &"z", None, false).collect();
+        assert!(nodes.is_empty());
+    }
+}
@@ -1,15 +1,44 @@
+pub mod astar;
+pub mod backend;
+pub mod beam;
+pub mod best_first;
 pub mod bfs;
+#[cfg(feature = "crossbeam")]
+#[cfg_attr(docsrs, doc(cfg(feature = "crossbeam")))]
+pub mod channel;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod checkpoint;
 pub mod dfs;
+pub mod dijkstra;
+pub mod graph;
+mod options;
+mod panic;
 #[cfg(feature = "rayon")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
 pub mod par;
+#[cfg(feature = "petgraph")]
+#[cfg_attr(docsrs, doc(cfg(feature = "petgraph")))]
+pub mod petgraph;
 mod queue;
 
-pub use bfs::{Bfs, FastBfs};
-pub use dfs::{Dfs, FastDfs};
+pub use astar::AStar;
+pub use backend::QueueBackend;
+pub use beam::BeamSearch;
+pub use best_first::BestFirst;
+pub use bfs::{Bfs, BidirectionalBfs, FastBfs, LevelBfs};
+pub use dfs::{Dfs, FastDfs, IterativeDeepeningDfs, OwnedDfs, PostOrderDfs};
+pub use dijkstra::{Dijkstra, WeightedNode};
+pub use options::{
+    Events, GroupBy, LastSiblingFlag, Leaves, Stats, TraversalEvent, WithDepth, WithPaths,
+};
+pub use panic::PanicError;
 
-use std::hash::Hash;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::{BuildHasher, Hash};
 use std::iter::{IntoIterator, Iterator};
+use std::marker::PhantomData;
+use std::sync::Arc;
 
 /// Extend a queue with the contents of an [`Iterator`].
 ///
@@ -29,6 +58,162 @@ pub trait ExtendQueue<I, E> {
         Iter: IntoIterator<Item = Result<I, E>>;
 }
 
+/// The reason why a traversal skipped a node.
+///
+/// Reported to the callback registered with `explain_skip`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The node was already visited and is not yielded again.
+    Cycle,
+    /// The node is at `max_depth` and its children are not expanded.
+    DepthLimit,
+    /// The node is a child beyond `max_branching` and is not queued.
+    BreadthLimit,
+    /// The node is pruned by `prune` and its children are not expanded.
+    Pruned,
+    /// The node is the last within `max_nodes` and its children are not expanded.
+    NodeLimit,
+}
+
+/// When a traversal allows circles, i.e. does not track visited nodes.
+///
+/// Converts from the `allow_circles` flag of the constructors, where `true`
+/// is [`Always`](Self::Always) and `false` is [`Never`](Self::Never).
+/// Set with `circle_policy`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CirclePolicy {
+    /// Visited nodes are never tracked, so nodes can be yielded more than once.
+    Always,
+    /// Every node is tracked and yielded at most once.
+    #[default]
+    Never,
+    /// Only nodes up to the given depth are tracked and yielded at most once.
+    ///
+    /// Deeper nodes are neither tracked nor checked against the visited set,
+    /// which bounds its memory while still deduplicating the upper levels.
+    BelowDepth(usize),
+}
+
+impl CirclePolicy {
+    /// Returns `true` if nodes at `depth` are not tracked.
+    #[inline]
+    #[must_use]
+    pub fn allows(self, depth: usize) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::BelowDepth(max_tracked_depth) => depth > max_tracked_depth,
+        }
+    }
+}
+
+impl From<bool> for CirclePolicy {
+    #[inline]
+    fn from(allow_circles: bool) -> Self {
+        if allow_circles {
+            Self::Always
+        } else {
+            Self::Never
+        }
+    }
+}
+
+/// A set of visited nodes that is used by the traversals to avoid cycles.
+///
+/// Implemented for [`HashSet`] with any hasher, which is used by default,
+/// and [`BTreeSet`].
+/// Implement this trait to plug in other sets, e.g. a probabilistic set
+/// when memory is the bottleneck.
+///
+/// [`HashSet`]: struct@std::collections::HashSet
+/// [`BTreeSet`]: struct@std::collections::BTreeSet
+pub trait VisitedSet<I> {
+    /// Returns `true` if the set contains `item`.
+    fn contains(&self, item: &I) -> bool;
+
+    /// Adds `item` to the set.
+    ///
+    /// Returns whether `item` was newly inserted.
+    fn insert(&mut self, item: I) -> bool;
+
+    /// Removes `item` from the set.
+    ///
+    /// Returns whether `item` was present in the set.
+    /// Used by `prune_visited` to forget visited nodes.
+    /// Sets that cannot remove items can keep the default implementation,
+    /// which never removes anything.
+    fn remove(&mut self, item: &I) -> bool {
+        let _ = item;
+        false
+    }
+
+    /// Removes all items from the set.
+    ///
+    /// Used when a traversal is reset.
+    /// The default implementation replaces the set with an empty one,
+    /// sets that can be cleared in place should override it to keep
+    /// their allocated capacity.
+    fn clear(&mut self)
+    where
+        Self: Sized + Default,
+    {
+        *self = Self::default();
+    }
+}
+
+impl<I, S> VisitedSet<I> for HashSet<I, S>
+where
+    I: Hash + Eq,
+    S: BuildHasher,
+{
+    #[inline]
+    fn contains(&self, item: &I) -> bool {
+        HashSet::contains(self, item)
+    }
+
+    #[inline]
+    fn insert(&mut self, item: I) -> bool {
+        HashSet::insert(self, item)
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &I) -> bool {
+        HashSet::remove(self, item)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        HashSet::clear(self);
+    }
+}
+
+impl<I> VisitedSet<I> for BTreeSet<I>
+where
+    I: Ord,
+{
+    #[inline]
+    fn contains(&self, item: &I) -> bool {
+        BTreeSet::contains(self, item)
+    }
+
+    #[inline]
+    fn insert(&mut self, item: I) -> bool {
+        BTreeSet::insert(self, item)
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &I) -> bool {
+        BTreeSet::remove(self, item)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        BTreeSet::clear(self);
+    }
+}
+
 /// A Queue that can be split and allows removing elements
 /// from the front or back.
 pub(crate) trait Queue<I, E> {
@@ -54,19 +239,6 @@ pub(crate) trait Queue<I, E> {
     /// [`None`]: type@std::option::Option::None
     fn pop_front(&mut self) -> Option<(usize, Result<I, E>)>;
 
-    /// Splits the queue into two at the given index.
-    /// Returns a newly allocated queue containing the elements in
-    /// the range `[at, len)`.
-    /// After the call, the original vector will be left containing
-    /// the elements `[0, at)` with its previous capacity unchanged.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `at > self.len()`
-    #[must_use]
-    #[allow(dead_code)]
-    fn split_off(&mut self, at: usize) -> Self;
-
     /// Add single item with given depth to the queue.
     fn add(&mut self, depth: usize, item: Result<I, E>);
 
@@ -78,6 +250,64 @@ pub(crate) trait Queue<I, E> {
         Iter: IntoIterator<Item = Result<I, E>>;
 }
 
+/// An unsigned integer type that counts the depth of nodes.
+///
+/// Implemented for all unsigned integer types, defaulting to [`usize`].
+/// Since the depth is stored with every queued node, smaller types save
+/// memory for huge frontiers.
+/// A traversal panics when the depth of a node exceeds the range of the type,
+/// hence `max_depth` should be set accordingly.
+///
+/// [`usize`]: primitive@usize
+pub trait Depth: Copy + Ord + Hash + std::fmt::Debug + Send + Sync + 'static {
+    /// The depth of the root.
+    const ZERO: Self;
+
+    /// Returns the depth one level deeper, or [`None`] on overflow.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    fn increment(self) -> Option<Self>;
+
+    /// Converts a depth from [`usize`], or returns [`None`] if it is out of range.
+    ///
+    /// [`usize`]: primitive@usize
+    /// [`None`]: type@std::option::Option::None
+    fn from_usize(depth: usize) -> Option<Self>;
+
+    /// Converts this depth into [`usize`].
+    ///
+    /// [`usize`]: primitive@usize
+    fn into_usize(self) -> usize;
+}
+
+macro_rules! impl_depth {
+    ($($ty:ty),*) => {
+        $(
+            impl Depth for $ty {
+                const ZERO: Self = 0;
+
+                #[inline]
+                fn increment(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                #[inline]
+                fn from_usize(depth: usize) -> Option<Self> {
+                    Self::try_from(depth).ok()
+                }
+
+                #[inline]
+                #[allow(clippy::cast_possible_truncation)]
+                fn into_usize(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_depth!(u8, u16, u32, u64, usize);
+
 /// A boxed [`Iterator`] of [`Node`]s.
 ///
 /// [`Iterator`]: trait@std::iter::Iterator
@@ -87,9 +317,24 @@ pub type NodeIter<I, E> = Result<Box<dyn Iterator<Item = Result<I, E>>>, E>;
 /// A node with produces an [`Iterator`] of children [`Node`]s
 /// for a given depth.
 ///
+/// The depth is counted using `D`, which defaults to [`usize`], see [`Depth`].
+///
+/// ### Cloning
+///
+/// Children are produced from a borrowed node, and each node is moved from
+/// the queue to the caller, so expanding and yielding a node never clones it.
+/// Nodes are only cloned to remember them: into the visited set unless
+/// `allow_circles` is set, and for the `unique` and `with_paths` options.
+/// For nodes with a large payload, keep the payload behind an [`Arc`],
+/// so that these clones are cheap, or implement [`OwnedNode`] instead.
+///
 /// [`Iterator`]: trait@std::iter::Iterator
 /// [`Node`]: trait@crate::sync::Node
-pub trait Node
+/// [`usize`]: primitive@usize
+/// [`Depth`]: trait@crate::sync::Depth
+/// [`Arc`]: struct@std::sync::Arc
+/// [`OwnedNode`]: trait@crate::sync::OwnedNode
+pub trait Node<D = usize>
 where
     Self: Hash + Eq + Clone + std::fmt::Debug,
 {
@@ -105,11 +350,52 @@ where
     /// [`Iterator`]: trait@std::iter::Iterator
     /// [`Node`]: trait@crate::sync::Node
     /// [`Self::Error`]: type@crate::async::Node::Error
-    fn children(&self, depth: usize) -> NodeIter<Self, Self::Error>;
+    fn children(&self, depth: D) -> NodeIter<Self, Self::Error>;
+
+    /// Adds its children [`Node`]s to a queue implementing [`ExtendQueue`].
+    ///
+    /// Used by [`Dfs`] and [`Bfs`] to expand a node.
+    /// Defaults to adding the iterator returned by [`children`], which is boxed.
+    /// Override this to push the children directly, like a [`FastNode`],
+    /// and avoid allocating an iterator for every node.
+    ///
+    /// # Errors
+    ///
+    /// Should return [`Self::Error`] if the children could not be added.
+    ///
+    /// [`Node`]: trait@crate::sync::Node
+    /// [`ExtendQueue`]: trait@crate::sync::ExtendQueue
+    /// [`Dfs`]: struct@crate::sync::Dfs
+    /// [`Bfs`]: struct@crate::sync::Bfs
+    /// [`children`]: Self::children
+    /// [`FastNode`]: trait@crate::sync::FastNode
+    /// [`Self::Error`]: type@crate::sync::Node::Error
+    fn children_into<Q>(&self, depth: D, queue: &mut Q) -> Result<(), Self::Error>
+    where
+        Q: ExtendQueue<Self, Self::Error>,
+    {
+        queue.add_all(self.children(depth)?);
+        Ok(())
+    }
+
+    /// Returns a short, human readable label of this node.
+    ///
+    /// Used as the label of this node when printing or exporting a traversal.
+    /// Defaults to the [`Debug`] representation.
+    ///
+    /// [`Debug`]: trait@std::fmt::Debug
+    fn display_label(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Owned(format!("{self:?}"))
+    }
 }
 
 /// A node which adds children [`Node`]s to a queue in place.
-pub trait FastNode
+///
+/// The depth is counted using `D`, which defaults to [`usize`], see [`Depth`].
+///
+/// [`usize`]: primitive@usize
+/// [`Depth`]: trait@crate::sync::Depth
+pub trait FastNode<D = usize>
 where
     Self: Hash + Eq + Clone + std::fmt::Debug,
 {
@@ -126,7 +412,220 @@ where
     /// [`ExtendQueue`]: trait@crate::sync::ExtendQueue
     /// [`Node`]: trait@crate::sync::Node
     /// [`Self::Error`]: type@crate::async::Node::Error
-    fn add_children<E>(&self, depth: usize, queue: &mut E) -> Result<(), Self::Error>
+    fn add_children<E>(&self, depth: D, queue: &mut E) -> Result<(), Self::Error>
     where
         E: ExtendQueue<Self, Self::Error>;
+
+    /// Returns a short, human readable label of this node.
+    ///
+    /// Used as the label of this node when printing or exporting a traversal.
+    /// Defaults to the [`Debug`] representation.
+    ///
+    /// [`Debug`]: trait@std::fmt::Debug
+    fn display_label(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Owned(format!("{self:?}"))
+    }
+}
+
+/// A node that is consumed to produce its children.
+///
+/// In contrast to a [`Node`], a node does not need to be [`Clone`], [`Hash`]
+/// or [`Eq`], and its children can take over its payload.
+/// Since the node is gone once it was expanded, a traversal yields an
+/// [`Item`](Self::Item) in its place, see [`OwnedDfs`].
+///
+/// [`Node`]: trait@crate::sync::Node
+/// [`Clone`]: trait@std::clone::Clone
+/// [`Hash`]: trait@std::hash::Hash
+/// [`Eq`]: trait@std::cmp::Eq
+/// [`OwnedDfs`]: struct@crate::sync::OwnedDfs
+pub trait OwnedNode: Sized {
+    /// The value that is yielded in place of a node.
+    type Item;
+
+    /// The type of the error when producing children fails.
+    type Error: std::fmt::Debug;
+
+    /// Consumes this node and returns the value that is yielded in its place
+    /// together with an [`Iterator`] over its children at `depth`.
+    ///
+    /// # Errors
+    ///
+    /// The iterator should be [`Self::Error`] if it cannot be created,
+    /// in which case the value is still yielded.
+    ///
+    /// [`Iterator`]: trait@std::iter::Iterator
+    /// [`Self::Error`]: type@crate::sync::OwnedNode::Error
+    fn into_children(self, depth: usize) -> (Self::Item, NodeIter<Self, Self::Error>);
+
+    /// Consumes this node without producing its children, e.g. at the depth
+    /// limit, and returns the value that is yielded in its place.
+    fn into_item(self) -> Self::Item;
+}
+
+/// A [`Node`] that can also produce its parents, i.e. the nodes that have
+/// it as one of their children.
+///
+/// Required to search backwards from a target node, see [`BidirectionalBfs`].
+///
+/// [`Node`]: trait@crate::sync::Node
+/// [`BidirectionalBfs`]: struct@crate::sync::BidirectionalBfs
+pub trait BidiNode: Node {
+    /// Returns an [`Iterator`] over its parent [`Node`]s.
+    ///
+    /// The `depth` is the distance of the parents from the target of a
+    /// backward traversal.
+    ///
+    /// # Errors
+    ///
+    /// Should return [`Self::Error`] if the iterator cannot be created.
+    ///
+    /// [`Iterator`]: trait@std::iter::Iterator
+    /// [`Node`]: trait@crate::sync::Node
+    /// [`Self::Error`]: type@crate::sync::Node::Error
+    fn parents(&self, depth: usize) -> NodeIter<Self, Self::Error>;
+}
+
+/// A node with a known, fixed number of children.
+///
+/// Lets traversals compute the number of nodes they yield up-front, see
+/// [`Dfs::exact_len`], e.g. to pre-allocate exactly or to show accurate progress.
+///
+/// Every node must have exactly [`branching_factor`](Self::branching_factor)
+/// children, all of its descendants must have the same branching factor,
+/// and no node may be reached twice, i.e. the nodes form a tree.
+///
+/// [`Dfs::exact_len`]: fn@crate::sync::Dfs::exact_len
+pub trait BoundedNode {
+    /// Returns the number of children of this node and of all of its descendants.
+    fn branching_factor(&self) -> usize;
+}
+
+/// A [`Node`] whose children are produced by a closure.
+///
+/// This avoids declaring a named type for one-off graphs.
+/// Equality, hashing and the debug representation are delegated to the
+/// wrapped `value`, hence two nodes are equal when their values are equal.
+/// The `children` closure is shared by all nodes of a traversal.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::{Dfs, FnNode};
+///
+/// // the numbers below 10 that are reachable by doubling or adding three
+/// let root = FnNode::new(1u32, |n, _depth| {
+///     let children = [n * 2, n + 3].into_iter().filter(|n| *n < 10).map(Ok);
+///     Ok(Box::new(children))
+/// });
+/// let mut numbers = Dfs::<FnNode<_, _>>::new(root, None, false)
+///     .map(|node| node.map(FnNode::into_value))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// numbers.sort_unstable();
+/// assert_eq!(numbers, [2, 4, 5, 7, 8]);
+/// ```
+///
+/// [`Node`]: trait@crate::sync::Node
+pub struct FnNode<T, F, E = std::convert::Infallible> {
+    value: T,
+    children: Arc<F>,
+    error: PhantomData<fn() -> E>,
+}
+
+impl<T, F, E> FnNode<T, F, E> {
+    /// Creates a new [`FnNode`] of `value`, whose children are produced by `children`.
+    ///
+    /// `children` is called with the value of a node and the depth of its children.
+    ///
+    /// [`FnNode`]: struct@crate::sync::FnNode
+    #[inline]
+    pub fn new(value: T, children: F) -> Self
+    where
+        F: Fn(&T, usize) -> NodeIter<T, E>,
+    {
+        Self {
+            value,
+            children: Arc::new(children),
+            error: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the wrapped value.
+    #[inline]
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the wrapped value.
+    #[inline]
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+impl<T, F, E> Node for FnNode<T, F, E>
+where
+    T: Hash + Eq + Clone + std::fmt::Debug + 'static,
+    F: Fn(&T, usize) -> NodeIter<T, E> + 'static,
+    E: std::fmt::Debug + 'static,
+{
+    type Error = E;
+
+    #[inline]
+    fn children(&self, depth: usize) -> NodeIter<Self, Self::Error> {
+        let children = (self.children)(&self.value, depth)?;
+        let shared = Arc::clone(&self.children);
+        Ok(Box::new(children.map(move |child| {
+            child.map(|value| Self {
+                value,
+                children: Arc::clone(&shared),
+                error: PhantomData,
+            })
+        })))
+    }
+}
+
+impl<T, F, E> Clone for FnNode<T, F, E>
+where
+    T: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            children: Arc::clone(&self.children),
+            error: PhantomData,
+        }
+    }
+}
+
+impl<T, F, E> PartialEq for FnNode<T, F, E>
+where
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T, F, E> Eq for FnNode<T, F, E> where T: Eq {}
+
+impl<T, F, E> Hash for FnNode<T, F, E>
+where
+    T: Hash,
+{
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T, F, E> std::fmt::Debug for FnNode<T, F, E>
+where
+    T: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
 }
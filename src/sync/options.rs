@@ -0,0 +1,922 @@
+//! Builder methods shared by all synchronous traversals.
+
+/// An iterator that yields each node together with whether it is the
+/// last of its siblings.
+///
+/// Created by the `with_last_sibling_flag` method of the traversals.
+#[derive(Debug, Clone)]
+pub struct LastSiblingFlag<I>(pub(super) I);
+
+/// An iterator that yields each node together with its depth.
+///
+/// Created by the `with_depth` method of the traversals.
+#[derive(Debug, Clone)]
+pub struct WithDepth<I>(pub(super) I);
+
+/// An iterator that only yields the nodes without children.
+///
+/// Created by the `leaves` method of [`Dfs`].
+///
+/// [`Dfs`]: struct@crate::sync::Dfs
+#[derive(Debug, Clone)]
+pub struct Leaves<I>(pub(super) I);
+
+/// An iterator that yields the path from the root to each node.
+///
+/// Created by the `with_paths` method of the traversals.
+#[derive(Debug, Clone)]
+pub struct WithPaths<I>(pub(super) I);
+
+/// An iterator that yields each node tagged as a [`TraversalEvent`].
+///
+/// Created by the `events` method of the traversals.
+#[derive(Debug, Clone)]
+pub struct Events<I>(pub(super) I);
+
+/// A node reached by a traversal, tagged by whether it was expanded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TraversalEvent<N> {
+    /// A node that was yielded and whose children were expanded.
+    Visited(N),
+    /// A node that was yielded but not expanded because it is at the depth limit.
+    ///
+    /// Together, these nodes form the unexplored boundary of the traversal.
+    Frontier(N),
+    /// A child that was not queued because it was already visited.
+    Cycle(N),
+}
+
+/// An iterator that yields runs of consecutive nodes with equal keys.
+///
+/// Created by the `group_by` method of the traversals.
+pub struct GroupBy<I, N, K, F> {
+    pub(super) iter: I,
+    pub(super) key: F,
+    pub(super) group: Option<(K, Vec<N>)>,
+}
+
+impl<I, N, K, F> std::fmt::Debug for GroupBy<I, N, K, F>
+where
+    I: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GroupBy")
+            .field("iter", &self.iter)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Metrics of a traversal that are updated as it proceeds.
+///
+/// Returned by the `stats` method of the traversals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The number of yielded nodes, excluding errors.
+    pub emitted: usize,
+    /// The number of nodes, including the root, whose children were expanded.
+    pub expanded: usize,
+    /// The number of children that were not queued because they were already visited.
+    pub skipped_visited: usize,
+    /// The number of yielded errors.
+    pub errors: usize,
+    /// The maximum number of queued nodes at any point of the traversal.
+    pub peak_queue_len: usize,
+}
+
+impl Stats {
+    /// Accounts for an expanded node after which `queue_len` nodes are queued.
+    #[inline]
+    pub(super) fn expanded(&mut self, queue_len: usize) {
+        self.expanded += 1;
+        self.peak_queue_len = self.peak_queue_len.max(queue_len);
+    }
+}
+
+macro_rules! traversal_options {
+    ($iter:ident<$node:ident>) => {
+        impl<N, V, D> Iterator for $crate::sync::LastSiblingFlag<$iter<N, V, D>>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            type Item = Result<(N, bool), N::Error>;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                let (_, node, last) = self.0.next_node()?;
+                Some(node.map(|node| (node, last)))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<N, V, D> Iterator for $crate::sync::WithDepth<$iter<N, V, D>>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            type Item = Result<(usize, N), N::Error>;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                let (depth, node, _) = self.0.next_node()?;
+                Some(node.map(|node| (depth, node)))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<N, V, D> Iterator for $crate::sync::WithPaths<$iter<N, V, D>>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            type Item = Result<Vec<N>, N::Error>;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                let (_, node, _) = self.0.next_node()?;
+                Some(node.map(|node| self.0.queue.path_to(node)))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<N, V, D> Iterator for $crate::sync::Events<$iter<N, V, D>>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            type Item = Result<$crate::sync::TraversalEvent<N>, N::Error>;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                use $crate::sync::TraversalEvent;
+
+                if let Some(node) = self.0.queue.pop_cycle() {
+                    return Some(Ok(TraversalEvent::Cycle(node)));
+                }
+                let Some((depth, node, _)) = self.0.next_node() else {
+                    // the last expanded nodes may have reached visited children
+                    return self
+                        .0
+                        .queue
+                        .pop_cycle()
+                        .map(|node| Ok(TraversalEvent::Cycle(node)));
+                };
+                let frontier = self.0.max_depth.is_some_and(|max_depth| depth >= max_depth);
+                Some(node.map(|node| {
+                    if frontier {
+                        TraversalEvent::Frontier(node)
+                    } else {
+                        TraversalEvent::Visited(node)
+                    }
+                }))
+            }
+        }
+
+        impl<N, V, D> $iter<N, V, D>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N> + Clone,
+            D: $crate::sync::Depth,
+        {
+            /// Partitions the queued nodes into `k` independent traversals with
+            /// the same depth limit and options, e.g. to distribute a crawl
+            /// across worker processes.
+            ///
+            /// The nodes are assigned round-robin, so the shards hold roughly
+            /// equal numbers of nodes at every depth.
+            /// Each shard owns a copy of the visited set, so nodes that were
+            /// visited so far are not yielded again.
+            /// However, deduplication across shards is not possible without a
+            /// shared visited set, so nodes that are reachable from more than one
+            /// shard are yielded by each of them, even when `allow_circles` is not set.
+            /// Likewise, each shard has its own copy of the node budget.
+            ///
+            /// Callbacks are shared by all shards.
+            /// A fresh traversal only queues the root, so all but one shard would
+            /// be empty, hence it should be advanced until enough nodes are queued,
+            /// see [`remaining`](Self::remaining).
+            ///
+            /// # Panics
+            ///
+            /// Panics if `k` is zero.
+            #[must_use]
+            pub fn shard(self, k: usize) -> Vec<Self> {
+                let max_depth = self.max_depth;
+                self.queue
+                    .shard(k)
+                    .into_iter()
+                    .map(|queue| Self {
+                        queue,
+                        max_depth,
+                        stats: $crate::sync::Stats::default(),
+                    })
+                    .collect()
+            }
+        }
+
+        impl<N, V, D> $iter<N, V, D>
+        where
+            N: $node<D> + $crate::sync::BoundedNode,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            /// Returns the exact number of nodes that remain to be yielded.
+            ///
+            /// Since every node has a known number of children, the nodes below
+            /// each queued node up to the depth limit form a geometric series.
+            /// Errors that are already queued are counted, but errors of
+            /// nodes that are not expanded yet cannot be known up-front.
+            ///
+            /// Returns `None` when the depth is unbounded, when an option inspects
+            /// individual nodes, e.g. [`min_depth`](Self::min_depth), when nodes
+            /// spilled to a queue backend, or when the number exceeds the range
+            /// of [`usize`].
+            ///
+            /// [`usize`]: primitive@usize
+            #[must_use]
+            pub fn exact_len(&self) -> Option<usize> {
+                let max_depth = self.max_depth?;
+                if !self.queue.is_plain() {
+                    return None;
+                }
+                self.queue
+                    .exact_len(max_depth, $crate::sync::BoundedNode::branching_factor)
+            }
+        }
+
+        impl<N, V, D, K, F> Iterator for $crate::sync::GroupBy<$iter<N, V, D>, N, K, F>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+            K: PartialEq,
+            F: FnMut(&N, usize) -> K,
+        {
+            type Item = Result<(K, Vec<N>), N::Error>;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                loop {
+                    let Some((depth, node, _)) = self.iter.next_node() else {
+                        return self.group.take().map(Ok);
+                    };
+                    // errors are yielded immediately and do not end the current group
+                    let node = match node {
+                        Ok(node) => node,
+                        Err(err) => return Some(Err(err)),
+                    };
+                    let key = (self.key)(&node, depth);
+                    match self.group.as_mut() {
+                        Some((current, nodes)) if *current == key => nodes.push(node),
+                        _ => {
+                            if let Some(group) = self.group.replace((key, vec![node])) {
+                                return Some(Ok(group));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        impl<N, V, D> $iter<N, V, D>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            /// Returns the number of queued nodes.
+            ///
+            /// Queued nodes are discovered but not yet yielded, so this is the
+            /// pending work at the frontier of the traversal, e.g. for progress
+            /// bars.
+            /// Their children are not discovered yet, hence this is not the
+            /// number of nodes that remain to be yielded.
+            #[must_use]
+            pub fn remaining(&self) -> usize {
+                self.queue.len()
+            }
+
+            /// Changes the depth limit to `max_depth` while iterating.
+            ///
+            /// Queued nodes are checked against the new limit once they are yielded,
+            /// so raising the limit expands queued nodes at the old limit, e.g. to
+            /// deepen an adaptive crawl.
+            /// However, nodes that were already yielded at the old limit were not
+            /// expanded, and are not expanded retroactively, so their subtrees are
+            /// missing from the traversal.
+            /// When lowering the limit, queued nodes below the new limit are
+            /// still yielded, but not expanded.
+            pub fn set_max_depth<M>(&mut self, max_depth: M)
+            where
+                M: Into<Option<usize>>,
+            {
+                self.max_depth = max_depth.into();
+            }
+
+            /// Removes the queued nodes for which `predicate` returns `true`
+            /// without expanding them, and returns them.
+            ///
+            /// This cancels pending work of a long-running traversal, e.g. all
+            /// queued nodes below a path.
+            /// The removed nodes are returned in the order of the queue, not in
+            /// the order they would be yielded, and stay visited, so they are
+            /// not queued again when they are reached along another path.
+            /// Queued errors and nodes that spilled to a queue backend are kept.
+            pub fn drain_matching<F>(&mut self, predicate: F) -> Vec<N>
+            where
+                F: FnMut(&N) -> bool,
+            {
+                self.queue.drain_matching(predicate)
+            }
+
+            /// Starts the children of the roots at `start_depth` instead of 1.
+            ///
+            /// This continues the depths of another traversal, e.g. for a sub-crawl
+            /// from a node at depth 4, the children of that node are at depth 5.
+            /// All depths are absolute, so the children are requested, yielded and
+            /// compared to `max_depth` at their continued depth.
+            ///
+            /// The roots are expanded immediately, so this must be called on a
+            /// fresh traversal, and the roots are not yielded even with
+            /// [`include_root`].
+            ///
+            /// # Panics
+            ///
+            /// Panics if `start_depth` is 0.
+            ///
+            /// [`include_root`]: Self::include_root
+            #[must_use]
+            pub fn start_depth(mut self, start_depth: usize) -> Self {
+                assert!(
+                    start_depth > 0,
+                    "children cannot start at the depth of the roots"
+                );
+                let mut roots = Vec::new();
+                while let Some(root) = self.queue.pop_front() {
+                    roots.push(root);
+                }
+                for (depth, root) in roots {
+                    debug_assert_eq!(depth, 0, "start depth set after iterating");
+                    match root {
+                        Ok(root) => {
+                            if self
+                                .max_depth
+                                .is_none_or(|max_depth| start_depth <= max_depth)
+                            {
+                                let len = self.queue.len();
+                                self.expand(&root, start_depth);
+                                self.queue.end_children(len, &root);
+                            }
+                        }
+                        Err(err) => self.queue.add(depth, Err(err)),
+                    }
+                }
+                self
+            }
+
+            /// Skips the next `n` nodes without expanding their children.
+            ///
+            /// In contrast to [`Iterator::skip`] and [`Iterator::nth`], which
+            /// expand every skipped node, the entire subtrees of the skipped nodes
+            /// are lost, so the traversal is no longer complete.
+            /// This is a distinct operation that is only useful when the nodes
+            /// past a certain point are of interest, but not their ancestors.
+            /// Errors count as skipped nodes.
+            ///
+            /// Returns the number of skipped nodes, which is less than `n`
+            /// when the traversal ends.
+            ///
+            /// [`Iterator::skip`]: fn@std::iter::Iterator::skip
+            /// [`Iterator::nth`]: fn@std::iter::Iterator::nth
+            pub fn skip_nodes(&mut self, n: usize) -> usize {
+                let mut skipped = 0;
+                while skipped < n && self.next_node_until(|_| true).is_some() {
+                    skipped += 1;
+                }
+                skipped
+            }
+
+            /// Returns the metrics of the traversal so far.
+            ///
+            /// The metrics are updated as the traversal proceeds and are
+            /// cheap to query at any point during iteration.
+            #[must_use]
+            pub fn stats(&self) -> $crate::sync::Stats {
+                $crate::sync::Stats {
+                    skipped_visited: self.queue.skipped_visited(),
+                    ..self.stats
+                }
+            }
+
+            /// Registers a callback that is notified whenever a node is skipped.
+            ///
+            /// This makes the otherwise silent decisions of the traversal observable,
+            /// e.g. nodes that are not yielded again because they were already visited,
+            /// or nodes whose children are not expanded because of the depth limit.
+            /// See [`SkipReason`] for all reasons.
+            ///
+            /// When no callback is registered, no cost is incurred.
+            ///
+            /// [`SkipReason`]: enum@crate::sync::SkipReason
+            #[must_use]
+            pub fn explain_skip<F>(mut self, on_skip: F) -> Self
+            where
+                F: FnMut(&N, $crate::sync::SkipReason) + Send + 'static,
+            {
+                self.queue
+                    .set_on_skip($crate::sync::queue::SkipHook::new(on_skip));
+                self
+            }
+
+            /// Registers a callback that is notified whenever an already visited node
+            /// is about to be revisited.
+            ///
+            /// The node is skipped as usual, so the output does not change,
+            /// but the traversal doubles as a cheap cycle detector.
+            /// This has no effect when circles are allowed, as visited nodes are not tracked.
+            ///
+            /// When no callback is registered, no cost is incurred.
+            #[must_use]
+            pub fn on_cycle<F>(mut self, mut on_cycle: F) -> Self
+            where
+                F: FnMut(&N) + Send + 'static,
+            {
+                self.queue
+                    .set_on_cycle($crate::sync::queue::SkipHook::new(move |node: &N, _| {
+                        on_cycle(node)
+                    }));
+                self
+            }
+
+            /// Only yields nodes at depth `min_depth` or deeper.
+            ///
+            /// Shallower nodes are still expanded, so deeper nodes remain reachable.
+            /// The children of the root are at depth 1.
+            /// Errors are always yielded.
+            #[must_use]
+            pub fn min_depth(mut self, min_depth: usize) -> Self {
+                self.queue.set_min_depth(min_depth);
+                self
+            }
+
+            /// Only adds the first `max_branching` children of every node.
+            ///
+            /// This bounds the breadth of the traversal for nodes with
+            /// many children.
+            /// Further children of a [`Node`] are never produced, unless
+            /// [`explain_skip`](Self::explain_skip) is set, which reports every
+            /// further child as [`SkipReason::BreadthLimit`].
+            /// In contrast, [`FastNode::add_children`] still builds and adds
+            /// all children beyond the limit, which are only ignored afterwards,
+            /// so the limit does not save the cost of producing them.
+            /// Children that were already visited and errors count towards
+            /// the limit, but an error returned instead of any children does not.
+            /// Passing [`None`] removes the limit.
+            ///
+            /// [`Node`]: trait@$crate::sync::Node
+            /// [`FastNode::add_children`]: fn@$crate::sync::FastNode::add_children
+            /// [`SkipReason::BreadthLimit`]: enum@$crate::sync::SkipReason
+            /// [`None`]: type@std::option::Option::None
+            #[must_use]
+            pub fn max_branching<M>(mut self, max_branching: M) -> Self
+            where
+                M: Into<Option<usize>>,
+            {
+                self.queue.set_max_branching(max_branching.into());
+                self
+            }
+
+            /// Catches panics while producing the children of a node, so that
+            /// a misbehaving node does not end the traversal.
+            ///
+            /// A panic is converted into a [`PanicError`], which is yielded in
+            /// place of the remaining children of the node, converted into the
+            /// error of the node.
+            /// Children that were produced before the panic are kept.
+            /// The panic hook still runs, so the panic is reported as usual,
+            /// and panics are only caught when the profile unwinds.
+            ///
+            /// Nodes are required to be [`RefUnwindSafe`], since a node is
+            /// still in use after its children panicked, e.g. as a sibling
+            /// or ancestor of other nodes.
+            /// Nodes that share state through interior mutability without
+            /// poisoning, such as a [`RefCell`], may observe broken invariants
+            /// and have to be wrapped in [`AssertUnwindSafe`] explicitly.
+            ///
+            /// [`PanicError`]: struct@$crate::sync::PanicError
+            /// [`RefUnwindSafe`]: trait@std::panic::RefUnwindSafe
+            /// [`RefCell`]: struct@std::cell::RefCell
+            /// [`AssertUnwindSafe`]: struct@std::panic::AssertUnwindSafe
+            #[must_use]
+            pub fn catch_panics(mut self) -> Self
+            where
+                N: std::panic::RefUnwindSafe,
+                N::Error: From<$crate::sync::PanicError>,
+            {
+                self.queue.set_on_panic(Some(N::Error::from));
+                self
+            }
+
+            /// Also yields the roots at depth 0, before any of their descendants.
+            ///
+            /// By default, the roots are only expanded and never yielded.
+            /// With a `max_depth` of `0`, only the roots are yielded and
+            /// nothing is expanded.
+            /// Like any other node, the roots are subject to [`min_depth`]
+            /// and [`yield_if`], and count towards [`max_nodes`].
+            ///
+            /// [`min_depth`]: Self::min_depth
+            /// [`yield_if`]: Self::yield_if
+            /// [`max_nodes`]: Self::max_nodes
+            #[must_use]
+            pub fn include_root(mut self) -> Self {
+                self.queue.set_yield_roots();
+                self
+            }
+
+            /// Stops the traversal once `max_nodes` nodes have been yielded.
+            ///
+            /// In contrast to limiting the yielded nodes with [`Iterator::take`],
+            /// the children of the last node within the limit are not expanded.
+            /// Errors count towards the limit, while nodes that are not yielded
+            /// because of [`min_depth`] or [`yield_if`] do not.
+            /// The limit is shared by all splits of a parallel traversal,
+            /// so it bounds the total number of yielded nodes.
+            ///
+            /// [`Iterator::take`]: method@std::iter::Iterator::take
+            /// [`min_depth`]: Self::min_depth
+            /// [`yield_if`]: Self::yield_if
+            #[must_use]
+            pub fn max_nodes<M>(mut self, max_nodes: M) -> Self
+            where
+                M: Into<Option<usize>>,
+            {
+                self.queue.set_max_nodes(max_nodes.into());
+                self
+            }
+
+            /// Does not expand the children of the nodes for which `predicate`
+            /// returns `true`.
+            ///
+            /// The predicate receives each node and its depth before its children
+            /// are expanded.
+            /// In contrast to filtering the yielded nodes, pruned nodes are still
+            /// yielded while their entire subtrees are skipped, e.g. to skip
+            /// the contents of directories matching a pattern.
+            /// Nodes at the depth limit are not expanded anyway, so the predicate
+            /// is not called for them.
+            /// Pruned nodes are reported to [`explain_skip`](Self::explain_skip)
+            /// as [`SkipReason::Pruned`].
+            ///
+            /// [`SkipReason::Pruned`]: enum@crate::sync::SkipReason
+            #[must_use]
+            pub fn prune<F>(mut self, predicate: F) -> Self
+            where
+                F: FnMut(&N, usize) -> bool + Send + 'static,
+            {
+                self.queue.set_prune(predicate);
+                self
+            }
+
+            /// Only yields the nodes for which `predicate` returns `true`.
+            ///
+            /// The predicate receives each node and its depth.
+            /// In contrast to filtering the yielded nodes, this does not affect
+            /// the expansion of the nodes, so the children of a node are
+            /// traversed even if the node itself is not yielded, e.g. to expand
+            /// all directories but only yield files.
+            /// Nodes that are not yielded do not count as yielded nodes for any
+            /// other option.
+            /// Errors are always yielded.
+            #[must_use]
+            pub fn yield_if<F>(mut self, predicate: F) -> Self
+            where
+                F: Fn(&N, usize) -> bool + Send + Sync + 'static,
+            {
+                self.queue.set_yield_if(predicate);
+                self
+            }
+
+            /// Returns the set of visited nodes.
+            ///
+            /// Nodes are marked as visited when they are queued, so the set
+            /// contains the roots and all yielded nodes, as well as nodes that
+            /// are queued but not yet yielded.
+            /// With `allow_circles`, visited nodes are not tracked and the set
+            /// is empty.
+            /// Nodes that were forgotten by `forget_after_depth` are not included.
+            ///
+            /// Under the `rayon` feature, the set is shared by all splits of
+            /// the traversal and locked for reading until the borrow is dropped.
+            #[inline]
+            pub fn visited(&self) -> impl std::ops::Deref<Target = V> + '_ {
+                self.queue.visited()
+            }
+
+            /// Converts the traversal into its set of visited nodes,
+            /// e.g. to find all nodes reachable from the root once it completed.
+            ///
+            /// See [`visited`](Self::visited) for which nodes are included.
+            /// Under the `rayon` feature, this is a snapshot of the set when
+            /// it is still shared with other splits of the traversal.
+            #[inline]
+            #[must_use]
+            pub fn into_visited(self) -> V
+            where
+                V: Clone,
+            {
+                self.queue.into_visited()
+            }
+
+            /// Yields every distinct node at most once, while still expanding
+            /// every occurrence of it.
+            ///
+            /// Unless `allow_circles` is set, the visited set already ensures
+            /// that every node is queued, and hence yielded and expanded, at
+            /// most once.
+            /// With `allow_circles`, a node that is reachable along more than
+            /// one path is expanded once for every path, e.g. when its
+            /// children depend on the depth, but yielded only the first time
+            /// it is dequeued.
+            /// This keeps a clone of every yielded node, which is shared by
+            /// all splits of a parallel traversal.
+            ///
+            /// Repeated nodes are not yielded, so they do not count towards
+            /// [`max_nodes`].
+            /// Since nodes are still expanded, cycles are only bounded by
+            /// `max_depth`.
+            ///
+            /// [`max_nodes`]: Self::max_nodes
+            #[must_use]
+            pub fn unique(mut self) -> Self {
+                self.queue.set_unique();
+                self
+            }
+
+            /// Yields each node together with its depth.
+            ///
+            /// The children of the root are at depth 1.
+            /// Apart from the type of the yielded items, the traversal is unchanged.
+            #[must_use]
+            pub fn with_depth(self) -> $crate::sync::WithDepth<Self> {
+                $crate::sync::WithDepth(self)
+            }
+
+            /// Yields each node tagged as a [`TraversalEvent`].
+            ///
+            /// Nodes at the depth limit are tagged as [`Frontier`], so they can be
+            /// persisted to resume the traversal later, e.g. with `from_frontier`.
+            /// All other nodes are tagged as [`Visited`].
+            /// Unless `allow_circles` is set, children that are not queued because
+            /// they were already visited are tagged as [`Cycle`] after the node
+            /// whose expansion reached them.
+            ///
+            /// [`TraversalEvent`]: enum@$crate::sync::TraversalEvent
+            /// [`Frontier`]: $crate::sync::TraversalEvent::Frontier
+            /// [`Visited`]: $crate::sync::TraversalEvent::Visited
+            /// [`Cycle`]: $crate::sync::TraversalEvent::Cycle
+            #[must_use]
+            pub fn events(mut self) -> $crate::sync::Events<Self> {
+                self.queue.set_record_cycles();
+                $crate::sync::Events(self)
+            }
+
+            /// Yields runs of consecutive nodes with equal keys as groups.
+            ///
+            /// The key of each node is computed by `key` from the node and its depth.
+            /// Since the order of the traversal is preserved, grouping a BFS by
+            /// depth yields its levels, while a DFS yields every subtree
+            /// contiguously, so keys that are shared within subtrees form a single
+            /// group each.
+            /// Nodes with equal keys that are not consecutive form separate
+            /// groups, see [`collect_groups`](Self::collect_groups) to group
+            /// all nodes instead.
+            /// Errors are yielded immediately and do not end the current group.
+            #[must_use]
+            pub fn group_by<K, F>(self, key: F) -> $crate::sync::GroupBy<Self, N, K, F>
+            where
+                K: PartialEq,
+                F: FnMut(&N, usize) -> K,
+            {
+                $crate::sync::GroupBy {
+                    iter: self,
+                    key,
+                    group: None,
+                }
+            }
+
+            /// Runs the traversal and groups all nodes by their keys.
+            ///
+            /// The key of each node is computed by `key` from the node and its depth.
+            /// In contrast to [`group_by`](Self::group_by), nodes with equal keys
+            /// form a single group, even when they are not consecutive.
+            /// The nodes of each group are in the order they were yielded.
+            ///
+            /// # Errors
+            ///
+            /// Returns the first error encountered while expanding nodes.
+            pub fn collect_groups<K, F>(
+                mut self,
+                mut key: F,
+            ) -> Result<std::collections::HashMap<K, Vec<N>>, N::Error>
+            where
+                K: std::hash::Hash + Eq,
+                F: FnMut(&N, usize) -> K,
+            {
+                let mut groups: std::collections::HashMap<K, Vec<N>> =
+                    std::collections::HashMap::new();
+                while let Some((depth, node, _)) = self.next_node() {
+                    let node = node?;
+                    groups.entry(key(&node, depth)).or_default().push(node);
+                }
+                Ok(groups)
+            }
+
+            /// Runs the traversal and buckets all nodes by their depth.
+            ///
+            /// The depths are the ones yielded by [`with_depth`](Self::with_depth),
+            /// so the children of the root are at depth 1.
+            /// The nodes of each depth are in the order they were yielded.
+            /// In contrast to streaming the levels of a BFS, this allows random
+            /// access by depth once the traversal completed.
+            ///
+            /// # Errors
+            ///
+            /// Returns the first error encountered while expanding nodes.
+            pub fn into_depth_map(
+                mut self,
+            ) -> Result<std::collections::BTreeMap<usize, Vec<N>>, N::Error> {
+                let mut depths: std::collections::BTreeMap<usize, Vec<N>> =
+                    std::collections::BTreeMap::new();
+                while let Some((depth, node, _)) = self.next_node() {
+                    depths.entry(depth).or_default().push(node?);
+                }
+                Ok(depths)
+            }
+
+            /// Yields the path from the root to each node instead of the node.
+            ///
+            /// Each path starts with the root and ends with the yielded node.
+            /// The parent of each node is recorded when the node is queued,
+            /// which keeps a clone of every discovered node and its parent.
+            /// When a node is discovered more than once, e.g. when `allow_circles`
+            /// is set, its first discovery determines its path.
+            ///
+            /// No parents are recorded unless this is called, which
+            /// should be done on a fresh traversal.
+            #[must_use]
+            pub fn with_paths(mut self) -> $crate::sync::WithPaths<Self> {
+                self.queue.track_parents();
+                $crate::sync::WithPaths(self)
+            }
+
+            /// Yields each node together with whether it is the last of its
+            /// siblings in the order in which they are yielded.
+            ///
+            /// This is the information needed to render trees, e.g. to choose
+            /// between `└──` and `├──`.
+            /// Siblings are the children of the same node that were not skipped
+            /// as already visited.
+            /// Siblings that are not yielded because of [`yield_if`] still
+            /// count as siblings.
+            ///
+            /// [`yield_if`]: Self::yield_if
+            #[must_use]
+            pub fn with_last_sibling_flag(mut self) -> $crate::sync::LastSiblingFlag<Self> {
+                self.queue.track_siblings();
+                $crate::sync::LastSiblingFlag(self)
+            }
+
+            /// Forgets visited nodes once the traversal is more than
+            /// `forget_after_depth` levels deeper than the depth at which
+            /// they were first visited.
+            ///
+            /// This bounds the memory of the visited set for deep graphs where
+            /// cycles only occur between nodes that are close to each other.
+            /// Cycles spanning more than `forget_after_depth` levels are no
+            /// longer detected, so nodes can be yielded more than once.
+            ///
+            /// Has no effect when `allow_circles` is set.
+            #[must_use]
+            pub fn prune_visited(mut self, forget_after_depth: usize) -> Self {
+                self.queue.set_forget_after_depth(Some(forget_after_depth));
+                self
+            }
+
+            /// Replaces `allow_circles` with a [`CirclePolicy`], e.g. to only
+            /// track the visited nodes of the upper levels.
+            ///
+            /// With [`CirclePolicy::BelowDepth`], nodes deeper than its depth are
+            /// queued without consulting the visited set, so they can be yielded
+            /// more than once, while the visited set stays bounded by the number
+            /// of nodes of the upper levels.
+            /// Cycles through deeper nodes are only bounded by `max_depth`.
+            ///
+            /// This should be called on a fresh traversal.
+            ///
+            /// [`CirclePolicy`]: enum@$crate::sync::CirclePolicy
+            /// [`CirclePolicy::BelowDepth`]: $crate::sync::CirclePolicy::BelowDepth
+            #[must_use]
+            pub fn circle_policy<P>(mut self, circle_policy: P) -> Self
+            where
+                P: Into<$crate::sync::CirclePolicy>,
+            {
+                self.queue.set_circle_policy(circle_policy.into());
+                self
+            }
+
+            /// Repeats the traversal until a full pass discovers no new nodes.
+            ///
+            /// This is intended for fixpoint computations, where the children
+            /// of a node depend on shared state that grows monotonically while
+            /// the graph is traversed, e.g. reachability analyses.
+            /// Every pass starts again from the root and each pass has its own
+            /// visited set, so nodes discovered in an earlier pass are expanded
+            /// again and their new children are found.
+            /// The passes stop when the set of all discovered nodes did not grow
+            /// during a pass or after `max_iterations` passes, whichever comes first.
+            /// The root is not part of the returned set.
+            ///
+            /// Within a pass, `allow_circles` behaves as for a single traversal,
+            /// hence it should only be set when the graph is acyclic or
+            /// `max_depth` is bounded, as otherwise a pass never terminates.
+            ///
+            /// This should be called on a fresh traversal.
+            ///
+            /// # Errors
+            ///
+            /// Returns the first error encountered while expanding nodes.
+            pub fn run_to_fixpoint(
+                self,
+                max_iterations: usize,
+            ) -> Result<::std::collections::HashSet<N>, N::Error>
+            where
+                V: Default,
+            {
+                let queue = self.queue.restart();
+                let max_depth = self.max_depth;
+                let mut discovered = ::std::collections::HashSet::new();
+                let mut pass = Some(self);
+                for _ in 0..max_iterations {
+                    let pass = pass.take().unwrap_or_else(|| Self {
+                        queue: queue.restart(),
+                        max_depth,
+                        stats: $crate::sync::Stats::default(),
+                    });
+                    let before = discovered.len();
+                    for node in pass {
+                        discovered.insert(node?);
+                    }
+                    if discovered.len() == before {
+                        break;
+                    }
+                }
+                Ok(discovered)
+            }
+
+            /// Yields only the nodes that succeeded and collects all errors
+            /// into a shared sidecar [`Vec`].
+            ///
+            /// This allows iterating over bare nodes while still being able to
+            /// inspect all errors once the traversal finished.
+            /// Errors are pushed in the order in which they are encountered.
+            ///
+            /// [`Vec`]: struct@std::vec::Vec
+            pub fn split_errors(
+                self,
+            ) -> (
+                impl Iterator<Item = N>,
+                ::std::sync::Arc<::std::sync::Mutex<Vec<N::Error>>>,
+            ) {
+                let errors = ::std::sync::Arc::new(::std::sync::Mutex::new(Vec::new()));
+                let sidecar = ::std::sync::Arc::clone(&errors);
+                let nodes = self.filter_map(move |node| match node {
+                    Ok(node) => Some(node),
+                    Err(err) => {
+                        sidecar.lock().unwrap().push(err);
+                        None
+                    }
+                });
+                (nodes, errors)
+            }
+        }
+    };
+}
+pub(crate) use traversal_options;
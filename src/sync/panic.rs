@@ -0,0 +1,51 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// The error of a node whose children panicked while being produced.
+///
+/// Only produced when panics are caught, see `catch_panics`.
+/// Convert it into the error of the node by implementing [`From<PanicError>`].
+///
+/// [`From<PanicError>`]: trait@std::convert::From
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq, Hash)]
+#[error("children at depth {depth} panicked: {message}")]
+pub struct PanicError {
+    /// The depth of the children that panicked.
+    pub depth: usize,
+    /// The message of the panic, if it was a string.
+    pub message: String,
+}
+
+impl PanicError {
+    #[inline]
+    fn new(depth: usize, payload: &(dyn Any + Send)) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        Self { depth, message }
+    }
+}
+
+/// Runs `expand` for the children at `depth`, converting a panic into an
+/// error with `on_panic`, if any.
+///
+/// Children are added to the queue one at a time, so the queue stays
+/// consistent when producing a child panics and is asserted to be unwind
+/// safe, while `catch_panics` requires the node itself to be.
+#[inline]
+pub(super) fn catch<T, E, F>(
+    on_panic: Option<fn(PanicError) -> E>,
+    depth: usize,
+    expand: F,
+) -> Result<T, E>
+where
+    F: FnOnce() -> Result<T, E>,
+{
+    match on_panic {
+        None => expand(),
+        Some(on_panic) => panic::catch_unwind(AssertUnwindSafe(expand))
+            .unwrap_or_else(|payload| Err(on_panic(PanicError::new(depth, &*payload)))),
+    }
+}
@@ -7,12 +7,43 @@
 //! [`ParallelSplittableIterator`]
 //! implements [`rayon::iter::ParallelIterator`].
 //!
+//! ### Visited nodes
+//!
+//! All splits of a traversal share one visited set behind a lock, so unless
+//! `allow_circles` is set, every node is yielded at most once across all
+//! threads.
+//! Which split visits a node first, and hence the order in which nodes are
+//! yielded, is nondeterministic.
+//! Clones of a traversal do not share their visited set.
+//!
+//! With the `dashmap` feature, the `dedup_parallel` method of the traversals
+//! instead returns a [`DedupParallel`] traversal, which tracks visited nodes
+//! in a concurrent set to reduce the contention of the lock.
+//!
+//! ### Ordered output
+//!
+//! By default, items are produced in the order in which the threads complete
+//! them, which is fine for e.g. `count()` but not reproducible.
+//! The `into_par_iter_ordered` method of the traversals instead returns an
+//! [`OrderedParallelSplittableIterator`], which yields the items in the order
+//! of the sequential traversal.
+//! This only holds when the visited set does not change which nodes are
+//! yielded, i.e. for trees or when `allow_circles` is set.
+//!
+//! ### Stats
+//!
+//! Each split of a traversal keeps its own `stats`, starting from zero.
+//! They are not merged back, hence the stats of the original traversal
+//! only cover the nodes it yielded itself.
+//!
 //! ### Acknowledgements
 //!
 //! This approach is taken from the amazing [blog post by tavianator](https://tavianator.com/2022/parallel_graph_search.html).
 //!
 //! [`rayon`]: mod@rayon
 //! [`ParallelSplittableIterator`]: struct@self::ParallelSplittableIterator
+//! [`OrderedParallelSplittableIterator`]: struct@self::OrderedParallelSplittableIterator
+//! [`DedupParallel`]: struct@self::DedupParallel
 //! [`SplittableIterator`]: trait@self::SplittableIterator
 //! [`rayon::iter::ParallelIterator`]: trait@rayon::iter::ParallelIterator
 
@@ -34,6 +65,39 @@ pub trait SplittableIterator: Iterator + Sized {
     /// [`None`]: type@std::option::Option::None
     /// [`self`]: trait@self::SplittableIterator
     fn split(&mut self) -> Option<Self>;
+
+    /// Split this iterator in two, if it holds at least `min_len` items.
+    ///
+    /// Defaults to [`SplittableIterator::split`], which ignores `min_len`,
+    /// for iterators that do not know how many items they hold.
+    ///
+    /// [`SplittableIterator::split`]: method@self::SplittableIterator::split
+    fn split_min_len(&mut self, min_len: usize) -> Option<Self> {
+        let _ = min_len;
+        self.split()
+    }
+}
+
+/// A [`SplittableIterator`] whose splits can be put back into sequential order.
+pub trait OrderedSplittableIterator: SplittableIterator {
+    /// Whether the items of a split precede the items remaining in the
+    /// iterator in the sequential order.
+    const SPLIT_FIRST: bool;
+
+    /// Returns the next item along with its level.
+    ///
+    /// In the sequential order, items of a lower level precede
+    /// items of a higher level, regardless of the split that produced them.
+    fn next_with_level(&mut self) -> Option<(usize, Self::Item)>;
+
+    /// Split this iterator in two, if the sequential order can be restored.
+    ///
+    /// Defaults to [`SplittableIterator::split`].
+    ///
+    /// [`SplittableIterator::split`]: method@self::SplittableIterator::split
+    fn split_ordered(&mut self) -> Option<Self> {
+        self.split()
+    }
 }
 
 /// Converts a [`SplittableIterator`] into a [`rayon::iter::ParallelIterator`].
@@ -55,10 +119,27 @@ where
     }
 }
 
+/// The minimum number of items of an iterator that is split by default.
+pub(crate) const MIN_SPLIT_LEN: usize = 2;
+
 /// A bridge from a [`SplittableIterator`] to a [`rayon::iter::ParallelIterator`].
+///
+/// The granularity of the splits can be tuned with [`min_split_len`] and
+/// [`splits`], e.g. to split less often when every item is expensive.
+///
+/// Splitting is thief-driven: every split halves the remaining splits,
+/// but whenever another thread steals a half, the splits of that half are
+/// reset to [`steal_reset_splits`], so that idle threads keep splitting
+/// work off until the pool is busy.
+///
+/// [`min_split_len`]: Self::min_split_len
+/// [`splits`]: Self::splits
+/// [`steal_reset_splits`]: Self::steal_reset_splits
 pub struct ParallelSplittableIterator<Iter> {
     iter: Iter,
     splits: usize,
+    min_split_len: usize,
+    steal_reset_splits: Option<usize>,
 }
 
 impl<Iter> ParallelSplittableIterator<Iter>
@@ -67,24 +148,71 @@ where
 {
     /// Creates a new [`ParallelSplittableIterator`] bridge from a [`SplittableIterator`].
     pub fn new(iter: Iter) -> Self {
+        Self::with_config(iter, MIN_SPLIT_LEN, current_num_threads())
+    }
+
+    /// Creates a new [`ParallelSplittableIterator`] bridge from a [`SplittableIterator`]
+    /// with a custom granularity.
+    ///
+    /// The iterator is only split while it holds at least `min_split_len` items,
+    /// and up to `splits` times before it is consumed sequentially.
+    /// By default, iterators of two items are split, starting with one split
+    /// per thread of the pool.
+    pub fn with_config(iter: Iter, min_split_len: usize, splits: usize) -> Self {
         Self {
             iter,
-            splits: current_num_threads(),
+            splits,
+            min_split_len,
+            steal_reset_splits: None,
         }
     }
 
+    /// Only splits the iterator while it holds at least `min_split_len` items.
+    ///
+    /// Defaults to 2.
+    /// Raising it avoids the overhead of splitting small iterators,
+    /// e.g. when every item is expensive.
+    #[must_use]
+    pub fn min_split_len(mut self, min_split_len: usize) -> Self {
+        self.min_split_len = min_split_len;
+        self
+    }
+
+    /// Starts with `splits` splits before the iterator is consumed sequentially.
+    ///
+    /// Defaults to the number of threads of the current pool.
+    /// Every split halves the remaining splits of both halves.
+    #[must_use]
+    pub fn splits(mut self, splits: usize) -> Self {
+        self.splits = splits;
+        self
+    }
+
+    /// Resets the splits to `splits` whenever a half is stolen by another thread.
+    ///
+    /// Defaults to the number of threads of the current pool.
+    /// Lowering it avoids over-splitting very unbalanced graphs, where the
+    /// thieves only find little work, e.g. on NUMA or pinned thread pools.
+    #[must_use]
+    pub fn steal_reset_splits(mut self, splits: usize) -> Self {
+        self.steal_reset_splits = Some(splits);
+        self
+    }
+
     /// Split the underlying iterator in half.
     fn split(&mut self) -> Option<Self> {
         if self.splits == 0 {
             return None;
         }
 
-        if let Some(split) = self.iter.split() {
+        if let Some(split) = self.iter.split_min_len(self.min_split_len) {
             self.splits /= 2;
 
             Some(Self {
                 iter: split,
                 splits: self.splits,
+                min_split_len: self.min_split_len,
+                steal_reset_splits: self.steal_reset_splits,
             })
         } else {
             None
@@ -102,7 +230,7 @@ where
         // Thief-splitting: start with enough splits to fill the thread pool,
         // and reset every time a job is stolen by another thread.
         if stolen {
-            self.splits = current_num_threads();
+            self.splits = self.steal_reset_splits.unwrap_or_else(current_num_threads);
         }
 
         let mut folder = consumer.split_off_left().into_folder();
@@ -151,22 +279,200 @@ where
     }
 }
 
+/// A bridge from an [`OrderedSplittableIterator`] to a
+/// [`rayon::iter::ParallelIterator`] that preserves the sequential order.
+///
+/// Each split buffers its items tagged with their level, and the reducer
+/// merges the buffers of two splits back into sequential order.
+/// Hence, all items of the traversal are held in memory at once and
+/// none are passed on before the traversal completes.
+/// Use the unordered [`ParallelSplittableIterator`] unless the order matters.
+pub struct OrderedParallelSplittableIterator<Iter> {
+    iter: Iter,
+    splits: usize,
+}
+
+impl<Iter> OrderedParallelSplittableIterator<Iter>
+where
+    Iter: OrderedSplittableIterator,
+{
+    /// Creates a new [`OrderedParallelSplittableIterator`] bridge from an
+    /// [`OrderedSplittableIterator`].
+    pub fn new(iter: Iter) -> Self {
+        Self {
+            iter,
+            splits: current_num_threads(),
+        }
+    }
+
+    /// Split the underlying iterator in half.
+    fn split(&mut self) -> Option<Self> {
+        if self.splits == 0 {
+            return None;
+        }
+
+        let split = self.iter.split_ordered()?;
+        self.splits /= 2;
+        Some(Self {
+            iter: split,
+            splits: self.splits,
+        })
+    }
+
+    /// Collects the items in sequential order, along with their level.
+    fn buffer(&mut self, stolen: bool) -> Vec<(usize, Iter::Item)>
+    where
+        Iter: Send,
+        Iter::Item: Send,
+    {
+        // Thief-splitting, as in the unordered bridge
+        if stolen {
+            self.splits = current_num_threads();
+        }
+
+        let mut items = Vec::new();
+        loop {
+            if let Some(mut split) = self.split() {
+                let (rest, split) = join_context(
+                    |ctx| self.buffer(ctx.migrated()),
+                    |ctx| split.buffer(ctx.migrated()),
+                );
+                let (first, second) = if Iter::SPLIT_FIRST {
+                    (split, rest)
+                } else {
+                    (rest, split)
+                };
+                return merge(merge(items, first), second);
+            }
+
+            match self.iter.next_with_level() {
+                Some(item) => items.push(item),
+                None => return items,
+            }
+        }
+    }
+}
+
+/// A traversal whose splits track visited nodes in a shared concurrent set.
+///
+/// Created by the `dedup_parallel` method of the traversals, and intended
+/// to be parallelized with `into_par_iter`.
+///
+/// Splits of a traversal always share one visited set, so every node is
+/// yielded at most once either way.
+/// The default visited set is guarded by a [`RwLock`]: checking a node only
+/// takes the shared lock, but marking a node as visited takes the exclusive
+/// lock, which blocks all other threads.
+/// When most nodes are new, e.g. in wide graphs, threads mostly wait for
+/// each other.
+/// A [`DashSet`] instead only locks one of its shards to insert a node, so
+/// threads only contend when they visit nodes of the same shard at the same time.
+/// In turn, every check hashes the node into a shard and the set uses more
+/// memory, so the default set is faster for few threads or mostly revisited
+/// nodes.
+///
+/// The visited set of the traversal is no longer updated, and
+/// `prune_visited` has no effect.
+///
+/// [`RwLock`]: struct@std::sync::RwLock
+/// [`DashSet`]: struct@dashmap::DashSet
+#[cfg(feature = "dashmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dashmap")))]
+#[derive(Debug)]
+pub struct DedupParallel<I>(pub(super) I);
+
+/// Merges two sequences of items that are sorted by level.
+///
+/// Items of `first` precede items of `second` of the same level.
+fn merge<T>(mut first: Vec<(usize, T)>, second: Vec<(usize, T)>) -> Vec<(usize, T)> {
+    match (first.last(), second.first()) {
+        (Some((last, _)), Some((next, _))) if last > next => {}
+        _ => {
+            first.extend(second);
+            return first;
+        }
+    }
+    let mut merged = Vec::with_capacity(first.len() + second.len());
+    let mut first = first.into_iter().peekable();
+    let mut second = second.into_iter().peekable();
+    while let (Some((a, _)), Some((b, _))) = (first.peek(), second.peek()) {
+        let item = if a <= b { first.next() } else { second.next() };
+        merged.extend(item);
+    }
+    merged.extend(first);
+    merged.extend(second);
+    merged
+}
+
+impl<Iter> ParallelIterator for OrderedParallelSplittableIterator<Iter>
+where
+    Iter: OrderedSplittableIterator + Send,
+    Iter::Item: Send,
+{
+    type Item = Iter::Item;
+
+    fn drive_unindexed<C>(mut self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let items = self.buffer(false);
+        rayon::iter::IntoParallelIterator::into_par_iter(items)
+            .map(|(_, item)| item)
+            .drive_unindexed(consumer)
+    }
+}
+
+/// Describes the sequential order of a traversal to the ordered bridge.
+macro_rules! traversal_order {
+    // a split takes the top of the stack, which is traversed first
+    (@split_first depth_first) => {
+        true
+    };
+    // a split takes the back of the queue, which is traversed last
+    (@split_first breadth_first) => {
+        false
+    };
+    (@level depth_first, $depth:expr) => {{
+        let _ = $depth;
+        0
+    }};
+    (@level breadth_first, $depth:expr) => {
+        $depth
+    };
+    (@splittable depth_first, $queue:expr) => {
+        true
+    };
+    // the children of both halves would interleave
+    // unless all queued nodes are of the same depth
+    (@splittable breadth_first, $queue:expr) => {
+        $queue.front_depth() == $queue.back_depth()
+    };
+}
+pub(crate) use traversal_order;
+
 macro_rules! parallel_iterator {
-    ($iter:ident<$node:ident>) => {
-        impl<N> $crate::sync::par::SplittableIterator for $iter<N>
+    ($iter:ident<$node:ident>, $order:ident) => {
+        impl<N, V, D> $crate::sync::par::SplittableIterator for $iter<N, V, D>
         where
-            N: $node,
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
         {
             fn split(&mut self) -> Option<Self> {
+                self.split_min_len($crate::sync::par::MIN_SPLIT_LEN)
+            }
+
+            fn split_min_len(&mut self, min_len: usize) -> Option<Self> {
                 use $crate::sync::Queue;
                 let len = self.queue.len();
-                if len >= 2 {
+                if len >= min_len.max($crate::sync::par::MIN_SPLIT_LEN) {
                     let split = self.queue.split_off(len / 2);
+                    // both halves share the visited set of the queue,
+                    // so no node is yielded twice unless circles are allowed
                     Some(Self {
                         queue: split,
-                        // visited: self.visited.clone(),
                         max_depth: self.max_depth,
-                        // allow_circles: self.allow_circles,
+                        stats: $crate::sync::Stats::default(),
                     })
                 } else {
                     None
@@ -174,10 +480,12 @@ macro_rules! parallel_iterator {
             }
         }
 
-        impl<N> rayon::iter::IntoParallelIterator for $iter<N>
+        impl<N, V, D> rayon::iter::IntoParallelIterator for $iter<N, V, D>
         where
-            N: $node + Sync + Send,
+            N: $node<D> + Sync + Send,
             N::Error: Send,
+            V: $crate::sync::VisitedSet<N> + Sync + Send,
+            D: $crate::sync::Depth,
         {
             type Iter = $crate::sync::par::ParallelSplittableIterator<Self>;
             type Item = <Self as Iterator>::Item;
@@ -186,6 +494,195 @@ macro_rules! parallel_iterator {
                 $crate::sync::par::ParallelSplittableIterator::new(self)
             }
         }
+
+        impl<N, V, D> $crate::sync::par::OrderedSplittableIterator for $iter<N, V, D>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            const SPLIT_FIRST: bool = $crate::sync::par::traversal_order!(@split_first $order);
+
+            fn next_with_level(&mut self) -> Option<(usize, Self::Item)> {
+                let (depth, node, _) = self.next_node()?;
+                Some(($crate::sync::par::traversal_order!(@level $order, depth), node))
+            }
+
+            fn split_ordered(&mut self) -> Option<Self> {
+                if !$crate::sync::par::traversal_order!(@splittable $order, self.queue) {
+                    return None;
+                }
+                $crate::sync::par::SplittableIterator::split(self)
+            }
+        }
+
+        impl<N, V, D> $iter<N, V, D>
+        where
+            N: $node<D> + Sync + Send,
+            N::Error: Send,
+            V: $crate::sync::VisitedSet<N> + Sync + Send,
+            D: $crate::sync::Depth,
+        {
+            /// Parallelizes this traversal while preserving its sequential order.
+            ///
+            /// Unlike `into_par_iter`, all nodes are buffered until the traversal
+            /// completes. See [`OrderedParallelSplittableIterator`] for details.
+            ///
+            /// [`OrderedParallelSplittableIterator`]: struct@$crate::sync::par::OrderedParallelSplittableIterator
+            pub fn into_par_iter_ordered(
+                self,
+            ) -> $crate::sync::par::OrderedParallelSplittableIterator<Self> {
+                $crate::sync::par::OrderedParallelSplittableIterator::new(self)
+            }
+        }
+
+        #[cfg(feature = "dashmap")]
+        impl<N, V, D> $iter<N, V, D>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            /// Tracks visited nodes in a concurrent set that is shared by all
+            /// splits of a parallel traversal.
+            ///
+            /// The roots are visited, so this must be called on a fresh traversal.
+            /// Has no effect when `allow_circles` is set.
+            /// See [`DedupParallel`] for when this reduces contention.
+            ///
+            /// [`DedupParallel`]: struct@$crate::sync::par::DedupParallel
+            #[must_use]
+            pub fn dedup_parallel(mut self) -> $crate::sync::par::DedupParallel<Self> {
+                self.queue.set_concurrent_visited();
+                $crate::sync::par::DedupParallel(self)
+            }
+        }
+
+        #[cfg(feature = "dashmap")]
+        impl<N, V, D> Iterator for $crate::sync::par::DedupParallel<$iter<N, V, D>>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            type Item = Result<N, N::Error>;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next()
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        #[cfg(feature = "dashmap")]
+        impl<N, V, D> $crate::sync::par::SplittableIterator
+            for $crate::sync::par::DedupParallel<$iter<N, V, D>>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            fn split(&mut self) -> Option<Self> {
+                self.0.split().map($crate::sync::par::DedupParallel)
+            }
+
+            fn split_min_len(&mut self, min_len: usize) -> Option<Self> {
+                self.0
+                    .split_min_len(min_len)
+                    .map($crate::sync::par::DedupParallel)
+            }
+        }
+
+        #[cfg(feature = "dashmap")]
+        impl<N, V, D> rayon::iter::IntoParallelIterator
+            for $crate::sync::par::DedupParallel<$iter<N, V, D>>
+        where
+            N: $node<D> + Sync + Send,
+            N::Error: Send,
+            V: $crate::sync::VisitedSet<N> + Sync + Send,
+            D: $crate::sync::Depth,
+        {
+            type Iter = $crate::sync::par::ParallelSplittableIterator<Self>;
+            type Item = <Self as Iterator>::Item;
+
+            fn into_par_iter(self) -> Self::Iter {
+                $crate::sync::par::ParallelSplittableIterator::new(self)
+            }
+        }
+
+        impl<N, V, D> $crate::sync::par::SplittableIterator
+            for $crate::sync::WithDepth<$iter<N, V, D>>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            fn split(&mut self) -> Option<Self> {
+                self.0.split().map($crate::sync::WithDepth)
+            }
+
+            fn split_min_len(&mut self, min_len: usize) -> Option<Self> {
+                self.0.split_min_len(min_len).map($crate::sync::WithDepth)
+            }
+        }
+
+        impl<N, V, D> rayon::iter::IntoParallelIterator for $crate::sync::WithDepth<$iter<N, V, D>>
+        where
+            N: $node<D> + Sync + Send,
+            N::Error: Send,
+            V: $crate::sync::VisitedSet<N> + Sync + Send,
+            D: $crate::sync::Depth,
+        {
+            type Iter = $crate::sync::par::ParallelSplittableIterator<Self>;
+            type Item = <Self as Iterator>::Item;
+
+            fn into_par_iter(self) -> Self::Iter {
+                $crate::sync::par::ParallelSplittableIterator::new(self)
+            }
+        }
+
+        impl<N, V, D> $crate::sync::par::OrderedSplittableIterator
+            for $crate::sync::WithDepth<$iter<N, V, D>>
+        where
+            N: $node<D>,
+            V: $crate::sync::VisitedSet<N>,
+            D: $crate::sync::Depth,
+        {
+            const SPLIT_FIRST: bool = $crate::sync::par::traversal_order!(@split_first $order);
+
+            fn next_with_level(&mut self) -> Option<(usize, Self::Item)> {
+                let (depth, node, _) = self.0.next_node()?;
+                let level = $crate::sync::par::traversal_order!(@level $order, depth);
+                Some((level, node.map(|node| (depth, node))))
+            }
+
+            fn split_ordered(&mut self) -> Option<Self> {
+                self.0.split_ordered().map($crate::sync::WithDepth)
+            }
+        }
+
+        impl<N, V, D> $crate::sync::WithDepth<$iter<N, V, D>>
+        where
+            N: $node<D> + Sync + Send,
+            N::Error: Send,
+            V: $crate::sync::VisitedSet<N> + Sync + Send,
+            D: $crate::sync::Depth,
+        {
+            /// Parallelizes this traversal while preserving its sequential order.
+            ///
+            /// See [`OrderedParallelSplittableIterator`] for details.
+            ///
+            /// [`OrderedParallelSplittableIterator`]: struct@$crate::sync::par::OrderedParallelSplittableIterator
+            pub fn into_par_iter_ordered(
+                self,
+            ) -> $crate::sync::par::OrderedParallelSplittableIterator<Self> {
+                $crate::sync::par::OrderedParallelSplittableIterator::new(self)
+            }
+        }
     };
 }
 pub(crate) use parallel_iterator;
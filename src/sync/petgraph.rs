@@ -0,0 +1,200 @@
+//! Support for traversing [`petgraph`] graphs.
+//!
+//! A [`PetNode`] is the index of a node together with a reference to the graph
+//! it belongs to, and implements [`FastNode`] by adding the neighbors of the node.
+//! Any of the synchronous traversals, including the parallel traversals, can
+//! hence be used on graphs such as [`petgraph::Graph`].
+//!
+//! ### Ownership
+//!
+//! Every node borrows the graph for the lifetime `'g`, so nodes are cheap
+//! to copy and the graph is never cloned.
+//! The graph must outlive the traversal, and the parallel traversals require
+//! the graph to be [`Sync`].
+//! Traversals that move nodes to other threads for an unbounded time, such as
+//! `into_channel_iter`, require a `&'static` graph, e.g. one created with
+//! [`Box::leak`].
+//!
+//! Two nodes are equal when their indices are equal, regardless of the graph.
+//!
+//! [`petgraph`]: mod@petgraph
+//! [`PetNode`]: struct@self::PetNode
+//! [`FastNode`]: trait@crate::sync::FastNode
+//! [`petgraph::Graph`]: struct@petgraph::Graph
+//! [`Sync`]: trait@std::marker::Sync
+//! [`Box::leak`]: fn@std::boxed::Box::leak
+
+use super::{ExtendQueue, FastNode};
+use ::petgraph::visit::{GraphBase, IntoNeighbors};
+use std::hash::{Hash, Hasher};
+
+/// A node of a borrowed [`petgraph`] graph.
+///
+/// ### Example
+/// ```
+/// use par_dfs::sync::{FastDfs, petgraph::PetNode};
+/// use petgraph::Graph;
+///
+/// let mut graph = Graph::<&str, ()>::new();
+/// let a = graph.add_node("a");
+/// let b = graph.add_node("b");
+/// let c = graph.add_node("c");
+/// graph.extend_with_edges([(a, b), (b, c), (c, a)]);
+///
+/// let labels = FastDfs::<PetNode<_>>::new(PetNode::new(&graph, a), None, false)
+///     .map(|node| node.map(|node| graph[node.id()]))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(labels, ["b", "c"]);
+/// ```
+///
+/// [`petgraph`]: mod@petgraph
+pub struct PetNode<'g, G>
+where
+    G: GraphBase,
+{
+    graph: &'g G,
+    id: G::NodeId,
+}
+
+impl<'g, G> PetNode<'g, G>
+where
+    G: GraphBase,
+{
+    /// Creates a new [`PetNode`] for the node `id` of `graph`.
+    ///
+    /// [`PetNode`]: struct@self::PetNode
+    #[inline]
+    pub fn new(graph: &'g G, id: G::NodeId) -> Self {
+        Self { graph, id }
+    }
+
+    /// Returns the index of this node.
+    #[inline]
+    pub fn id(&self) -> G::NodeId {
+        self.id
+    }
+
+    /// Returns the graph of this node.
+    #[inline]
+    pub fn graph(&self) -> &'g G {
+        self.graph
+    }
+}
+
+impl<'g, G> FastNode for PetNode<'g, G>
+where
+    G: GraphBase,
+    G::NodeId: Hash + Eq + std::fmt::Debug,
+    &'g G: IntoNeighbors<NodeId = G::NodeId>,
+{
+    type Error = std::convert::Infallible;
+
+    #[inline]
+    fn add_children<E>(&self, _depth: usize, queue: &mut E) -> Result<(), Self::Error>
+    where
+        E: ExtendQueue<Self, Self::Error>,
+    {
+        let graph = self.graph;
+        queue.add_all(graph.neighbors(self.id).map(|id| Ok(Self::new(graph, id))));
+        Ok(())
+    }
+}
+
+impl<G> Clone for PetNode<'_, G>
+where
+    G: GraphBase,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<G> Copy for PetNode<'_, G> where G: GraphBase {}
+
+impl<G> PartialEq for PetNode<'_, G>
+where
+    G: GraphBase,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<G> Eq for PetNode<'_, G>
+where
+    G: GraphBase,
+    G::NodeId: Eq,
+{
+}
+
+impl<G> Hash for PetNode<'_, G>
+where
+    G: GraphBase,
+    G::NodeId: Hash,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<G> std::fmt::Debug for PetNode<'_, G>
+where
+    G: GraphBase,
+    G::NodeId: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PetNode").field(&self.id).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PetNode;
+    use crate::sync::{FastBfs, FastDfs};
+    use anyhow::Result;
+    use petgraph::Graph;
+
+    #[test]
+    fn test_petgraph_traversals() -> Result<()> {
+        // 0 → 1 → 3, 0 → 2 → 3 → 0
+        let mut graph = Graph::<usize, ()>::new();
+        let nodes = (0..4).map(|i| graph.add_node(i)).collect::<Vec<_>>();
+        graph.extend_with_edges([
+            (nodes[0], nodes[1]),
+            (nodes[0], nodes[2]),
+            (nodes[1], nodes[3]),
+            (nodes[2], nodes[3]),
+            (nodes[3], nodes[0]),
+        ]);
+        let root = PetNode::new(&graph, nodes[0]);
+
+        let bfs = FastBfs::<PetNode<_>>::new(root, None, false)
+            .map(|node| node.map(|node| graph[node.id()]))
+            .collect::<Result<Vec<_>, _>>()?;
+        // petgraph yields the most recently added edges first
+        similar_asserts::assert_eq!(bfs, [2, 1, 3]);
+
+        let mut dfs = FastDfs::<PetNode<_>>::new(root, None, false)
+            .map(|node| node.map(|node| graph[node.id()]))
+            .collect::<Result<Vec<_>, _>>()?;
+        dfs.sort_unstable();
+        similar_asserts::assert_eq!(dfs, [1, 2, 3]);
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+            let mut par = FastDfs::<PetNode<_>>::new(root, None, false)
+                .into_par_iter()
+                .map(|node| node.map(|node| graph[node.id()]))
+                .collect::<Result<Vec<_>, _>>()?;
+            par.sort_unstable();
+            similar_asserts::assert_eq!(par, [1, 2, 3]);
+        }
+        Ok(())
+    }
+}
@@ -1,98 +1,657 @@
-use std::collections::{HashSet, VecDeque};
+use super::{CirclePolicy, Depth, QueueBackend, SkipReason, VisitedSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
 #[cfg(feature = "rayon")]
-use std::sync::{Arc, RwLock};
+use std::sync::RwLock;
+use std::sync::{Arc, Mutex, PoisonError};
 
+type SkipFn<I> = dyn FnMut(&I, SkipReason) + Send;
+
+/// A shared callback that is notified whenever a node is skipped.
+pub(super) struct SkipHook<I>(Arc<Mutex<SkipFn<I>>>);
+
+impl<I> SkipHook<I> {
+    #[inline]
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: FnMut(&I, SkipReason) + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(hook)))
+    }
+
+    /// A callback that panicked before is still called, so that panics can be caught.
+    #[inline]
+    fn call(&self, item: &I, reason: SkipReason) {
+        let mut hook = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        (*hook)(item, reason);
+    }
+}
+
+impl<I> Clone for SkipHook<I> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<I> std::fmt::Debug for SkipHook<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SkipHook").finish_non_exhaustive()
+    }
+}
+
+type PruneFn<I> = dyn FnMut(&I, usize) -> bool + Send;
+
+/// A shared predicate that decides whether the children of a node are skipped.
+struct PruneHook<I>(Arc<Mutex<PruneFn<I>>>);
+
+impl<I> Clone for PruneHook<I> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<I> std::fmt::Debug for PruneHook<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PruneHook").finish_non_exhaustive()
+    }
+}
+
+type ChildrenFn<I, E, D> = dyn FnMut(&I, D) -> super::NodeIter<I, E> + Send;
+
+/// A shared closure that produces the children of an item in place of the item itself.
+pub(super) struct ChildrenHook<I, E, D>(Arc<Mutex<ChildrenFn<I, E, D>>>);
+
+impl<I, E, D> ChildrenHook<I, E, D> {
+    /// Returns the children of `item` at `depth`.
+    ///
+    /// A closure that panicked before is still called, so that panics can be caught.
+    #[inline]
+    pub fn call(&self, item: &I, depth: D) -> super::NodeIter<I, E> {
+        let mut children = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        (*children)(item, depth)
+    }
+}
+
+impl<I, E, D> Clone for ChildrenHook<I, E, D> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<I, E, D> std::fmt::Debug for ChildrenHook<I, E, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChildrenHook").finish_non_exhaustive()
+    }
+}
+
+type YieldFn<I> = dyn Fn(&I, usize) -> bool + Send + Sync;
+
+/// A shared predicate that decides whether a node is yielded.
+struct YieldFilter<I>(Arc<YieldFn<I>>);
+
+impl<I> Clone for YieldFilter<I> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<I> std::fmt::Debug for YieldFilter<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("YieldFilter").finish_non_exhaustive()
+    }
+}
+
+/// A maximum number of yielded items that is shared by all splits of a queue.
+#[derive(Debug)]
+struct NodeBudget {
+    max_nodes: usize,
+    count: Arc<AtomicUsize>,
+}
+
+impl NodeBudget {
+    #[inline]
+    fn new(max_nodes: usize) -> Self {
+        Self {
+            max_nodes,
+            count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns a handle to the same budget, used by a split of the queue.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    fn share(&self) -> Self {
+        Self {
+            max_nodes: self.max_nodes,
+            count: Arc::clone(&self.count),
+        }
+    }
+}
+
+impl Clone for NodeBudget {
+    /// Returns an independent budget with the same number of used items.
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            max_nodes: self.max_nodes,
+            count: Arc::new(AtomicUsize::new(self.count.load(Ordering::Relaxed))),
+        }
+    }
+}
+
+/// Items that were already yielded, used to yield every item at most once.
+///
+/// In contrast to the visited set, which decides whether an item is queued
+/// and hence also expanded, this only decides whether a dequeued item is
+/// yielded, so repeated items are still expanded.
+#[derive(Debug)]
+struct Emitted<I>(Arc<Mutex<HashSet<I>>>);
+
+impl<I> Emitted<I>
+where
+    I: Hash + Eq + Clone,
+{
+    #[inline]
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(HashSet::new())))
+    }
+
+    /// Marks `item` as yielded and returns whether it was not yielded before.
+    #[inline]
+    fn insert(&self, item: &I) -> bool {
+        let mut emitted = self.0.lock().unwrap();
+        !emitted.contains(item) && emitted.insert(item.clone())
+    }
+}
+
+impl<I> Emitted<I> {
+    /// Returns a handle to the same set, used by a split of the queue.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    fn share(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<I> Clone for Emitted<I>
+where
+    I: Clone,
+{
+    /// Returns an independent set with the same yielded items.
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::new(Mutex::new(self.0.lock().unwrap().clone())))
+    }
+}
+
+/// A concurrent set of visited items that is shared by all splits of a queue.
+#[cfg(feature = "dashmap")]
+struct ConcurrentVisited<I>(Arc<dashmap::DashSet<I>>);
+
+#[cfg(feature = "dashmap")]
+impl<I> Clone for ConcurrentVisited<I> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+#[cfg(feature = "dashmap")]
+impl<I> std::fmt::Debug for ConcurrentVisited<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConcurrentVisited").finish_non_exhaustive()
+    }
+}
+
+/// Visited nodes, and the depth at which they were first added
+/// when visited nodes are forgotten.
 #[derive(Debug, Clone)]
-pub(super) struct Queue<I, E> {
-    inner: VecDeque<(usize, Result<I, E>)>,
+struct Visited<I, V> {
+    set: V,
+    depths: Option<BTreeMap<usize, Vec<I>>>,
+    /// Replaces `set` for deciding whether an item is queued, when set.
+    #[cfg(feature = "dashmap")]
+    concurrent: Option<ConcurrentVisited<I>>,
+}
+
+impl<I, V> Visited<I, V>
+where
+    I: Clone,
+    V: VisitedSet<I>,
+{
+    /// Marks `item` at `depth` as visited and returns whether it was not visited before.
+    #[inline]
+    fn insert(&mut self, item: &I, depth: usize) -> bool {
+        if self.set.contains(item) || !self.set.insert(item.clone()) {
+            return false;
+        }
+        if let Some(depths) = &mut self.depths {
+            depths.entry(depth).or_default().push(item.clone());
+        }
+        true
+    }
+
+    /// Starts recording the depths of visited items, starting with `items`.
+    #[inline]
+    fn track_depths<'a>(&mut self, items: impl Iterator<Item = (usize, &'a I)>)
+    where
+        I: 'a,
+    {
+        let mut depths: BTreeMap<usize, Vec<I>> = BTreeMap::new();
+        for (depth, item) in items {
+            depths.entry(depth).or_default().push(item.clone());
+        }
+        self.depths = Some(depths);
+    }
+
+    /// Removes all visited items that were first added above `min_depth`.
+    #[inline]
+    fn forget(&mut self, min_depth: usize) {
+        if let Some(depths) = &mut self.depths {
+            let keep = depths.split_off(&min_depth);
+            for item in std::mem::replace(depths, keep).into_values().flatten() {
+                self.set.remove(&item);
+            }
+        }
+    }
+}
+
+/// Takes the visited set that may still be shared with splits of the queue.
+///
+/// Returns a snapshot, when the set is still shared.
+#[cfg(feature = "rayon")]
+#[inline]
+fn unshare<I, V>(visited: Arc<RwLock<Visited<I, V>>>) -> Visited<I, V>
+where
+    I: Clone,
+    V: Clone,
+{
+    Arc::try_unwrap(visited).map_or_else(
+        |visited| visited.read().unwrap().clone(),
+        |visited| visited.into_inner().unwrap(),
+    )
+}
+
+/// A borrow of the visited set that is shared with splits of the queue.
+#[cfg(feature = "rayon")]
+struct VisitedGuard<'a, I, V>(std::sync::RwLockReadGuard<'a, Visited<I, V>>);
+
+#[cfg(feature = "rayon")]
+impl<I, V> std::ops::Deref for VisitedGuard<'_, I, V> {
+    type Target = V;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0.set
+    }
+}
+
+/// The serializable state of a queue.
+///
+/// Pending errors, callbacks and the queue backend cannot be serialized and
+/// are not part of the state, while the items of the backend are.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub(super) struct State<I, V> {
+    inner: Vec<(usize, I)>,
+    visited: V,
+    depths: Option<BTreeMap<usize, Vec<I>>>,
+    circles: CirclePolicy,
+    forget_after_depth: Option<usize>,
+    min_visited_depth: usize,
+    min_depth: usize,
+    remaining_nodes: Option<usize>,
+    yield_roots: bool,
+    max_branching: Option<usize>,
+    preserve_child_order: bool,
+    siblings: Option<VecDeque<usize>>,
+    parents: Option<Vec<(I, Option<I>)>>,
+    emitted: Option<Vec<I>>,
+    cycles: Option<VecDeque<I>>,
+    #[cfg(feature = "rand")]
+    shuffle: Option<rand_pcg::Pcg64Mcg>,
+}
+
+/// Discovered nodes and the node that discovered them, if any.
+type Parents<I> = HashMap<I, Option<I>>;
+
+/// The items of a first-in, first-out queue that did not fit into memory.
+///
+/// Errors cannot be stored by the backend and are kept in memory,
+/// along with their position among the overflowing items.
+/// Failures of the backend are converted with `on_error` and take the
+/// position of the items that were lost.
+struct Overflow<I, E> {
+    backend: Box<dyn QueueBackend<I>>,
+    on_error: fn(std::io::Error) -> E,
+    errors: VecDeque<(usize, usize, E)>,
+    pushed: usize,
+    popped: usize,
+    /// The depth of the last item, which is assigned to failures when reading back.
+    depth: usize,
+}
+
+impl<I, E> Overflow<I, E> {
+    #[inline]
+    fn new(backend: Box<dyn QueueBackend<I>>, on_error: fn(std::io::Error) -> E) -> Self {
+        Self {
+            backend,
+            on_error,
+            errors: VecDeque::new(),
+            pushed: 0,
+            popped: 0,
+            depth: 0,
+        }
+    }
+
+    /// Returns an empty overflow into a copy of the backend.
+    #[inline]
+    fn empty_clone(&self) -> Self {
+        Self::new(self.backend.boxed_clone(), self.on_error)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.pushed - self.popped
+    }
+
+    #[inline]
+    fn push(&mut self, depth: usize, item: Result<I, E>) {
+        let err = match item {
+            Ok(item) => self.backend.push_back(depth, item).err().map(self.on_error),
+            Err(err) => Some(err),
+        };
+        if let Some(err) = err {
+            self.errors.push_back((self.pushed, depth, err));
+        }
+        if self.len() == 0 {
+            self.depth = depth;
+        }
+        self.pushed += 1;
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<(usize, Result<I, E>)> {
+        // errors of items that were lost by the backend are due as well
+        if let Some(&(position, _, _)) = self.errors.front() {
+            if position <= self.popped {
+                let (_, depth, err) = self.errors.pop_front()?;
+                self.popped += 1;
+                return Some((depth, Err(err)));
+            }
+        }
+        let len = self.backend.len();
+        match self.backend.pop_front() {
+            Ok(item) => {
+                let (depth, item) = item?;
+                self.popped += 1;
+                self.depth = depth;
+                Some((depth, Ok(item)))
+            }
+            Err(err) => {
+                // the lost items are replaced by a single error
+                self.popped += len - self.backend.len();
+                Some((self.depth, Err((self.on_error)(err))))
+            }
+        }
+    }
+}
+
+impl<I, E> Clone for Overflow<I, E>
+where
+    E: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.boxed_clone(),
+            on_error: self.on_error,
+            errors: self.errors.clone(),
+            pushed: self.pushed,
+            popped: self.popped,
+            depth: self.depth,
+        }
+    }
+}
+
+impl<I, E> std::fmt::Debug for Overflow<I, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Overflow")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Appends `item` to `inner`, or to the overflow once `inner` is full.
+#[inline]
+fn push_back<I, E, D>(
+    inner: &mut VecDeque<(D, Result<I, E>)>,
+    overflow: &mut Option<Overflow<I, E>>,
+    depth: D,
+    item: Result<I, E>,
+) where
+    D: Depth,
+{
+    match overflow {
+        Some(overflow) if overflow.len() > 0 || inner.len() >= overflow.backend.memory_limit() => {
+            overflow.push(depth.into_usize(), item);
+        }
+        _ => inner.push_back((depth, item)),
+    }
+}
+
+/// Appends all `items` to `inner`, or to the overflow once `inner` is full.
+#[inline]
+fn extend<I, E, D>(
+    inner: &mut VecDeque<(D, Result<I, E>)>,
+    overflow: &mut Option<Overflow<I, E>>,
+    depth: D,
+    items: impl Iterator<Item = Result<I, E>>,
+) where
+    D: Depth,
+{
+    if overflow.is_none() {
+        inner.extend(items.map(|item| (depth, item)));
+    } else {
+        for item in items {
+            push_back(inner, overflow, depth, item);
+        }
+    }
+}
+
+/// Only splits of a queue share its visited set and budget, while clones are independent.
+#[derive(Debug)]
+pub(super) struct Queue<I, E, V = HashSet<I>, D = usize> {
+    inner: VecDeque<(D, Result<I, E>)>,
     #[cfg(feature = "rayon")]
-    visited: Arc<RwLock<HashSet<I>>>,
+    visited: Arc<RwLock<Visited<I, V>>>,
     #[cfg(not(feature = "rayon"))]
-    visited: HashSet<I>,
-    allow_circles: bool,
+    visited: Visited<I, V>,
+    circles: CirclePolicy,
+    forget_after_depth: Option<usize>,
+    min_visited_depth: usize,
+    on_skip: Option<SkipHook<I>>,
+    on_cycle: Option<SkipHook<I>>,
+    yield_if: Option<YieldFilter<I>>,
+    prune: Option<PruneHook<I>>,
+    children: Option<ChildrenHook<I, E, D>>,
+    min_depth: usize,
+    siblings: Option<VecDeque<usize>>,
+    parents: Option<Parents<I>>,
+    node_budget: Option<NodeBudget>,
+    emitted: Option<Emitted<I>>,
+    #[cfg(feature = "rand")]
+    shuffle: Option<rand_pcg::Pcg64Mcg>,
+    yield_roots: bool,
+    max_branching: Option<usize>,
+    on_panic: Option<fn(super::PanicError) -> E>,
+    preserve_child_order: bool,
+    overflow: Option<Overflow<I, E>>,
+    subtree: Option<usize>,
+    produced: usize,
+    skipped_visited: usize,
+    cycles: Option<VecDeque<I>>,
+}
+
+impl<I, E, V, D> Clone for Queue<I, E, V, D>
+where
+    I: Clone,
+    E: Clone,
+    V: Clone,
+    D: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            #[cfg(feature = "rayon")]
+            visited: Arc::new(RwLock::new(self.visited.read().unwrap().clone())),
+            #[cfg(not(feature = "rayon"))]
+            visited: self.visited.clone(),
+            circles: self.circles,
+            forget_after_depth: self.forget_after_depth,
+            min_visited_depth: self.min_visited_depth,
+            on_skip: self.on_skip.clone(),
+            on_cycle: self.on_cycle.clone(),
+            yield_if: self.yield_if.clone(),
+            prune: self.prune.clone(),
+            children: self.children.clone(),
+            min_depth: self.min_depth,
+            siblings: self.siblings.clone(),
+            parents: self.parents.clone(),
+            node_budget: self.node_budget.clone(),
+            emitted: self.emitted.clone(),
+            #[cfg(feature = "rand")]
+            shuffle: self.shuffle.clone(),
+            yield_roots: self.yield_roots,
+            max_branching: self.max_branching,
+            on_panic: self.on_panic,
+            preserve_child_order: self.preserve_child_order,
+            overflow: self.overflow.clone(),
+            subtree: self.subtree,
+            produced: self.produced,
+            skipped_visited: self.skipped_visited,
+            cycles: self.cycles.clone(),
+        }
+    }
 }
 
 #[cfg(feature = "rayon")]
 #[inline]
-fn unvisited<I>(visited: &mut Arc<RwLock<HashSet<I>>>, item: &I) -> bool
+fn unvisited<I, V>(visited: &mut Arc<RwLock<Visited<I, V>>>, item: &I, depth: usize) -> bool
 where
     I: Hash + Eq + Clone,
+    V: VisitedSet<I>,
 {
-    if visited.read().unwrap().contains(item) {
-        false
-    } else {
-        visited.write().unwrap().insert(item.clone());
-        true
+    #[cfg(feature = "dashmap")]
+    if let Some(concurrent) = &visited.read().unwrap().concurrent {
+        // the concurrent set decides races between splits without the exclusive lock
+        return !concurrent.0.contains(item) && concurrent.0.insert(item.clone());
     }
+    // checking under the shared lock first avoids contention for visited items,
+    // while inserting under the exclusive lock decides races between splits
+    !visited.read().unwrap().set.contains(item) && visited.write().unwrap().insert(item, depth)
 }
 
 #[cfg(not(feature = "rayon"))]
 #[inline]
-fn unvisited<I>(visited: &mut HashSet<I>, item: &I) -> bool
+fn unvisited<I, V>(visited: &mut Visited<I, V>, item: &I, depth: usize) -> bool
 where
-    I: Hash + Eq + Clone,
+    I: Clone,
+    V: VisitedSet<I>,
 {
-    if visited.contains(item) {
-        false
-    } else {
-        visited.insert(item.clone());
-        true
-    }
+    visited.insert(item, depth)
+}
+
+#[cfg(feature = "rayon")]
+#[inline]
+fn visited_mut<I, V>(
+    visited: &mut Arc<RwLock<Visited<I, V>>>,
+) -> std::sync::RwLockWriteGuard<'_, Visited<I, V>> {
+    visited.write().unwrap()
+}
+
+#[cfg(not(feature = "rayon"))]
+#[inline]
+fn visited_mut<I, V>(visited: &mut Visited<I, V>) -> &mut Visited<I, V> {
+    visited
+}
+
+/// Converts `depth` into the depth type `D`.
+///
+/// # Panics
+///
+/// Panics if `depth` exceeds the range of `D`.
+#[inline]
+pub(super) fn depth<D: Depth>(depth: usize) -> D {
+    D::from_usize(depth)
+        .unwrap_or_else(|| panic!("depth {depth} exceeds the range of the depth type"))
 }
 
-impl<I, E> super::Queue<I, E> for Queue<I, E>
+impl<I, E, V, D> super::Queue<I, E> for Queue<I, E, V, D>
 where
     I: Hash + Eq + Clone,
+    V: VisitedSet<I>,
+    D: Depth,
 {
     #[inline]
     fn len(&self) -> usize {
-        self.inner.len()
+        self.inner.len() + self.overflow_len()
     }
 
     #[inline]
     fn pop_back(&mut self) -> Option<(usize, Result<I, E>)> {
-        self.inner.pop_back()
+        debug_assert!(
+            self.overflow_len() == 0,
+            "cannot pop from the back of a queue with overflowing items"
+        );
+        let (depth, item) = self.inner.pop_back()?;
+        Some((depth.into_usize(), item))
     }
 
     #[inline]
     fn pop_front(&mut self) -> Option<(usize, Result<I, E>)> {
-        self.inner.pop_front()
-    }
-
-    #[inline]
-    fn split_off(&mut self, at: usize) -> Self {
-        let split = self.inner.split_off(at);
-        Self {
-            inner: split,
-            visited: self.visited.clone(),
-            allow_circles: self.allow_circles,
+        let Some((depth, item)) = self.inner.pop_front() else {
+            return self.overflow.as_mut()?.pop();
+        };
+        // move the next overflowing item into the freed slot
+        if let Some((depth, item)) = self.overflow.as_mut().and_then(Overflow::pop) {
+            self.inner.push_back((self::depth(depth), item));
         }
+        Some((depth.into_usize(), item))
     }
 
     #[inline]
     fn add(&mut self, depth: usize, item: Result<I, E>) {
+        let queued_depth: D = self::depth(depth);
         match item {
-            item if self.allow_circles => self.inner.push_back((depth, item)),
+            item if self.circles.allows(depth) => {
+                push_back(&mut self.inner, &mut self.overflow, queued_depth, item);
+            }
             Ok(item) => {
-                if unvisited(&mut self.visited, &item) {
-                    self.inner.push_back((depth, Ok(item.clone())));
+                self.forget_visited(depth);
+                // the visited set holds the only clone of the item
+                if unvisited(&mut self.visited, &item, depth) {
+                    push_back(&mut self.inner, &mut self.overflow, queued_depth, Ok(item));
+                } else {
+                    crate::trace::skipped_visited(depth);
+                    self.skipped_visited += 1;
+                    self.skip(&item, SkipReason::Cycle);
+                    if let Some(hook) = &self.on_cycle {
+                        hook.call(&item, SkipReason::Cycle);
+                    }
+                    if let Some(cycles) = &mut self.cycles {
+                        cycles.push_back(item);
+                    }
                 }
             }
-            Err(err) => self.inner.push_back((depth, Err(err))),
+            Err(err) => push_back(&mut self.inner, &mut self.overflow, queued_depth, Err(err)),
         }
-        // if self.allow_circles {
-        //     self.inner.push_back((depth, item));
-        // } else {
-        //     match item {
-        //         Ok(item) => {
-        //             if unvisited(&mut self.visited, &item) {
-        //                 self.inner.push_back((depth, Ok(item.clone())));
-        //             }
-        //         }
-        //         Err(err) => self.inner.push_back((depth, Err(err))),
-        //     }
-        // }
     }
 
     #[inline]
@@ -100,63 +659,1192 @@ where
     where
         Iter: IntoIterator<Item = Result<I, E>>,
     {
-        if self.allow_circles {
-            self.inner.extend(iter.into_iter().map(|i| (depth, i)));
+        let queued_depth: D = self::depth(depth);
+        if self.circles.allows(depth) {
+            extend(
+                &mut self.inner,
+                &mut self.overflow,
+                queued_depth,
+                iter.into_iter(),
+            );
         } else {
+            self.forget_visited(depth);
+            let on_skip = &self.on_skip;
+            let on_cycle = &self.on_cycle;
+            let skipped_visited = &mut self.skipped_visited;
+            let cycles = &mut self.cycles;
             let not_visited = iter.into_iter().filter(|c| match c {
-                Ok(item) => unvisited(&mut self.visited, item),
+                Ok(item) => {
+                    let unvisited = unvisited(&mut self.visited, item, depth);
+                    if !unvisited {
+                        crate::trace::skipped_visited(depth);
+                        *skipped_visited += 1;
+                        for hook in [on_skip, on_cycle].into_iter().flatten() {
+                            hook.call(item, SkipReason::Cycle);
+                        }
+                        if let Some(cycles) = cycles.as_mut() {
+                            cycles.push_back(item.clone());
+                        }
+                    }
+                    unvisited
+                }
                 Err(_) => true,
             });
-            self.inner.extend(not_visited.map(|i| (depth, i)));
+            extend(
+                &mut self.inner,
+                &mut self.overflow,
+                queued_depth,
+                not_visited,
+            );
         }
     }
 }
 
-impl<I, E> Queue<I, E> {
+impl<I, E, V, D> Queue<I, E, V, D>
+where
+    D: Depth,
+{
+    #[inline]
+    #[must_use]
+    pub fn new(allow_circles: bool) -> Self
+    where
+        V: Default,
+    {
+        Self::with_visited(allow_circles, V::default())
+    }
+
+    /// Creates a new queue that tracks visited items in `visited`.
     #[inline]
     #[must_use]
-    pub fn new(allow_circles: bool) -> Self {
+    pub fn with_visited(allow_circles: bool, visited: V) -> Self {
+        let visited = Visited {
+            set: visited,
+            depths: None,
+            #[cfg(feature = "dashmap")]
+            concurrent: None,
+        };
         Self {
             inner: VecDeque::new(),
             #[cfg(feature = "rayon")]
-            visited: Arc::new(RwLock::new(HashSet::new())),
+            visited: Arc::new(RwLock::new(visited)),
             #[cfg(not(feature = "rayon"))]
-            visited: HashSet::new(),
-            allow_circles,
+            visited,
+            circles: allow_circles.into(),
+            forget_after_depth: None,
+            min_visited_depth: 0,
+            on_skip: None,
+            on_cycle: None,
+            yield_if: None,
+            prune: None,
+            children: None,
+            min_depth: 0,
+            siblings: None,
+            parents: None,
+            node_budget: None,
+            emitted: None,
+            #[cfg(feature = "rand")]
+            shuffle: None,
+            yield_roots: false,
+            max_branching: None,
+            on_panic: None,
+            preserve_child_order: true,
+            overflow: None,
+            subtree: None,
+            produced: 0,
+            skipped_visited: 0,
+            cycles: None,
         }
     }
-}
-
-impl<I, E> Default for Queue<I, E> {
-    #[inline]
-    fn default() -> Self {
-        Self::new(false)
-    }
-}
-
-pub(super) struct QueueWrapper<'a, Q> {
-    inner: &'a mut Q,
-    depth: usize,
-}
 
-impl<'a, Q> QueueWrapper<'a, Q> {
+    /// Splits the queue into two at the given index.
+    /// Returns a newly allocated queue containing the elements in
+    /// the range `[at, len)`, which shares the visited set with this queue.
+    /// After the call, the original queue will be left containing
+    /// the elements `[0, at)` with its previous capacity unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > self.len()`
+    ///
+    /// Items in the overflow of a queue backend stay with this queue,
+    /// so the split may hold fewer items.
+    #[cfg(feature = "rayon")]
     #[inline]
-    pub fn new(depth: usize, queue: &'a mut Q) -> Self {
+    #[must_use]
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let split = self.inner.split_off(at.min(self.inner.len()));
         Self {
-            inner: queue,
-            depth,
+            inner: split,
+            visited: self.visited.clone(),
+            circles: self.circles,
+            forget_after_depth: self.forget_after_depth,
+            min_visited_depth: self.min_visited_depth,
+            on_skip: self.on_skip.clone(),
+            on_cycle: self.on_cycle.clone(),
+            yield_if: self.yield_if.clone(),
+            prune: self.prune.clone(),
+            children: self.children.clone(),
+            min_depth: self.min_depth,
+            // sibling groups and parents do not survive splitting
+            siblings: None,
+            parents: None,
+            node_budget: self.node_budget.as_ref().map(NodeBudget::share),
+            emitted: self.emitted.as_ref().map(Emitted::share),
+            // the split draws its own seed, so both halves shuffle differently
+            #[cfg(feature = "rand")]
+            shuffle: self.shuffle.as_mut().map(|rng| {
+                use rand::{RngCore, SeedableRng};
+                rand_pcg::Pcg64Mcg::seed_from_u64(rng.next_u64())
+            }),
+            yield_roots: self.yield_roots,
+            max_branching: self.max_branching,
+            on_panic: self.on_panic,
+            preserve_child_order: self.preserve_child_order,
+            // overflowing items are always at the back and stay with this queue
+            overflow: None,
+            subtree: None,
+            produced: 0,
+            skipped_visited: 0,
+            // cycles are only recorded for the sequential traversal
+            cycles: None,
+        }
+    }
+
+    /// Partitions the queued items round-robin into `k` independent queues
+    /// with the same options.
+    ///
+    /// Every shard owns a copy of the visited set and of the node budget,
+    /// so items that were visited so far are not queued again, but items
+    /// that are reachable from more than one shard are yielded by each of them.
+    /// Like splits, sibling groups and parents do not survive sharding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    #[inline]
+    #[must_use]
+    pub fn shard(mut self, k: usize) -> Vec<Self>
+    where
+        I: Clone,
+        V: Clone,
+    {
+        assert!(k > 0, "cannot shard a queue into zero shards");
+        let mut items: Vec<(usize, Result<I, E>)> = std::mem::take(&mut self.inner)
+            .into_iter()
+            .map(|(depth, item)| (depth.into_usize(), item))
+            .collect();
+        if let Some(overflow) = self.overflow.as_mut() {
+            items.extend(std::iter::from_fn(|| overflow.pop()));
+        }
+        let mut shards: Vec<Self> = (0..k).map(|_| self.empty_shard()).collect();
+        for (i, (depth, item)) in items.into_iter().enumerate() {
+            let shard = &mut shards[i % k];
+            push_back(
+                &mut shard.inner,
+                &mut shard.overflow,
+                self::depth(depth),
+                item,
+            );
+        }
+        shards
+    }
+
+    /// Returns an empty queue with the same options and a copy of the visited set.
+    #[inline]
+    fn empty_shard(&mut self) -> Self
+    where
+        I: Clone,
+        V: Clone,
+    {
+        Self {
+            inner: VecDeque::new(),
+            #[cfg(feature = "rayon")]
+            visited: Arc::new(RwLock::new(self.visited.read().unwrap().clone())),
+            #[cfg(not(feature = "rayon"))]
+            visited: self.visited.clone(),
+            circles: self.circles,
+            forget_after_depth: self.forget_after_depth,
+            min_visited_depth: self.min_visited_depth,
+            on_skip: self.on_skip.clone(),
+            on_cycle: self.on_cycle.clone(),
+            yield_if: self.yield_if.clone(),
+            prune: self.prune.clone(),
+            children: self.children.clone(),
+            min_depth: self.min_depth,
+            siblings: None,
+            parents: None,
+            node_budget: self.node_budget.clone(),
+            emitted: self.emitted.clone(),
+            // every shard draws its own seed
+            #[cfg(feature = "rand")]
+            shuffle: self.shuffle.as_mut().map(|rng| {
+                use rand::{RngCore, SeedableRng};
+                rand_pcg::Pcg64Mcg::seed_from_u64(rng.next_u64())
+            }),
+            yield_roots: self.yield_roots,
+            max_branching: self.max_branching,
+            on_panic: self.on_panic,
+            preserve_child_order: self.preserve_child_order,
+            overflow: self.overflow.as_ref().map(Overflow::empty_clone),
+            subtree: None,
+            produced: 0,
+            skipped_visited: 0,
+            cycles: self.cycles.as_ref().map(|_| VecDeque::new()),
+        }
+    }
+
+    /// Forget visited nodes once the queue receives nodes
+    /// more than `forget_after_depth` levels deeper.
+    ///
+    /// The depths of visited nodes are only recorded from now on,
+    /// starting with the queued nodes.
+    #[inline]
+    pub fn set_forget_after_depth(&mut self, forget_after_depth: Option<usize>)
+    where
+        I: Clone,
+        V: VisitedSet<I>,
+    {
+        self.forget_after_depth = forget_after_depth;
+        if forget_after_depth.is_some() {
+            let queued = self.inner.iter().filter_map(|(depth, item)| match item {
+                Ok(item) => Some((depth.into_usize(), item)),
+                Err(_) => None,
+            });
+            visited_mut(&mut self.visited).track_depths(queued);
+        }
+    }
+
+    /// Evicts all visited nodes more than `forget_after_depth` levels above `depth`.
+    ///
+    /// The visited set is only scanned when it may contain such nodes.
+    #[inline]
+    fn forget_visited(&mut self, depth: usize)
+    where
+        I: Clone,
+        V: VisitedSet<I>,
+    {
+        let Some(forget_after_depth) = self.forget_after_depth else {
+            return;
+        };
+        let min_depth = depth.saturating_sub(forget_after_depth);
+        if self.min_visited_depth < min_depth {
+            visited_mut(&mut self.visited).forget(min_depth);
+            self.min_visited_depth = min_depth;
+        } else {
+            self.min_visited_depth = self.min_visited_depth.min(depth);
+        }
+    }
+
+    /// Converts the queue into its serializable state.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn into_state(self) -> State<I, V>
+    where
+        I: Clone,
+        V: Clone,
+    {
+        #[cfg(feature = "rayon")]
+        let visited = unshare(self.visited);
+        #[cfg(not(feature = "rayon"))]
+        let visited = self.visited;
+        let remaining_nodes = self.node_budget.map(|budget| {
+            budget
+                .max_nodes
+                .saturating_sub(budget.count.load(Ordering::Relaxed))
+        });
+        let overflow = self
+            .overflow
+            .into_iter()
+            .flat_map(|mut overflow| std::iter::from_fn(move || overflow.pop()));
+        let mut siblings = self.siblings;
+        // the group of siblings of every queued item, front to back
+        let groups: Vec<usize> = siblings
+            .iter()
+            .flat_map(|siblings| {
+                siblings
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(group, &len)| std::iter::repeat_n(group, len))
+            })
+            .collect();
+        let mut inner = Vec::new();
+        for (idx, (depth, item)) in self
+            .inner
+            .into_iter()
+            .map(|(depth, item)| (depth.into_usize(), item))
+            .chain(overflow)
+            .enumerate()
+        {
+            match item {
+                Ok(item) => inner.push((depth, item)),
+                // dropped errors no longer count as siblings
+                Err(_) => {
+                    if let (Some(siblings), Some(&group)) = (&mut siblings, groups.get(idx)) {
+                        siblings[group] -= 1;
+                    }
+                }
+            }
+        }
+        if let Some(siblings) = &mut siblings {
+            siblings.retain(|&len| len > 0);
+        }
+        State {
+            inner,
+            visited: visited.set,
+            depths: visited.depths,
+            circles: self.circles,
+            forget_after_depth: self.forget_after_depth,
+            min_visited_depth: self.min_visited_depth,
+            min_depth: self.min_depth,
+            remaining_nodes,
+            yield_roots: self.yield_roots,
+            max_branching: self.max_branching,
+            preserve_child_order: self.preserve_child_order,
+            siblings,
+            parents: self.parents.map(|parents| parents.into_iter().collect()),
+            emitted: self
+                .emitted
+                .map(|emitted| emitted.0.lock().unwrap().iter().cloned().collect()),
+            cycles: self.cycles,
+            #[cfg(feature = "rand")]
+            shuffle: self.shuffle,
+        }
+    }
+
+    /// Restores a queue from its serializable state.
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub fn from_state(state: State<I, V>) -> Self
+    where
+        I: Hash + Eq,
+    {
+        let mut queue = Self::with_visited(false, state.visited);
+        queue.circles = state.circles;
+        queue.inner = state
+            .inner
+            .into_iter()
+            .map(|(depth, item)| (self::depth(depth), Ok(item)))
+            .collect();
+        visited_mut(&mut queue.visited).depths = state.depths;
+        queue.forget_after_depth = state.forget_after_depth;
+        queue.min_visited_depth = state.min_visited_depth;
+        queue.min_depth = state.min_depth;
+        queue.node_budget = state.remaining_nodes.map(NodeBudget::new);
+        queue.yield_roots = state.yield_roots;
+        queue.max_branching = state.max_branching;
+        queue.preserve_child_order = state.preserve_child_order;
+        queue.siblings = state.siblings;
+        queue.parents = state.parents.map(HashMap::from_iter);
+        queue.emitted = state
+            .emitted
+            .map(|emitted| Emitted(Arc::new(Mutex::new(HashSet::from_iter(emitted)))));
+        queue.cycles = state.cycles;
+        #[cfg(feature = "rand")]
+        {
+            queue.shuffle = state.shuffle;
+        }
+        queue
+    }
+
+    /// Returns the set of visited items.
+    ///
+    /// Under `rayon`, the set is locked for reading until the borrow is dropped.
+    #[inline]
+    pub fn visited(&self) -> impl std::ops::Deref<Target = V> + '_ {
+        #[cfg(feature = "rayon")]
+        return VisitedGuard(self.visited.read().unwrap());
+        #[cfg(not(feature = "rayon"))]
+        return &self.visited.set;
+    }
+
+    /// Tracks the visited items of this queue and all of its splits in a
+    /// concurrent set, starting with the queued items.
+    ///
+    /// The visited set of the queue is no longer updated, so it only holds
+    /// the items that were visited before.
+    #[cfg(feature = "dashmap")]
+    #[inline]
+    pub fn set_concurrent_visited(&mut self)
+    where
+        I: Hash + Eq + Clone,
+    {
+        let concurrent = dashmap::DashSet::new();
+        for (_, item) in &self.inner {
+            if let Ok(item) = item {
+                concurrent.insert(item.clone());
+            }
+        }
+        visited_mut(&mut self.visited).concurrent = Some(ConcurrentVisited(Arc::new(concurrent)));
+    }
+
+    /// Converts the queue into its set of visited items.
+    ///
+    /// Under `rayon`, this is a snapshot if the set is still shared with a split.
+    #[inline]
+    pub fn into_visited(self) -> V
+    where
+        I: Clone,
+        V: Clone,
+    {
+        #[cfg(feature = "rayon")]
+        return unshare(self.visited).set;
+        #[cfg(not(feature = "rayon"))]
+        return self.visited.set;
+    }
+
+    /// Returns a new queue with the same options that holds the pending nodes.
+    ///
+    /// The visited set of the new queue only contains these nodes,
+    /// and pending errors are not carried over.
+    #[inline]
+    pub fn restart(&self) -> Self
+    where
+        I: Hash + Eq + Clone,
+        V: VisitedSet<I> + Default,
+    {
+        debug_assert_eq!(
+            self.overflow_len(),
+            0,
+            "cannot restart a queue with overflowing items"
+        );
+        let mut queue = Self {
+            on_skip: self.on_skip.clone(),
+            on_cycle: self.on_cycle.clone(),
+            yield_if: self.yield_if.clone(),
+            prune: self.prune.clone(),
+            children: self.children.clone(),
+            min_depth: self.min_depth,
+            node_budget: self
+                .node_budget
+                .as_ref()
+                .map(|budget| NodeBudget::new(budget.max_nodes)),
+            #[cfg(feature = "rand")]
+            shuffle: self.shuffle.clone(),
+            yield_roots: self.yield_roots,
+            max_branching: self.max_branching,
+            on_panic: self.on_panic,
+            preserve_child_order: self.preserve_child_order,
+            // only fresh queues are restarted, so the backend is still empty
+            overflow: self.overflow.as_ref().map(Overflow::empty_clone),
+            circles: self.circles,
+            ..Self::new(false)
+        };
+        queue.set_forget_after_depth(self.forget_after_depth);
+        for (depth, item) in &self.inner {
+            if let Ok(item) = item {
+                super::Queue::add(&mut queue, depth.into_usize(), Ok(item.clone()));
+            }
+        }
+        queue
+    }
+
+    /// Clears the queue and its visited set and starts over with `roots`.
+    ///
+    /// All options are kept, and the allocated capacity is reused.
+    /// Shuffling continues with the current state of the PRNG.
+    #[inline]
+    pub fn reset<R>(&mut self, roots: R)
+    where
+        I: Hash + Eq + Clone,
+        V: VisitedSet<I> + Default,
+        R: IntoIterator<Item = I>,
+    {
+        self.inner.clear();
+        if let Some(overflow) = &mut self.overflow {
+            while overflow.pop().is_some() {}
+        }
+        visited_mut(&mut self.visited).set.clear();
+        if let Some(depths) = &mut visited_mut(&mut self.visited).depths {
+            depths.clear();
+        }
+        self.min_visited_depth = 0;
+        if let Some(siblings) = &mut self.siblings {
+            siblings.clear();
+        }
+        if let Some(budget) = &self.node_budget {
+            budget.count.store(0, Ordering::Relaxed);
+        }
+        if let Some(emitted) = &self.emitted {
+            emitted.0.lock().unwrap().clear();
+        }
+        if let Some(parents) = &mut self.parents {
+            parents.clear();
+        }
+        self.subtree = None;
+        self.skipped_visited = 0;
+        for root in roots {
+            if let Some(parents) = &mut self.parents {
+                parents.insert(root.clone(), None);
+            }
+            super::Queue::add(self, 0, Ok(root));
+        }
+    }
+
+    /// Shuffles the items added by each expansion, using a PRNG seeded with `seed`.
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn set_shuffle(&mut self, seed: u64) {
+        use rand::SeedableRng;
+        self.shuffle = Some(rand_pcg::Pcg64Mcg::seed_from_u64(seed));
+    }
+
+    /// Shuffles the items from index `start` on, if shuffling is enabled.
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn shuffle_from(&mut self, start: usize) {
+        use rand::seq::SliceRandom;
+        if let Some(rng) = &mut self.shuffle {
+            if self.inner.len() > start + 1 {
+                self.inner.make_contiguous()[start..].shuffle(rng);
+            }
+        }
+    }
+
+    /// Reverses the items from index `start` on, if the child order is preserved.
+    ///
+    /// Items are popped from the back in depth-first order, so reversing the
+    /// children of a node yields them in the order they were added.
+    #[inline]
+    pub fn reverse_from(&mut self, start: usize) {
+        if !self.preserve_child_order || self.inner.len() <= start {
+            return;
+        }
+        let (mut front, mut back) = (start, self.inner.len() - 1);
+        while front < back {
+            self.inner.swap(front, back);
+            front += 1;
+            back -= 1;
+        }
+    }
+
+    /// Sets whether the children of a node are reversed by [`reverse_from`](Self::reverse_from).
+    #[inline]
+    pub fn set_preserve_child_order(&mut self, preserve_child_order: bool) {
+        self.preserve_child_order = preserve_child_order;
+    }
+
+    /// Sets the callback that is notified whenever a node is skipped.
+    #[inline]
+    pub fn set_on_skip(&mut self, hook: SkipHook<I>) {
+        self.on_skip = Some(hook);
+    }
+
+    /// Sets the callback that is notified whenever an already visited node is skipped.
+    #[inline]
+    pub fn set_on_cycle(&mut self, hook: SkipHook<I>) {
+        self.on_cycle = Some(hook);
+    }
+
+    /// Sets the predicate that decides whether a node is yielded.
+    #[inline]
+    pub fn set_yield_if<F>(&mut self, predicate: F)
+    where
+        F: Fn(&I, usize) -> bool + Send + Sync + 'static,
+    {
+        self.yield_if = Some(YieldFilter(Arc::new(predicate)));
+    }
+
+    /// Sets the predicate that decides whether the children of a node are skipped.
+    #[inline]
+    pub fn set_prune<F>(&mut self, predicate: F)
+    where
+        F: FnMut(&I, usize) -> bool + Send + 'static,
+    {
+        self.prune = Some(PruneHook(Arc::new(Mutex::new(predicate))));
+    }
+
+    /// Sets the closure that produces the children of an item in place of the item itself.
+    #[inline]
+    pub fn set_children<F>(&mut self, children: F)
+    where
+        F: FnMut(&I, D) -> super::NodeIter<I, E> + Send + 'static,
+    {
+        self.children = Some(ChildrenHook(Arc::new(Mutex::new(children))));
+    }
+
+    /// Returns the closure that produces the children of an item, if any.
+    #[inline]
+    pub fn children(&self) -> Option<ChildrenHook<I, E, D>> {
+        self.children.clone()
+    }
+
+    /// Returns whether the children of `item` at `depth` should be skipped.
+    #[inline]
+    pub fn prunes(&self, item: &I, depth: usize) -> bool {
+        self.prune.as_ref().is_some_and(|predicate| {
+            let mut predicate = predicate.0.lock().unwrap_or_else(PoisonError::into_inner);
+            (*predicate)(item, depth)
+        })
+    }
+
+    /// Sets the minimum depth of yielded nodes.
+    #[inline]
+    pub fn set_min_depth(&mut self, min_depth: usize) {
+        self.min_depth = min_depth;
+    }
+
+    /// Sets the maximum number of yielded items.
+    #[inline]
+    pub fn set_max_nodes(&mut self, max_nodes: Option<usize>) {
+        self.node_budget = max_nodes.map(NodeBudget::new);
+    }
+
+    /// Returns whether all yielded items of the budget have been used up.
+    #[inline]
+    pub fn exhausted(&self) -> bool {
+        self.node_budget
+            .as_ref()
+            .is_some_and(|budget| budget.count.load(Ordering::Relaxed) >= budget.max_nodes)
+    }
+
+    /// Returns the depth of the first item, or [`None`] if the queue is empty.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    #[inline]
+    pub fn front_depth(&self) -> Option<usize> {
+        self.inner.front().map(|(depth, _)| depth.into_usize())
+    }
+
+    /// Returns the depth of the last item, or [`None`] if the queue is empty.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn back_depth(&self) -> Option<usize> {
+        if self.overflow_len() > 0 {
+            // the last item is in the backend
+            return None;
+        }
+        self.inner.back().map(|(depth, _)| depth.into_usize())
+    }
+
+    /// Returns the first item, or [`None`] if the queue is empty.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    #[inline]
+    pub fn front(&self) -> Option<&Result<I, E>> {
+        self.inner.front().map(|(_, item)| item)
+    }
+
+    /// Returns the last item, or [`None`] if the queue is empty.
+    ///
+    /// [`None`]: type@std::option::Option::None
+    #[inline]
+    pub fn back(&self) -> Option<&Result<I, E>> {
+        self.inner.back().map(|(_, item)| item)
+    }
+
+    /// Shortens the queue to its first `len` items and drops the rest.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.inner.truncate(len);
+    }
+
+    /// Returns whether no option inspects individual items, so that every
+    /// queued item is yielded and items below the depth limit are expanded.
+    #[inline]
+    pub fn is_plain(&self) -> bool {
+        self.on_skip.is_none()
+            && self.yield_if.is_none()
+            && self.prune.is_none()
+            && self.min_depth == 0
+            && self.siblings.is_none()
+            && self.parents.is_none()
+            && self.node_budget.is_none()
+            && self.emitted.is_none()
+            && !self.yield_roots
+    }
+
+    /// Returns the number of items that were not queued because they were already visited.
+    #[inline]
+    pub fn skipped_visited(&self) -> usize {
+        self.skipped_visited
+    }
+
+    /// Accounts for an item that is about to be yielded.
+    ///
+    /// Returns `None` if the budget is used up and the item must not be yielded,
+    /// or whether items remain in the budget after this one.
+    #[inline]
+    pub fn count_node(&self) -> Option<bool> {
+        let Some(budget) = &self.node_budget else {
+            return Some(true);
+        };
+        let count = budget.count.fetch_add(1, Ordering::Relaxed) + 1;
+        if count > budget.max_nodes {
+            None
+        } else {
+            Some(count < budget.max_nodes)
+        }
+    }
+
+    /// Returns the bounds on the number of items that remain to be yielded.
+    ///
+    /// Queued items are only a lower bound if all of them are yielded,
+    /// that is when no yield filter is set and items are not unique.
+    /// The queued root items are only expanded and not yielded,
+    /// unless roots are yielded.
+    #[inline]
+    pub fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.node_budget.as_ref().map(|budget| {
+            budget
+                .max_nodes
+                .saturating_sub(budget.count.load(Ordering::Relaxed))
+        });
+        let len = self.inner.len() + self.overflow_len();
+        if len == 0 || remaining == Some(0) {
+            return (0, Some(0));
+        }
+        let lower = if self.min_depth == 0 && self.yield_if.is_none() && self.emitted.is_none() {
+            let roots = if self.yield_roots {
+                0
+            } else {
+                self.inner
+                    .iter()
+                    .take_while(|(depth, _)| *depth == D::ZERO)
+                    .count()
+            };
+            len - roots
+        } else {
+            0
+        };
+        (
+            remaining.map_or(lower, |remaining| lower.min(remaining)),
+            remaining,
+        )
+    }
+
+    /// Returns the exact number of items that remain to be yielded,
+    /// when every node has `branching_factor` children up to `max_depth`.
+    ///
+    /// Returns `None` when items in a queue backend cannot be inspected,
+    /// or when the number exceeds the range of [`usize`].
+    ///
+    /// [`usize`]: primitive@usize
+    #[inline]
+    pub fn exact_len<F>(&self, max_depth: usize, branching_factor: F) -> Option<usize>
+    where
+        F: Fn(&I) -> usize,
+    {
+        if self.overflow_len() > 0 {
+            return None;
+        }
+        self.inner.iter().try_fold(0usize, |len, (depth, item)| {
+            let depth = depth.into_usize();
+            let Ok(item) = item else {
+                return len.checked_add(1);
+            };
+            let yielded = usize::from(depth > 0 || self.yield_roots);
+            // the descendants form a geometric series b + b^2 + ... + b^levels
+            let branching_factor = branching_factor(item);
+            let levels = max_depth.saturating_sub(depth);
+            let descendants = match branching_factor {
+                0 => 0,
+                1 => levels,
+                // overflows within a few levels for large depths
+                _ => {
+                    (0..levels)
+                        .try_fold((0usize, 1usize), |(sum, level), _| {
+                            let level = level.checked_mul(branching_factor)?;
+                            Some((sum.checked_add(level)?, level))
+                        })?
+                        .0
+                }
+            };
+            len.checked_add(yielded)?.checked_add(descendants)
+        })
+    }
+
+    /// Returns whether `item` at `depth` should be yielded.
+    ///
+    /// When items are unique, the item is marked as yielded.
+    #[inline]
+    pub fn yields(&self, item: &I, depth: usize) -> bool
+    where
+        I: Hash + Eq + Clone,
+    {
+        depth >= self.min_depth
+            && self
+                .yield_if
+                .as_ref()
+                .is_none_or(|predicate| (predicate.0)(item, depth))
+            && self
+                .emitted
+                .as_ref()
+                .is_none_or(|emitted| emitted.insert(item))
+    }
+
+    /// Starts recording the items that are not queued because they were
+    /// already visited.
+    #[inline]
+    pub fn set_record_cycles(&mut self) {
+        self.cycles.get_or_insert_with(VecDeque::new);
+    }
+
+    /// Pops the oldest recorded item that was not queued because it was
+    /// already visited.
+    #[inline]
+    pub fn pop_cycle(&mut self) -> Option<I> {
+        self.cycles.as_mut()?.pop_front()
+    }
+
+    /// Starts yielding the root items at depth 0.
+    #[inline]
+    pub fn set_yield_roots(&mut self) {
+        self.yield_roots = true;
+    }
+
+    /// Returns whether the root items are yielded.
+    #[inline]
+    pub fn yields_roots(&self) -> bool {
+        self.yield_roots
+    }
+
+    /// Appends the items beyond the memory limit of `backend` to it,
+    /// converting its failures into errors with `on_error`.
+    #[inline]
+    pub fn set_backend(
+        &mut self,
+        backend: Box<dyn QueueBackend<I>>,
+        on_error: fn(std::io::Error) -> E,
+    ) {
+        self.overflow = Some(Overflow::new(backend, on_error));
+    }
+
+    /// Returns the number of items in the backend or pending errors.
+    #[inline]
+    fn overflow_len(&self) -> usize {
+        self.overflow.as_ref().map_or(0, Overflow::len)
+    }
+
+    /// Appends `item` at `depth` as its own group of siblings,
+    /// bypassing the visited set.
+    ///
+    /// Forgets the children of the last yielded item, see [`skip_subtree`](Self::skip_subtree).
+    #[inline]
+    pub fn inject(&mut self, depth: usize, item: I) {
+        push_back(
+            &mut self.inner,
+            &mut self.overflow,
+            self::depth(depth),
+            Ok(item),
+        );
+        if let Some(siblings) = &mut self.siblings {
+            siblings.push_back(1);
+        }
+        self.subtree = None;
+    }
+
+    /// Remembers that the children of the item that is about to be yielded
+    /// were queued from index `start` on.
+    #[inline]
+    pub fn set_subtree(&mut self, start: Option<usize>) {
+        self.subtree = start;
+    }
+
+    /// Remembers the number of children that the expansion of the item that
+    /// is about to be yielded produced, including already visited children.
+    #[inline]
+    pub fn set_produced(&mut self, produced: usize) {
+        self.produced = produced;
+    }
+
+    /// Returns the number of children that the last yielded item produced.
+    #[inline]
+    pub fn produced(&self) -> usize {
+        self.produced
+    }
+
+    /// Removes the queued children of the last yielded item, if they are
+    /// still at the back of the queue, and removes them from the visited set.
+    #[inline]
+    pub fn skip_subtree(&mut self)
+    where
+        I: Clone,
+        V: VisitedSet<I>,
+    {
+        let Some(start) = self.subtree.take() else {
+            return;
+        };
+        if start >= self.inner.len() {
+            return;
+        }
+        if let Some(siblings) = &mut self.siblings {
+            siblings.pop_back();
+        }
+        for (depth, item) in self.inner.drain(start..) {
+            if let (false, Ok(item)) = (self.circles.allows(depth.into_usize()), item) {
+                visited_mut(&mut self.visited).set.remove(&item);
+            }
+        }
+    }
+
+    /// Sets which items are not tracked as visited.
+    #[inline]
+    pub fn set_circle_policy(&mut self, circles: CirclePolicy) {
+        self.circles = circles;
+    }
+
+    /// Limits the number of children that are added for every item.
+    #[inline]
+    pub fn set_max_branching(&mut self, max_branching: Option<usize>) {
+        self.max_branching = max_branching;
+    }
+
+    /// Returns the maximum number of children that are added for every item.
+    #[inline]
+    pub fn max_branching(&self) -> Option<usize> {
+        self.max_branching
+    }
+
+    /// Converts panics while adding the children of an item into errors with `on_panic`.
+    #[inline]
+    pub fn set_on_panic(&mut self, on_panic: Option<fn(super::PanicError) -> E>) {
+        self.on_panic = on_panic;
+    }
+
+    /// Returns the conversion of panics while adding children into errors, if any.
+    #[inline]
+    pub fn on_panic(&self) -> Option<fn(super::PanicError) -> E> {
+        self.on_panic
+    }
+
+    /// Starts yielding every item at most once.
+    #[inline]
+    pub fn set_unique(&mut self)
+    where
+        I: Hash + Eq + Clone,
+    {
+        self.emitted = Some(Emitted::new());
+    }
+
+    /// Removes the queued items for which `predicate` returns `true`, and
+    /// returns them in queue order.
+    ///
+    /// The removed items stay visited.
+    /// Errors and items in the overflow of a queue backend are kept.
+    #[inline]
+    pub fn drain_matching<F>(&mut self, mut predicate: F) -> Vec<I>
+    where
+        F: FnMut(&I) -> bool,
+    {
+        // the group of siblings of every queued item, front to back
+        let groups: Option<Vec<usize>> = self.siblings.as_ref().map(|siblings| {
+            siblings
+                .iter()
+                .enumerate()
+                .flat_map(|(group, &len)| std::iter::repeat_n(group, len))
+                .collect()
+        });
+        let mut drained = Vec::new();
+        let queued = std::mem::take(&mut self.inner);
+        for (idx, (depth, item)) in queued.into_iter().enumerate() {
+            match item {
+                Ok(item) if predicate(&item) => {
+                    let group = groups.as_ref().and_then(|groups| groups.get(idx));
+                    if let (Some(siblings), Some(&group)) = (&mut self.siblings, group) {
+                        siblings[group] -= 1;
+                    }
+                    drained.push(item);
+                }
+                item => self.inner.push_back((depth, item)),
+            }
+        }
+        if let Some(siblings) = &mut self.siblings {
+            siblings.retain(|&len| len > 0);
+        }
+        drained
+    }
+
+    /// Starts tracking the number of remaining siblings of the queued items.
+    #[inline]
+    pub fn track_siblings(&mut self) {
+        self.siblings = Some(VecDeque::new());
+    }
+
+    /// Starts recording the parent of each queued item.
+    ///
+    /// The queued root items do not have a parent.
+    #[inline]
+    pub fn track_parents(&mut self)
+    where
+        I: Hash + Eq + Clone,
+    {
+        assert!(
+            self.overflow.is_none(),
+            "parents cannot be tracked with a queue backend"
+        );
+        let roots = self.inner.iter().filter_map(|(depth, item)| match item {
+            Ok(item) if *depth == D::ZERO => Some((item.clone(), None)),
+            _ => None,
+        });
+        self.parents = Some(roots.collect());
+    }
+
+    /// Records all items added since the queue had length `len` as
+    /// siblings and as children of `parent`.
+    ///
+    /// Items that already have a parent keep it, so the first discovery wins.
+    #[inline]
+    pub fn end_children(&mut self, len: usize, parent: &I)
+    where
+        I: Hash + Eq + Clone,
+    {
+        let added = (self.inner.len() + self.overflow_len()).saturating_sub(len);
+        if let Some(siblings) = &mut self.siblings {
+            if added > 0 {
+                siblings.push_back(added);
+            }
+        }
+        if let Some(parents) = &mut self.parents {
+            for (_, item) in self.inner.range(len..) {
+                if let Ok(item) = item {
+                    parents
+                        .entry(item.clone())
+                        .or_insert_with(|| Some(parent.clone()));
+                }
+            }
+        }
+    }
+
+    /// Returns whether a parent was recorded for `item`, i.e. whether `item`
+    /// is a root or was queued since parents are recorded.
+    #[inline]
+    pub fn discovered(&self, item: &I) -> bool
+    where
+        I: Hash + Eq,
+    {
+        self.parents
+            .as_ref()
+            .is_some_and(|parents| parents.contains_key(item))
+    }
+
+    /// Returns the path from the root to `item` along the recorded parents.
+    ///
+    /// The path starts with the root and ends with `item`.
+    #[inline]
+    pub fn path_to(&self, item: I) -> Vec<I>
+    where
+        I: Hash + Eq + Clone,
+    {
+        let mut path = vec![item];
+        if let Some(parents) = &self.parents {
+            while let Some(Some(parent)) = path.last().and_then(|item| parents.get(item)) {
+                path.push(parent.clone());
+            }
+        }
+        path.reverse();
+        path
+    }
+
+    /// Accounts for the item that was popped from the back and returns
+    /// whether it was the last of its siblings.
+    #[inline]
+    pub fn pop_sibling_back(&mut self) -> bool {
+        let Some(siblings) = &mut self.siblings else {
+            return false;
+        };
+        match siblings.back_mut() {
+            Some(1) => siblings.pop_back().is_some(),
+            Some(remaining) => {
+                *remaining -= 1;
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Accounts for the item that was popped from the front and returns
+    /// whether it was the last of its siblings.
+    #[inline]
+    pub fn pop_sibling_front(&mut self) -> bool {
+        let Some(siblings) = &mut self.siblings else {
+            return false;
+        };
+        match siblings.front_mut() {
+            Some(1) => siblings.pop_front().is_some(),
+            Some(remaining) => {
+                *remaining -= 1;
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Notifies the skip callback, if any, that `item` was skipped.
+    #[inline]
+    pub fn skip(&self, item: &I, reason: SkipReason) {
+        if let Some(hook) = &self.on_skip {
+            hook.call(item, reason);
         }
     }
 }
 
-impl<I, E, Q> super::ExtendQueue<I, E> for QueueWrapper<'_, Q>
+impl<I, E, V, D> Default for Queue<I, E, V, D>
+where
+    V: Default,
+    D: Depth,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+pub(super) struct QueueWrapper<'a, Q> {
+    inner: &'a mut Q,
+    depth: usize,
+    remaining: Option<usize>,
+    produced: usize,
+}
+
+impl<'a, Q> QueueWrapper<'a, Q> {
+    #[inline]
+    pub fn new(depth: usize, queue: &'a mut Q) -> Self {
+        Self {
+            inner: queue,
+            depth,
+            remaining: None,
+            produced: 0,
+        }
+    }
+
+    /// Returns the number of items that were added within the limit,
+    /// including items that the queue did not accept as already visited.
+    #[inline]
+    pub fn produced(&self) -> usize {
+        self.produced
+    }
+
+    /// Ignores all items that are added beyond the first `max_items`.
+    #[inline]
+    #[must_use]
+    pub fn limit(mut self, max_items: Option<usize>) -> Self {
+        self.remaining = max_items;
+        self
+    }
+}
+
+impl<I, E, V, D> super::ExtendQueue<I, E> for QueueWrapper<'_, Queue<I, E, V, D>>
 where
-    Q: super::Queue<I, E>,
     I: Hash + Eq + Clone,
+    V: VisitedSet<I>,
+    D: Depth,
 {
     #[inline]
     fn add(&mut self, item: Result<I, E>) {
-        self.inner.add(self.depth, item);
+        match &mut self.remaining {
+            Some(0) => {
+                if let Ok(item) = &item {
+                    self.inner.skip(item, SkipReason::BreadthLimit);
+                }
+                return;
+            }
+            Some(remaining) => *remaining -= 1,
+            None => {}
+        }
+        self.produced += 1;
+        super::Queue::add(self.inner, self.depth, item);
     }
 
     #[inline]
@@ -164,6 +1852,19 @@ where
     where
         Iter: IntoIterator<Item = Result<I, E>>,
     {
-        self.inner.add_all(self.depth, iter);
+        let mut added = 0;
+        let mut iter = iter.into_iter();
+        let limited = iter.by_ref().take(self.remaining.unwrap_or(usize::MAX));
+        super::Queue::add_all(self.inner, self.depth, limited.inspect(|_| added += 1));
+        self.produced += added;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= added;
+            // items beyond the limit are only produced to report them
+            if *remaining == 0 && self.inner.on_skip.is_some() {
+                for item in iter.flatten() {
+                    self.inner.skip(&item, SkipReason::BreadthLimit);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,124 @@
+//! Optional [`tracing`] instrumentation of the traversals.
+//!
+//! Without the `tracing` feature, all helpers are no-ops that compile away.
+//!
+//! [`tracing`]: mod@tracing
+
+/// A span around the expansion of the children of a node.
+#[cfg(all(feature = "sync", feature = "tracing"))]
+pub(crate) struct ExpandSpan(tracing::span::EnteredSpan);
+
+#[cfg(all(feature = "sync", feature = "tracing"))]
+impl ExpandSpan {
+    /// Enters a span for expanding children at `depth`.
+    #[inline]
+    pub fn enter(depth: usize) -> Self {
+        let span = tracing::trace_span!("children", depth, children = tracing::field::Empty);
+        Self(span.entered())
+    }
+
+    /// Records the number of children that were produced.
+    #[inline]
+    pub fn children(&self, children: usize) {
+        self.0.record("children", children);
+    }
+}
+
+#[cfg(all(feature = "sync", not(feature = "tracing")))]
+pub(crate) struct ExpandSpan;
+
+#[cfg(all(feature = "sync", not(feature = "tracing")))]
+impl ExpandSpan {
+    #[inline(always)]
+    pub fn enter(_depth: usize) -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    pub fn children(&self, _children: usize) {}
+}
+
+/// Emits an event for a node at `depth` that was skipped because it was already visited.
+#[cfg(any(feature = "sync", feature = "async"))]
+#[inline(always)]
+pub(crate) fn skipped_visited(depth: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(depth, "skipped visited node");
+    #[cfg(not(feature = "tracing"))]
+    let _ = depth;
+}
+
+/// Instruments a future that resolves the children of a node at `depth`.
+#[cfg(feature = "async")]
+#[inline(always)]
+pub(crate) fn instrument_children<F>(
+    fut: F,
+    depth: usize,
+) -> impl std::future::Future<Output = F::Output>
+where
+    F: std::future::Future,
+{
+    #[cfg(feature = "tracing")]
+    {
+        tracing::Instrument::instrument(fut, tracing::trace_span!("children", depth))
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = depth;
+        fut
+    }
+}
+
+#[cfg(all(test, feature = "tracing", feature = "sync"))]
+mod tests {
+    use crate::sync::Dfs;
+    use crate::utils::test::Node;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    /// Counts the spans and events that it receives.
+    #[derive(Default, Clone)]
+    struct Counter {
+        next_id: Arc<AtomicU64>,
+        spans: Arc<AtomicUsize>,
+        events: Arc<AtomicUsize>,
+    }
+
+    impl tracing::Subscriber for Counter {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            self.spans.fetch_add(1, Ordering::SeqCst);
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {
+            self.events.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn test_trace_expansions_and_skips() {
+        let counter = Counter::default();
+        let nodes = tracing::subscriber::with_default(counter.clone(), || {
+            Dfs::<Node>::new(0, 3, false).count()
+        });
+        similar_asserts::assert_eq!(nodes, 3);
+        // the root and all nodes above the depth limit are expanded
+        similar_asserts::assert_eq!(counter.spans.load(Ordering::SeqCst), 3);
+        // every expansion produces a visited duplicate
+        similar_asserts::assert_eq!(counter.events.load(Ordering::SeqCst), 3);
+    }
+}
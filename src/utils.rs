@@ -26,7 +26,8 @@ pub mod test {
     #[error("error")]
     pub struct Error;
 
-    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Node(pub usize);
 
     impl From<usize> for Node {
@@ -35,10 +36,69 @@ pub mod test {
         }
     }
 
+    /// The depth of the leaves of [`LeafNode`] trees.
+    pub const LEAF_DEPTH: usize = 3;
+
+    /// A node that panics when its children are requested at [`LEAF_DEPTH`].
+    #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+    pub struct LeafNode(pub usize);
+
+    impl From<usize> for LeafNode {
+        fn from(depth: usize) -> Self {
+            Self(depth)
+        }
+    }
+
+    impl LeafNode {
+        /// Returns the two children at `depth`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if this node is a leaf.
+        pub fn children(&self, depth: usize) -> [Self; 2] {
+            assert!(
+                depth <= LEAF_DEPTH,
+                "requested children of leaf {self:?} at depth {depth}"
+            );
+            [Self(depth), Self(depth)]
+        }
+    }
+
+    /// A node of a directed graph given by its adjacency lists.
+    #[derive(Clone, Copy, Debug)]
+    pub struct GraphNode {
+        pub id: usize,
+        pub edges: &'static [&'static [usize]],
+    }
+
+    impl GraphNode {
+        pub fn new(id: usize, edges: &'static [&'static [usize]]) -> Self {
+            Self { id, edges }
+        }
+
+        pub fn neighbors(&self) -> impl Iterator<Item = Self> {
+            let edges = self.edges;
+            edges[self.id].iter().map(move |&id| Self { id, edges })
+        }
+    }
+
+    crate::dedup_key!(GraphNode { id });
+
+    /// A node of an infinite binary tree, identified by its index in level order.
+    #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct TreeNode(pub usize);
+
+    impl TreeNode {
+        pub fn children(&self) -> [Self; 2] {
+            [Self(2 * self.0 + 1), Self(2 * self.0 + 2)]
+        }
+    }
+
     #[cfg(feature = "async")]
     #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
     pub mod r#async {
-        use crate::r#async::{Node, NodeStream};
+        use crate::r#async::{ExtendQueue, FastNode, Node, NodeStream};
         use async_trait::async_trait;
         use futures::{stream, StreamExt};
         use std::sync::Arc;
@@ -65,12 +125,109 @@ pub mod test {
                 Ok(Box::pin(stream.boxed()))
             }
         }
+
+        #[async_trait]
+        impl Node for super::GraphNode {
+            type Error = super::Error;
+
+            async fn children(
+                self: Arc<Self>,
+                _depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                // complete expansions out of order
+                let delay = 10 * (3 - self.id % 3) as u64;
+                sleep(Duration::from_millis(delay)).await;
+                let nodes = self.neighbors().map(Result::Ok).collect::<Vec<_>>();
+                Ok(Box::pin(stream::iter(nodes).boxed()))
+            }
+        }
+
+        #[async_trait]
+        impl Node for super::TreeNode {
+            type Error = super::Error;
+
+            async fn children(
+                self: Arc<Self>,
+                _depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                let nodes = super::TreeNode::children(&self).map(Result::Ok);
+                Ok(Box::pin(stream::iter(nodes).boxed()))
+            }
+        }
+
+        #[async_trait]
+        impl Node for super::LeafNode {
+            type Error = super::Error;
+
+            async fn children(
+                self: Arc<Self>,
+                depth: usize,
+            ) -> Result<NodeStream<Self, Self::Error>, Self::Error> {
+                let nodes = super::LeafNode::children(&self, depth).map(Result::Ok);
+                sleep(Duration::from_millis(10)).await;
+                Ok(Box::pin(stream::iter(nodes).boxed()))
+            }
+        }
+
+        #[async_trait]
+        impl FastNode for super::LeafNode {
+            type Error = super::Error;
+
+            async fn add_children<Q>(
+                self: Arc<Self>,
+                depth: usize,
+                queue: &mut Q,
+            ) -> Result<(), Self::Error>
+            where
+                Q: ExtendQueue<Self, Self::Error> + Send,
+            {
+                let nodes = super::LeafNode::children(&self, depth).map(Result::Ok);
+                sleep(Duration::from_millis(10)).await;
+                queue.add_all(nodes);
+                Ok(())
+            }
+        }
+
+        #[async_trait]
+        impl FastNode for super::Node {
+            type Error = super::Error;
+
+            async fn add_children<Q>(
+                self: Arc<Self>,
+                depth: usize,
+                queue: &mut Q,
+            ) -> Result<(), Self::Error>
+            where
+                Q: ExtendQueue<Self, Self::Error> + Send,
+            {
+                // we want to test with multiple await points
+
+                sleep(Duration::from_millis(50)).await;
+                queue.add(Ok(Self(depth)));
+
+                sleep(Duration::from_millis(50)).await;
+                queue.add_all([Ok(Self(depth))]);
+                Ok(())
+            }
+        }
     }
 
     #[cfg(feature = "sync")]
     #[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
     pub mod sync {
-        use crate::sync::{ExtendQueue, FastNode, Node, NodeIter};
+        use crate::sync::{BidiNode, BoundedNode, ExtendQueue, FastNode, Node, NodeIter};
+
+        impl From<crate::sync::PanicError> for super::Error {
+            fn from(_: crate::sync::PanicError) -> Self {
+                Self
+            }
+        }
+
+        impl From<std::io::Error> for super::Error {
+            fn from(_: std::io::Error) -> Self {
+                Self
+            }
+        }
 
         impl Node for super::Node {
             type Error = super::Error;
@@ -82,6 +239,84 @@ pub mod test {
             }
         }
 
+        impl Node for super::LeafNode {
+            type Error = super::Error;
+
+            fn children(&self, depth: usize) -> NodeIter<Self, Self::Error> {
+                let nodes = super::LeafNode::children(self, depth).map(Result::Ok);
+                Ok(Box::new(nodes.into_iter()))
+            }
+        }
+
+        impl FastNode for super::LeafNode {
+            type Error = super::Error;
+
+            fn add_children<E>(&self, depth: usize, queue: &mut E) -> Result<(), Self::Error>
+            where
+                E: ExtendQueue<Self, Self::Error>,
+            {
+                queue.add_all(super::LeafNode::children(self, depth).map(Result::Ok));
+                Ok(())
+            }
+        }
+
+        impl Node for super::TreeNode {
+            type Error = super::Error;
+
+            fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+                let nodes = super::TreeNode::children(self).map(Result::Ok);
+                Ok(Box::new(nodes.into_iter()))
+            }
+        }
+
+        impl BoundedNode for super::TreeNode {
+            fn branching_factor(&self) -> usize {
+                2
+            }
+        }
+
+        impl FastNode for super::TreeNode {
+            type Error = super::Error;
+
+            fn add_children<E>(&self, _depth: usize, queue: &mut E) -> Result<(), Self::Error>
+            where
+                E: ExtendQueue<Self, Self::Error>,
+            {
+                queue.add_all(super::TreeNode::children(self).map(Result::Ok));
+                Ok(())
+            }
+        }
+
+        impl Node for super::GraphNode {
+            type Error = super::Error;
+
+            fn children(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+                Ok(Box::new(self.neighbors().map(Result::Ok)))
+            }
+        }
+
+        impl BidiNode for super::GraphNode {
+            fn parents(&self, _depth: usize) -> NodeIter<Self, Self::Error> {
+                let (id, edges) = (self.id, self.edges);
+                let parents = (0..edges.len())
+                    .filter(move |&parent| edges[parent].contains(&id))
+                    .map(move |parent| Ok(Self::new(parent, edges)));
+                Ok(Box::new(parents))
+            }
+        }
+
+        impl FastNode for super::GraphNode {
+            type Error = super::Error;
+
+            fn add_children<E>(&self, _depth: usize, queue: &mut E) -> Result<(), Self::Error>
+            where
+                E: ExtendQueue<Self, Self::Error>,
+            {
+                queue.add_all(self.neighbors().map(Result::Ok));
+                Ok(())
+            }
+        }
+
         impl FastNode for super::Node {
             type Error = super::Error;
 